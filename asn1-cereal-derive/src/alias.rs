@@ -5,7 +5,8 @@ use logging_enabled;
 
 // FIXME: Documenation
 
-pub fn asn1_alias_info_constructed(ast: &syn::MacroInput) -> Tokens {
+/// Get the type that an alias wraps.
+pub fn alias_inner_ty(ast: &syn::MacroInput) -> &syn::Ty {
   let fields = if let syn::Body::Struct(ref body) = ast.body {
     match *body {
       syn::VariantData::Tuple(ref fields)
@@ -15,12 +16,16 @@ pub fn asn1_alias_info_constructed(ast: &syn::MacroInput) -> Tokens {
   } else {
     panic!("Expected a struct for alias");
   };
-  let inner_ty = &fields[0].ty;
+  &fields[0].ty
+}
+
+pub fn asn1_alias_info_constructed(ast: &syn::MacroInput) -> Tokens {
+  let inner_ty = alias_inner_ty(ast);
 
   quote! {
     fn asn1_constructed<E: ::asn1_cereal::BerEncRules>(e: E) -> bool {
       let tag = Self::asn1_tag();
-      if E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit ||
+      if e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit ||
          tag.is_none() {
 
         <#inner_ty as ::asn1_cereal::Asn1Info>::asn1_constructed(e)
@@ -49,7 +54,7 @@ pub fn ber_alias_serialize(ast: &syn::MacroInput) -> Tokens {
 
         // FIXME: We should be conditionally setting the constructed flag.
 
-        if E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
+        if e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
           #implicit_msg
           self.0.serialize_value(e, writer)
         }  else {
@@ -62,6 +67,7 @@ pub fn ber_alias_serialize(ast: &syn::MacroInput) -> Tokens {
 
 pub fn ber_alias_deserialize(ast: &syn::MacroInput) -> Tokens {
   let name = &ast.ident;
+  let inner_ty = alias_inner_ty(ast);
   let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
   let logging = logging_enabled(&ast);
 
@@ -73,9 +79,15 @@ pub fn ber_alias_deserialize(ast: &syn::MacroInput) -> Tokens {
 
   quote! {
     impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      // With an implicit tag, the inner value is decoded in its place.
+      fn _reads_indef_end<E: ::asn1_cereal::BerEncRules>(e: E) -> bool {
+        e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit &&
+          <#inner_ty as ::asn1_cereal::BerDeserialize>::_reads_indef_end(e)
+      }
+
       fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=std::io::Result<u8>>>
           (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
-        if E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
+        if e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
           #implicit_msg
           Ok(#name(try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len))))
         }  else {
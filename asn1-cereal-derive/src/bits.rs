@@ -0,0 +1,118 @@
+use quote::Tokens;
+use syn;
+
+use field;
+
+// FIXME: Documenation
+
+/// Build an expression that creates this struct from a BitString named `bits`.
+pub fn bits_to_struct(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let fields = bits_fields(ast);
+
+  let assignments: Vec<_> = fields.iter().map(|&(ref ident, bit)| {
+    quote!(#ident: bits.get(#bit))
+  }).collect();
+
+  quote! {
+    #name {
+      #(#assignments),*
+    }
+  }
+}
+
+/// Build statements that create a BitString named `bits` from this struct.
+/// Named bit lists don't encode trailing zero bits, unless `sized` is true and
+/// they're needed for the size constraint in `constraints`.
+pub fn struct_to_bits(ast: &syn::MacroInput, sized: bool) -> Tokens {
+  let set_bits: Vec<_> = bits_fields(ast).iter().map(|&(ref ident, bit)| {
+    quote!(bits.set(#bit, self.#ident);)
+  }).collect();
+  let resize = if sized {
+    quote!(
+      if let Some(lower) = constraints.size.and_then(|s| s.lower) {
+        if (bits.len() as i128) < lower {
+          bits.resize(lower as u64);
+        }
+      }
+    )
+  } else {
+    quote!()
+  };
+
+  quote!(
+    let mut bits = ::asn1_cereal::BitString::new();
+    #(#set_bits)*
+    bits.trim();
+    #resize
+  )
+}
+
+/// Get the identifier and bit number for each field of a named bit struct.
+pub fn bits_fields(ast: &syn::MacroInput) -> Vec<(syn::Ident, u64)> {
+  let name = &ast.ident;
+
+  let fields = if let syn::Body::Struct(syn::VariantData::Struct(ref fields)) = ast.body {
+    fields
+  } else {
+    panic!("Expected a struct with named fields for bits, but type {} was not", name);
+  };
+
+  fields.iter().map(|v| {
+    let ident = v.ident.clone().expect("Requires named idents");
+    let field = field::Field::parse(&v.attrs);
+    let bit = field.bit.unwrap_or_else(||
+      panic!("Field {} of {} requires a bit number, eg. #[asn1(bit=0)]", ident, name)
+    );
+    (ident, bit)
+  }).collect()
+}
+
+pub fn ber_bits_serialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let bits = struct_to_bits(ast, false);
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerSerialize for #name #ty_generics #where_clause {
+      fn serialize_value<E: ::asn1_cereal::BerEncRules, W: ::std::io::Write>
+          (&self, e: E, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        #bits
+        ::asn1_cereal::BerSerialize::serialize_value(&bits, e, writer)
+      }
+    }
+  }
+}
+
+pub fn ber_bits_deserialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+  let build = bits_to_struct(ast);
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      fn _deserialize_with_tag<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, tag: ::asn1_cereal::tag::Tag, len: ::asn1_cereal::tag::Len) ->
+          Option<Result<Self, ::asn1_cereal::err::DecodeError>> {
+        // Handle the constructed form of BIT STRING, the primitive form uses
+        // the normal behaviour.
+        if ::asn1_cereal::ber::serial::segment::is_constructed_form(
+             e, <Self as ::asn1_cereal::Asn1Info>::asn1_tag(), tag) {
+          Some(::asn1_cereal::BitString::deserialize_constructed(e, reader, len)
+               .map(|bits| #build))
+        } else {
+          None
+        }
+      }
+
+      fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        // Any unknown bits are ignored.
+        let bits: ::asn1_cereal::BitString =
+          try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len));
+        Ok(#build)
+      }
+    }
+  }
+}
@@ -1,8 +1,26 @@
 use quote::Tokens;
 use syn;
 
+use field;
+
 // FIXME: Documenation
 
+/// Get the tag for a unit variant, which is encoded as a NULL. This is the tag
+/// given by the variant's attributes, or the universal NULL tag.
+pub fn unit_variant_tag(v: &syn::Variant) -> Tokens {
+  match field::Field::parse(&v.attrs).tag {
+    Some(tag) => quote!(
+      ::asn1_cereal::tag::Tag {
+        constructed: false,
+        .. #tag.unwrap()
+      }
+    ),
+    None => quote!(
+      <() as ::asn1_cereal::Asn1Info>::asn1_tag().unwrap()
+    ),
+  }
+}
+
 pub fn ber_choice_serialize(ast: &syn::MacroInput) -> Tokens {
   let name = &ast.ident;
   let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
@@ -15,9 +33,20 @@ pub fn ber_choice_serialize(ast: &syn::MacroInput) -> Tokens {
 
   let match_pattern: Vec<_> = fields.iter().map(|v| {
     let ident = &v.ident;
-    quote!(#name::#ident(ref item))
+    if v.data == syn::VariantData::Unit {
+      quote!(#name::#ident)
+    } else {
+      quote!(#name::#ident(ref item))
+    }
   }).collect();
-  let match_action: Vec<_> = fields.iter().map(|_v| {
+  let match_action: Vec<_> = fields.iter().map(|v| {
+    // A unit variant is encoded as a NULL, with an optional tag.
+    if v.data == syn::VariantData::Unit {
+      let tag = unit_variant_tag(v);
+      return quote! {{
+        try!(::asn1_cereal::tag::write_taglen(#tag, ::asn1_cereal::tag::Len::Def(0), writer));
+      }};
+    }
     // TODO: Handle asn1 attributes
     quote! {
       if false {
@@ -30,7 +59,7 @@ pub fn ber_choice_serialize(ast: &syn::MacroInput) -> Tokens {
         // FIXME: Does implicit tagging + CHOICE mean always explicit tag?
         // Maybe not with context-specific tags?
         // FIXME: We should be conditionally setting the constructed flag.
-        // if E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
+        // if e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
         //   try!(item.serialize_value(e, writer));
         // } else {
           try!(item.serialize_enc(e, writer));
@@ -64,28 +93,42 @@ pub fn ber_choice_deserialize(ast: &syn::MacroInput) -> Tokens {
 
   let match_pattern: Vec<_> = fields.iter().map(|v| {
     let ident = &v.ident;
+    if v.data == syn::VariantData::Unit {
+      let unit_tag = unit_variant_tag(v);
+      return quote! {
+        tag @ ::asn1_cereal::tag::Tag { .. } if ::asn1_cereal::ber::enc::tag_matches(e, tag, #unit_tag)
+      };
+    }
     let inner = if let syn::VariantData::Tuple(ref fields) = v.data {
       fields[0].clone()
     } else {
       panic!("Expected a tuple, but type {} variant {} was not a tuple", name, ident);
     };
     // TODO: Handle asn1 attributes
+    // A type without a tag (like RawElement) will match any tag. A tolerated
+    // deviation is reported when the alternative checks its own tag.
     quote! {
       tag @ ::asn1_cereal::tag::Tag { .. }
-        if tag == <#inner as ::asn1_cereal::Asn1Info>::asn1_tag()
-                    .expect("All types used for CHOICE must have a defined tag")
+        if <#inner as ::asn1_cereal::Asn1Info>::asn1_tag()
+             .map_or(true, |t| ::asn1_cereal::ber::enc::tag_may_match(e, tag, t))
     }
   }).collect();
 
   let match_action: Vec<_> = fields.iter().map(|v| {
     let ident = &v.ident;
+    if v.data == syn::VariantData::Unit {
+      return quote! {{
+        let () = try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len));
+        Ok(#name::#ident)
+      }};
+    }
     // TODO: Handle asn1 attributes
     quote! {
       Ok(#name::#ident(
         // FIXME: Does implicit tagging + CHOICE mean always explicit tag?
         // Maybe not with context-specific tags?
         // FIXME: We should be conditionally setting the constructed flag.
-        // if E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
+        // if e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit {
         //   try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len))
         // } else {
         try!(::asn1_cereal::BerDeserialize::deserialize_with_tag(e, reader, tag, len))
@@ -102,7 +145,7 @@ pub fn ber_choice_deserialize(ast: &syn::MacroInput) -> Tokens {
         let mut res = ||
           match tag {
             #(#match_pattern => #match_action),*,
-            _ => panic!("Choice: Unknown tag {}", tag),
+            _ => Err(::asn1_cereal::err::DecodeError::TagTypeMismatch),
           };
         Some(res())
       }
@@ -110,7 +153,7 @@ pub fn ber_choice_deserialize(ast: &syn::MacroInput) -> Tokens {
       fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
           (e: E, reader: &mut I, _len: ::asn1_cereal::tag::Len) ->
           Result<Self, ::asn1_cereal::err::DecodeError> {
-        let (tag, len) = ::asn1_cereal::tag::read_taglen(reader)?;
+        let (tag, len) = ::asn1_cereal::tag::read_taglen_enc(e, reader)?;
         Self::_deserialize_with_tag(e, reader, tag, len).unwrap()
       }
     }
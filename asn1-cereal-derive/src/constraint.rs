@@ -0,0 +1,37 @@
+use quote::Tokens;
+use syn;
+
+/// Parse a value or size constraint, like `0..255`, `MIN..0`, `1..MAX` or `8`,
+/// into a Range. An extension marker can follow, like `0..7, ...`.
+pub fn parse_range(value: &str) -> Tokens {
+  let (bounds, extensible) = match value.find(',') {
+    Some(i) if value[i + 1..].trim() == "..." => (&value[..i], true),
+    Some(_) => panic!("Failed to parse constraint {}, expected an extension marker after ','", value),
+    None => (value, false),
+  };
+  let (lower, upper) = match bounds.find("..") {
+    Some(i) => (parse_bound(&bounds[..i], "MIN"), parse_bound(&bounds[i + 2..], "MAX")),
+    None => (parse_bound(bounds, ""), parse_bound(bounds, "")),
+  };
+
+  quote!(
+    ::asn1_cereal::constraint::Range {
+      lower: #lower,
+      upper: #upper,
+      extensible: #extensible,
+    }
+  )
+}
+
+/// Parse a bound of a range, which is a number, or unbounded (MIN or MAX).
+fn parse_bound(bound: &str, unbounded: &str) -> Tokens {
+  let bound = bound.trim();
+  if bound == unbounded {
+    return quote!(None);
+  }
+  let value: i128 = bound.parse().unwrap_or_else(|_|
+    panic!("Failed to parse constraint bound {}", bound)
+  );
+  let value: syn::Ident = format!("{}i128", value).into();
+  quote!(Some(#value))
+}
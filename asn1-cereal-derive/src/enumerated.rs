@@ -0,0 +1,123 @@
+use quote::Tokens;
+use syn;
+
+use field;
+use is_extensible;
+
+// FIXME: Documenation
+
+/// Get the value of an explicit discriminant, eg. `A = 3` or `B = -1`.
+fn discriminant_value(expr: &syn::ConstExpr) -> Option<i64> {
+  match *expr {
+    syn::ConstExpr::Lit(syn::Lit::Int(v, _)) => Some(v as i64),
+    syn::ConstExpr::Unary(syn::UnOp::Neg, ref inner) =>
+      discriminant_value(inner).map(|v| -v),
+    syn::ConstExpr::Paren(ref inner) => discriminant_value(inner),
+    _ => None,
+  }
+}
+
+/// The parsed variants of an ENUMERATED. Each named variant has a value, and
+/// there may be a catch-all variant for unknown values.
+pub struct Variants {
+  pub named: Vec<(syn::Ident, i64)>,
+  pub unknown: Option<syn::Ident>,
+}
+
+pub fn enumerated_variants(ast: &syn::MacroInput) -> Variants {
+  let name = &ast.ident;
+  let extensible = is_extensible(ast);
+
+  let variants = if let syn::Body::Enum(ref variants) = ast.body {
+    variants
+  } else {
+    panic!("Expected an enum, but type {} was not an enum", name);
+  };
+
+  let mut named = Vec::new();
+  let mut unknown = None;
+  // Like Rust discriminants, a variant without a value is one more than the last.
+  let mut next = 0i64;
+  for v in variants {
+    match v.data {
+      syn::VariantData::Unit => {},
+      syn::VariantData::Tuple(ref fields) if fields.len() == 1 && extensible => {
+        if unknown.is_some() {
+          panic!("Enum {} can only have one variant for unknown values", name);
+        }
+        unknown = Some(v.ident.clone());
+        continue;
+      },
+      _ => panic!("Variant {} of {} must be a unit variant, or a catch-all Unknown(i64) \
+                   variant for an extensible enum", v.ident, name),
+    }
+
+    let field = field::Field::parse(&v.attrs);
+    let value = match (field.value, v.discriminant.as_ref()) {
+      (Some(value), _) => value,
+      (None, Some(expr)) => discriminant_value(expr).unwrap_or_else(||
+        panic!("Couldn't understand discriminant of {}, use #[asn1(value=N)] instead", v.ident)
+      ),
+      (None, None) => next,
+    };
+    next = value + 1;
+    named.push((v.ident.clone(), value));
+  }
+
+  Variants {
+    named: named,
+    unknown: unknown,
+  }
+}
+
+pub fn ber_enumerated_serialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+  let variants = enumerated_variants(ast);
+
+  let mut arms: Vec<_> = variants.named.iter().map(|&(ref ident, value)| {
+    quote!(#name::#ident => #value)
+  }).collect();
+  if let Some(ref ident) = variants.unknown {
+    arms.push(quote!(#name::#ident(value) => value));
+  }
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerSerialize for #name #ty_generics #where_clause {
+      fn serialize_value<E: ::asn1_cereal::BerEncRules, W: ::std::io::Write>
+          (&self, e: E, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        let value: i64 = match *self {
+          #(#arms),*
+        };
+        ::asn1_cereal::BerSerialize::serialize_value(&value, e, writer)
+      }
+    }
+  }
+}
+
+pub fn ber_enumerated_deserialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+  let variants = enumerated_variants(ast);
+
+  let arms: Vec<_> = variants.named.iter().map(|&(ref ident, value)| {
+    quote!(#value => Ok(#name::#ident))
+  }).collect();
+  let unknown = match variants.unknown {
+    Some(ref ident) => quote!(value => Ok(#name::#ident(value))),
+    None => quote!(_ => Err(::asn1_cereal::err::DecodeError::UnknownEnumerated)),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        let value: i64 = try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len));
+        match value {
+          #(#arms,)*
+          #unknown
+        }
+      }
+    }
+  }
+}
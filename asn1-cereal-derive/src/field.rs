@@ -5,6 +5,14 @@ pub struct Field {
   pub tag: Option<::quote::Tokens>,
   pub optional: bool,
   pub default: bool,
+  pub default_value: Option<::quote::Tokens>,
+  pub bit: Option<u64>,
+  pub value: Option<i64>,
+  pub time: Option<String>,
+  pub defined_by: Option<syn::Ident>,
+  pub range: Option<::quote::Tokens>,
+  pub size: Option<::quote::Tokens>,
+  pub name: Option<String>,
 }
 
 impl Field {
@@ -14,6 +22,14 @@ impl Field {
     let mut tag = None;
     let mut optional = false;
     let mut default = false;
+    let mut default_value = None;
+    let mut bit = None;
+    let mut value = None;
+    let mut time = None;
+    let mut defined_by = None;
+    let mut range = None;
+    let mut size = None;
+    let mut name = None;
 
     for attr in attrs.into_iter().find(|e| e.name() == "asn1") {
       let items = if let syn::MetaItem::List(_, ref items) = attr.value {
@@ -31,6 +47,12 @@ impl Field {
             syn::MetaItem::Word(ref ident)
               if ident == "default" =>
                 default = true,
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
+              if ident == "default" => {
+                let expr = syn::parse_token_trees(value).expect("Failed to parse default");
+                default = true;
+                default_value = Some(quote!(#(#expr)*));
+              },
             syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
               if ident == "tag" => {
                 let _tag = ::tag::parse_tag(value.as_bytes());
@@ -39,6 +61,30 @@ impl Field {
                 }
                 tag = Some(_tag.unwrap().1)
               },
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Int(value, _))
+              if ident == "bit" =>
+                bit = Some(value),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Int(_value, _))
+              if ident == "value" =>
+                value = Some(_value as i64),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref _value, _))
+              if ident == "value" =>
+                value = Some(_value.parse().expect("Failed to parse value")),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
+              if ident == "time" =>
+                time = Some(value.clone()),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
+              if ident == "defined_by" =>
+                defined_by = Some(value.as_str().into()),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
+              if ident == "range" =>
+                range = Some(::constraint::parse_range(value)),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
+              if ident == "size" =>
+                size = Some(::constraint::parse_range(value)),
+            syn::MetaItem::NameValue(ref ident, syn::Lit::Str(ref value, _))
+              if ident == "name" =>
+                name = Some(value.clone()),
             _ => (),
           };
         }
@@ -49,6 +95,108 @@ impl Field {
       tag: tag,
       optional: optional,
       default: default,
+      default_value: default_value,
+      bit: bit,
+      value: value,
+      time: time,
+      defined_by: defined_by,
+      range: range,
+      size: size,
+      name: name,
+    }
+  }
+
+  /// Get the ASN.1 identifier of this field, which is the `name` attribute,
+  /// or the Rust identifier.
+  pub fn identifier(&self, ident: &syn::Ident) -> String {
+    self.name.clone().unwrap_or_else(|| ident.to_string())
+  }
+
+  /// Get the tag of this component, which is given by the `tag` attribute, or
+  /// a context-specific tag numbered by the component's position.
+  pub fn base_tag(&self, index: u64) -> ::quote::Tokens {
+    match self.tag {
+      Some(ref tag) => quote!(#tag.unwrap()),
+      None => quote!(
+        ::asn1_cereal::tag::Tag {
+          class: ::asn1_cereal::tag::Class::ContextSpecific,
+          tagnum: #index,
+          constructed: true,
+        }
+      ),
+    }
+  }
+
+  /// Get the tag of this component in a SEQUENCE or SET, where the constructed
+  /// flag depends on `is_implicit` and the type `info_ty` it's encoded as.
+  pub fn component_tag(&self, info_ty: &::quote::Tokens, index: u64) -> ::quote::Tokens {
+    let base = self.base_tag(index);
+    quote!(
+      ::asn1_cereal::tag::Tag {
+        constructed:
+          if is_implicit {
+            <#info_ty as ::asn1_cereal::Asn1Info>::asn1_constructed(e)
+          } else {
+            true
+          },
+        .. #base
+      }
+    )
+  }
+
+  /// Get the value of a DEFAULT field with the type `ty`, which is the
+  /// expression given by `default="<expr>"`, or the `Default` value of the type.
+  pub fn default_value(&self, ty: &syn::Ty) -> ::quote::Tokens {
+    match self.default_value {
+      Some(ref expr) => quote!({
+        let value: #ty = #expr;
+        value
+      }),
+      None => quote!(<#ty as ::std::default::Default>::default()),
+    }
+  }
+
+  /// Get the constraints of this field, where `ty` is the type it's encoded as.
+  /// The `range` and `size` attributes replace the constraints of the type.
+  pub fn constraints(&self, ty: &::quote::Tokens) -> ::quote::Tokens {
+    let base = quote!(<#ty as ::asn1_cereal::Asn1Info>::asn1_constraints());
+    if self.range.is_none() && self.size.is_none() {
+      return base;
+    }
+    let value = match self.range {
+      Some(ref range) => quote!(Some(#range)),
+      None => quote!(base.value),
+    };
+    let size = match self.size {
+      Some(ref size) => quote!(Some(#size)),
+      None => quote!(base.size),
+    };
+    quote!({
+      let base = #base;
+      ::asn1_cereal::constraint::Constraints {
+        value: #value,
+        size: #size,
+        .. base
+      }
+    })
+  }
+
+  /// For a field with a time attribute, get the ASN.1 time type it's encoded as,
+  /// and the names of the `Asn1Time` methods that convert to and from it.
+  pub fn time_conversion(&self) -> Option<(::quote::Tokens, syn::Ident, syn::Ident)> {
+    match self.time.as_ref().map(|t| t.as_str()) {
+      Some("utc") => Some((
+        quote!(::asn1_cereal::UtcTime),
+        "to_utc_time".into(),
+        "from_utc_time".into(),
+      )),
+      Some("generalized") => Some((
+        quote!(::asn1_cereal::GeneralizedTime),
+        "to_generalized_time".into(),
+        "from_generalized_time".into(),
+      )),
+      Some(time) => panic!("Unknown time type {}, expected \"utc\" or \"generalized\"", time),
+      None => None,
     }
   }
 }
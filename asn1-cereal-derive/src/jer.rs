@@ -0,0 +1,322 @@
+use quote::Tokens;
+use syn;
+
+use field;
+use is_extensible;
+use bits::{bits_to_struct, struct_to_bits};
+use per::{field_info, choice_variants};
+use set::set_fields;
+use xer::enumerated_named;
+
+/// Generate the JerSerialize implementation for a form.
+pub fn jer_serialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" | "set" => jer_sequence_encode(ast),
+    "choice" => jer_choice_encode(ast),
+    "enumerated" => jer_enumerated_encode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      ::asn1_cereal::jer::serial::seq_of::encode_seq_of(self.into_iter(), writer)
+    ),
+    "alias" => quote!(
+      ::asn1_cereal::JerSerialize::jer_encode(&self.0, constraints, writer)
+    ),
+    "null" => quote!(
+      ::asn1_cereal::JerSerialize::jer_encode(&(), constraints, writer)
+    ),
+    "bits" => jer_bits_encode(ast),
+    _ => panic!("Unknown serialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::JerSerialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn jer_encode<W: ::std::io::Write>(&self, constraints: ::asn1_cereal::constraint::Constraints,
+          writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        #body
+      }
+    }
+  }
+}
+
+/// Generate the JerDeserialize implementation for a form.
+pub fn jer_deserialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" | "set" => jer_sequence_decode(ast),
+    "choice" => jer_choice_decode(ast),
+    "enumerated" => jer_enumerated_decode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      let items: Vec<_> = try!(::asn1_cereal::jer::serial::seq_of::decode_seq_of(reader));
+      Ok(items.into_iter().collect())
+    ),
+    "alias" => quote!(
+      Ok(#name(try!(::asn1_cereal::JerDeserialize::jer_decode(constraints, reader))))
+    ),
+    "null" => quote!(
+      try!(<() as ::asn1_cereal::JerDeserialize>::jer_decode(constraints, reader));
+      Ok(#name)
+    ),
+    "bits" => {
+      let build = bits_to_struct(ast);
+      quote!(
+        // Any unknown bits are ignored.
+        let bits: ::asn1_cereal::BitString = try!(::asn1_cereal::JerDeserialize::jer_decode(
+          <::asn1_cereal::BitString as ::asn1_cereal::Asn1Info>::asn1_constraints(), reader));
+        Ok(#build)
+      )
+    },
+    _ => panic!("Unknown deserialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::JerDeserialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn jer_decode<I: Iterator<Item=::std::io::Result<u8>>>
+          (constraints: ::asn1_cereal::constraint::Constraints, reader: &mut ::asn1_cereal::jer::json::JsonReader<I>)
+          -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        #body
+      }
+    }
+  }
+}
+
+fn jer_sequence_encode(ast: &syn::MacroInput) -> Tokens {
+  let fields = set_fields(ast);
+
+  let actions: Vec<_> = fields.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = field_info(v, &field, true);
+    let constraints = field.constraints(&info_ty);
+    let encode = quote!(
+      #convert
+      try!(::asn1_cereal::jer::json::write_key(writer, fields[#i], &mut first));
+      try!(::asn1_cereal::JerSerialize::jer_encode(value, #constraints, writer));
+    );
+    // An absent OPTIONAL component has no member.
+    if field.optional {
+      quote!(
+        if let Some(ref value) = self.#ident {
+          #encode
+        }
+      )
+    } else {
+      quote!({
+        let value = &self.#ident;
+        #encode
+      })
+    }
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    let mut first = true;
+    try!(writer.write_all(b"{"));
+    #(#actions)*
+    try!(writer.write_all(b"}"));
+    Ok(())
+  )
+}
+
+fn jer_sequence_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let fields = set_fields(ast);
+  let extensible = is_extensible(ast);
+
+  let mut init = Vec::new();
+  let mut actions = Vec::new();
+  let mut assignments = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let f_ident: syn::Ident = format!("field_{}", ident.as_ref().expect("Requires named idents")).into();
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = field_info(v, &field, false);
+    let constraints = field.constraints(&info_ty);
+
+    // An OPTIONAL field is already an Option.
+    if field.optional {
+      init.push(quote!(let mut #f_ident: #ty = None;));
+      assignments.push(quote!(#ident: #f_ident));
+    } else if field.default {
+      let default = field.default_value(&v.ty);
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(#ident: #f_ident.unwrap_or_else(|| #default)));
+    } else {
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(
+        #ident: try!(#f_ident.ok_or(::asn1_cereal::err::DecodeError::MissingComponent))
+      ));
+    }
+    actions.push(quote!(
+      if key == fields[#i] {
+        if #f_ident.is_some() {
+          return Err(::asn1_cereal::err::DecodeError::DuplicateComponent);
+        }
+        #f_ident = Some({
+          let value = try!(::asn1_cereal::JerDeserialize::jer_decode(#constraints, reader));
+          #convert
+        });
+      } else
+    ));
+  }
+
+  // Members of an unknown extension are skipped.
+  let unknown = if extensible {
+    quote!(try!(reader.skip_value());)
+  } else {
+    quote!(return Err(::asn1_cereal::err::DecodeError::InvalidJson);)
+  };
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    #(#init)*
+    try!(reader.start_object());
+    while let Some(key) = try!(reader.next_key()) {
+      #(#actions)* {
+        #unknown
+      }
+    }
+    Ok(#name {
+      #(#assignments),*
+    })
+  )
+}
+
+fn jer_choice_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+
+  let actions: Vec<_> = variants.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        quote!(
+          #name::#ident(ref item) => {
+            try!(::asn1_cereal::jer::json::write_key(writer, fields[#i], &mut true));
+            try!(::asn1_cereal::JerSerialize::jer_encode(
+              item, <#inner as ::asn1_cereal::Asn1Info>::asn1_constraints(), writer));
+          }
+        )
+      },
+      // A unit variant is a NULL.
+      _ => quote!(
+        #name::#ident => {
+          try!(::asn1_cereal::jer::json::write_key(writer, fields[#i], &mut true));
+          try!(writer.write_all(b"null"));
+        }
+      ),
+    }
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    try!(writer.write_all(b"{"));
+    match *self {
+      #(#actions),*
+    }
+    try!(writer.write_all(b"}"));
+    Ok(())
+  )
+}
+
+fn jer_choice_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+
+  let actions: Vec<_> = variants.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    let value = match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        quote!(
+          #name::#ident(try!(::asn1_cereal::JerDeserialize::jer_decode(
+            <#inner as ::asn1_cereal::Asn1Info>::asn1_constraints(), reader)))
+        )
+      },
+      _ => quote!({
+        try!(<() as ::asn1_cereal::JerDeserialize>::jer_decode(
+          ::asn1_cereal::constraint::Constraints::default(), reader));
+        #name::#ident
+      }),
+    };
+    quote!(
+      if key == fields[#i] {
+        #value
+      } else
+    )
+  }).collect();
+
+  // The object must have exactly one member.
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    try!(reader.start_object());
+    let key = try!(try!(reader.next_key()).ok_or(::asn1_cereal::err::DecodeError::InvalidJson));
+    let value = #(#actions)* {
+      return Err(::asn1_cereal::err::DecodeError::InvalidJson);
+    };
+    if try!(reader.next_key()).is_some() {
+      return Err(::asn1_cereal::err::DecodeError::InvalidJson);
+    }
+    Ok(value)
+  )
+}
+
+fn jer_enumerated_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let unknown: Vec<_> = choice_variants(ast).iter().filter(|v| v.data != syn::VariantData::Unit).map(|v| {
+    let ident = &v.ident;
+    quote!(
+      #name::#ident(..) =>
+        return Err(::asn1_cereal::err::EncodeError::Custom("Unknown ENUMERATED values can't be encoded with JER"))
+    )
+  }).collect();
+  let arms: Vec<_> = enumerated_named(ast).into_iter().map(|(i, ident)| {
+    quote!(#name::#ident => fields[#i])
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    let identifier = match *self {
+      #(#arms,)*
+      #(#unknown,)*
+    };
+    try!(::asn1_cereal::jer::json::write_string(writer, identifier));
+    Ok(())
+  )
+}
+
+fn jer_enumerated_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let actions: Vec<_> = enumerated_named(ast).into_iter().map(|(i, ident)| {
+    quote!(
+      if identifier == fields[#i] {
+        Ok(#name::#ident)
+      } else
+    )
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    let identifier = try!(reader.read_string());
+    #(#actions)* {
+      Err(::asn1_cereal::err::DecodeError::UnknownEnumerated)
+    }
+  )
+}
+
+fn jer_bits_encode(ast: &syn::MacroInput) -> Tokens {
+  let bits = struct_to_bits(ast, false);
+
+  quote!(
+    #bits
+    ::asn1_cereal::JerSerialize::jer_encode(
+      &bits, <::asn1_cereal::BitString as ::asn1_cereal::Asn1Info>::asn1_constraints(), writer)
+  )
+}
@@ -15,18 +15,39 @@ extern crate nom;
 
 use proc_macro::TokenStream;
 
-use ::alias::{asn1_alias_info_constructed, ber_alias_serialize, ber_alias_deserialize};
+use ::enumerated::{ber_enumerated_serialize, ber_enumerated_deserialize};
+use ::null::{ber_null_serialize, ber_null_deserialize};
+use ::bits::{ber_bits_serialize, ber_bits_deserialize};
+use ::alias::{alias_inner_ty, asn1_alias_info_constructed, ber_alias_serialize, ber_alias_deserialize};
 use ::choice::{ber_choice_serialize, ber_choice_deserialize};
 use ::seq::{ber_sequence_serialize, ber_sequence_deserialize};
-use ::seq_of::{ber_sequence_of_serialize, ber_sequence_of_deserialize};
+use ::set::{ber_set_serialize, ber_set_deserialize};
+use ::seq_of::{ber_sequence_of_serialize, ber_sequence_of_deserialize, ber_set_of_serialize,
+               ber_set_of_deserialize};
 use ::tag::parse_tag;
+use ::constraint::parse_range;
+use ::per::{per_serialize, per_deserialize};
+use ::oer::{oer_serialize, oer_deserialize};
+use ::xer::{xer_serialize, xer_deserialize};
+use ::jer::{jer_serialize, jer_deserialize};
+use ::schema::asn1_schema;
 
 mod alias;
+mod bits;
 mod choice;
+mod constraint;
+mod enumerated;
 mod field;
+mod jer;
+mod null;
+mod oer;
+mod per;
+mod schema;
 mod seq;
 mod seq_of;
+mod set;
 mod tag;
+mod xer;
 
 #[proc_macro_derive(Asn1Info, attributes(asn1))]
 pub fn asn1_info(input: TokenStream) -> TokenStream {
@@ -38,6 +59,8 @@ pub fn asn1_info(input: TokenStream) -> TokenStream {
   let mut asn1_type = ast.ident.as_ref().to_owned();
   let mut _logging = false;
   let mut form = None;
+  let mut range = None;
+  let mut size = None;
 
   // Parse attributes.
   for attr in &ast.attrs.iter().find(|e| e.name() == "asn1") {
@@ -61,6 +84,8 @@ pub fn asn1_info(input: TokenStream) -> TokenStream {
               },
               "form" => form = Some(value.clone()),
               "asn1_type" => asn1_type = value.clone(),
+              "range" => range = Some(parse_range(value)),
+              "size" => size = Some(parse_range(value)),
               _ => (),
             };
           },
@@ -83,14 +108,86 @@ pub fn asn1_info(input: TokenStream) -> TokenStream {
     asn1_constructed = asn1_alias_info_constructed(&ast);
   }
 
+  // Constraints of this type, where an alias uses the constraints of the
+  // inner type by default.
+  let base_constraints = if form == Some("alias".into()) {
+    let inner_ty = alias_inner_ty(&ast);
+    quote!(<#inner_ty as ::asn1_cereal::Asn1Info>::asn1_constraints())
+  } else {
+    quote!(::asn1_cereal::constraint::Constraints::default())
+  };
+  let extensible = is_extensible(&ast);
+  let asn1_constraints = if range.is_some() || size.is_some() || extensible {
+    let value = match range {
+      Some(range) => quote!(Some(#range)),
+      None => quote!(base.value),
+    };
+    let size = match size {
+      Some(size) => quote!(Some(#size)),
+      None => quote!(base.size),
+    };
+    quote! {
+      fn asn1_constraints() -> ::asn1_cereal::constraint::Constraints {
+        let base = #base_constraints;
+        ::asn1_cereal::constraint::Constraints {
+          value: #value,
+          size: #size,
+          extensible: #extensible || base.extensible,
+        }
+      }
+    }
+  } else if form == Some("alias".into()) {
+    quote! {
+      fn asn1_constraints() -> ::asn1_cereal::constraint::Constraints {
+        #base_constraints
+      }
+    }
+  } else {
+    quote!()
+  };
+
+  // The ASN.1 identifiers of the fields or variants, for encodings like XER.
+  let identifiers: Vec<String> = match ast.body {
+    syn::Body::Struct(syn::VariantData::Struct(ref fields)) => fields.iter().map(|f| {
+      field::Field::parse(&f.attrs).identifier(f.ident.as_ref().expect("Requires named idents"))
+    }).collect(),
+    syn::Body::Enum(ref variants) => variants.iter().map(|v| {
+      field::Field::parse(&v.attrs).identifier(&v.ident)
+    }).collect(),
+    _ => Vec::new(),
+  };
+  let asn1_fields = if identifiers.is_empty() {
+    quote!()
+  } else {
+    quote! {
+      fn asn1_fields() -> &'static [&'static str] {
+        &[#(#identifiers),*]
+      }
+    }
+  };
+
   if form == Some("choice".into()) && tag.is_some() {
     panic!("A choice must not have a tag defined");
   }
 
+  // A unit struct is a NULL, which is always primitive.
+  if let syn::Body::Struct(syn::VariantData::Unit) = ast.body {
+    form = Some("null".into());
+  }
+  if form == Some("null".into()) {
+    tag = tag.map(|t| quote!(
+      #t.map(|t| ::asn1_cereal::tag::Tag { constructed: false, .. t })
+    ));
+  }
+
+  // Default tag number, and whether it's constructed.
   let default_tag_num = if let Some(form) = form {
     match form.as_str() {
-      "sequence" | "seq of" => Some(16),
-      "set" | "set of" => Some(17),
+      "sequence" | "seq of" => Some((16u64, true)),
+      "set" | "set of" => Some((17u64, true)),
+      "bits" => Some((3u64, false)),
+      "null" => Some((5u64, false)),
+      "enumerated" => Some((10u64, false)),
       "choice" | "alias" => None,
       _ => None,
     }
@@ -100,11 +197,11 @@ pub fn asn1_info(input: TokenStream) -> TokenStream {
 
   let tag = match (tag, default_tag_num) {
     (Some(t), _) => quote!(#t),
-    (_, Some(n)) => quote!(
+    (_, Some((n, constructed))) => quote!(
       Some(::asn1_cereal::tag::Tag {
         class: ::asn1_cereal::tag::Class::Universal,
         tagnum: #n.into(),
-        constructed: true,
+        constructed: #constructed,
       })
     ),
     _ => quote!(None),
@@ -121,12 +218,25 @@ pub fn asn1_info(input: TokenStream) -> TokenStream {
       }
 
       #asn1_constructed
+
+      #asn1_constraints
+
+      #asn1_fields
     }
   };
 
   derived.to_string().parse().expect("Failure parsing derived impl")
 }
 
+#[proc_macro_derive(Asn1Schema, attributes(asn1))]
+pub fn asn1_schema_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = asn1_schema(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
 fn logging_enabled(ast: &syn::MacroInput) -> bool {
   // Parse attributes.
   for attr in &ast.attrs.iter().find(|e| e.name() == "asn1") {
@@ -146,6 +256,22 @@ fn logging_enabled(ast: &syn::MacroInput) -> bool {
   false
 }
 
+/// Returns true when this type has the `extensible` attribute.
+fn is_extensible(ast: &syn::MacroInput) -> bool {
+  for attr in &ast.attrs.iter().find(|e| e.name() == "asn1") {
+    if let syn::MetaItem::List(_, ref items) = attr.value {
+      for item in items {
+        if let syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) = *item {
+          if ident == "extensible" {
+            return true;
+          }
+        }
+      }
+    }
+  }
+  false
+}
+
 #[proc_macro_derive(BerSerialize, attributes(asn1))]
 pub fn ber_serialize(input: TokenStream) -> TokenStream {
   let source = input.to_string();
@@ -177,10 +303,15 @@ pub fn ber_serialize(input: TokenStream) -> TokenStream {
 
   let derived = if let Some(form) = form {
     match form.as_str() {
-      "sequence of" | "seq of" | "set of" => ber_sequence_of_serialize(&ast),
+      "sequence of" | "seq of" => ber_sequence_of_serialize(&ast),
+      "set of" => ber_set_of_serialize(&ast),
       "alias" => ber_alias_serialize(&ast),
       "choice" => ber_choice_serialize(&ast),
+      "bits" => ber_bits_serialize(&ast),
+      "null" => ber_null_serialize(&ast),
+      "enumerated" => ber_enumerated_serialize(&ast),
       "seq" | "sequence" => ber_sequence_serialize(&ast),
+      "set" => ber_set_serialize(&ast),
       _ => panic!("Unknown serialize form {}", form),
     }
   } else {
@@ -198,7 +329,9 @@ pub fn ber_serialize(input: TokenStream) -> TokenStream {
       syn::Body::Struct(syn::VariantData::Struct(_fields)) => {
         ber_sequence_serialize(&ast)
       },
-      _ => unimplemented!(),
+      syn::Body::Struct(syn::VariantData::Unit) => {
+        ber_null_serialize(&ast)
+      },
     }
   };
 
@@ -236,10 +369,15 @@ pub fn ber_deserialize(input: TokenStream) -> TokenStream {
 
   let derived = if let Some(form) = form {
     match form.as_str() {
-      "seq of" | "sequence of" | "set of" => ber_sequence_of_deserialize(&ast),
+      "seq of" | "sequence of" => ber_sequence_of_deserialize(&ast),
+      "set of" => ber_set_of_deserialize(&ast),
       "alias" => ber_alias_deserialize(&ast),
       "choice" => ber_choice_deserialize(&ast),
+      "bits" => ber_bits_deserialize(&ast),
+      "null" => ber_null_deserialize(&ast),
+      "enumerated" => ber_enumerated_deserialize(&ast),
       "seq" | "sequence" => ber_sequence_deserialize(&ast),
+      "set" => ber_set_deserialize(&ast),
       _ => panic!("Unknown deserialize form {}", form),
     }
   } else {
@@ -257,13 +395,111 @@ pub fn ber_deserialize(input: TokenStream) -> TokenStream {
       syn::Body::Struct(syn::VariantData::Struct(_fields)) => {
         ber_sequence_deserialize(&ast)
       },
-      _ => unimplemented!(),
+      syn::Body::Struct(syn::VariantData::Unit) => {
+        ber_null_deserialize(&ast)
+      },
     }
   };
 
   derived.to_string().parse().expect("Failure parsing derived impl")
 }
 
+/// Get the form of a type, from its form attribute or the shape of its body.
+fn derive_form(ast: &syn::MacroInput) -> String {
+  for attr in &ast.attrs.iter().find(|e| e.name() == "asn1") {
+    if let syn::MetaItem::List(_, ref items) = attr.value {
+      for item in items {
+        if let syn::NestedMetaItem::MetaItem(
+            syn::MetaItem::NameValue(ref name, syn::Lit::Str(ref value, _))) = *item {
+          if name == "form" {
+            return value.clone();
+          }
+        }
+      }
+    }
+  }
+
+  match ast.body {
+    syn::Body::Enum(_) => "choice",
+    syn::Body::Struct(syn::VariantData::Tuple(ref fields)) if fields.len() == 1 => "alias",
+    syn::Body::Struct(syn::VariantData::Tuple(_)) => "sequence",
+    syn::Body::Struct(syn::VariantData::Struct(_)) => "sequence",
+    syn::Body::Struct(syn::VariantData::Unit) => "null",
+  }.into()
+}
+
+#[proc_macro_derive(PerSerialize, attributes(asn1))]
+pub fn per_serialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = per_serialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(PerDeserialize, attributes(asn1))]
+pub fn per_deserialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = per_deserialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(OerSerialize, attributes(asn1))]
+pub fn oer_serialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = oer_serialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(OerDeserialize, attributes(asn1))]
+pub fn oer_deserialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = oer_deserialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(XerSerialize, attributes(asn1))]
+pub fn xer_serialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = xer_serialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(XerDeserialize, attributes(asn1))]
+pub fn xer_deserialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = xer_deserialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(JerSerialize, attributes(asn1))]
+pub fn jer_serialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = jer_serialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
+#[proc_macro_derive(JerDeserialize, attributes(asn1))]
+pub fn jer_deserialize_derive(input: TokenStream) -> TokenStream {
+  let source = input.to_string();
+  let ast = syn::parse_macro_input(&source).expect("Couldn't parse input TokenSteam into AST");
+
+  let derived = jer_deserialize(&ast, &derive_form(&ast));
+  derived.to_string().parse().expect("Failure parsing derived impl")
+}
+
 #[test]
 fn test() {
 }
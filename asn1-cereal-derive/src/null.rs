@@ -0,0 +1,33 @@
+use quote::Tokens;
+use syn;
+
+// FIXME: Documenation
+
+pub fn ber_null_serialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerSerialize for #name #ty_generics #where_clause {
+      fn serialize_value<E: ::asn1_cereal::BerEncRules, W: ::std::io::Write>
+          (&self, e: E, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        ::asn1_cereal::BerSerialize::serialize_value(&(), e, writer)
+      }
+    }
+  }
+}
+
+pub fn ber_null_deserialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        let () = try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len));
+        Ok(#name)
+      }
+    }
+  }
+}
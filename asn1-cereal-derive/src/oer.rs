@@ -0,0 +1,397 @@
+use quote::Tokens;
+use syn;
+
+use field;
+use is_extensible;
+use bits::{bits_to_struct, struct_to_bits};
+use choice::unit_variant_tag;
+use enumerated::enumerated_variants;
+use per::{component_order, field_info, choice_variants};
+use set::set_fields;
+
+/// Generate the OerSerialize implementation for a form.
+pub fn oer_serialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" => oer_sequence_encode(ast, false),
+    "set" => oer_sequence_encode(ast, true),
+    "choice" => oer_choice_encode(ast),
+    "enumerated" => oer_enumerated_encode(ast),
+    "seq of" | "sequence of" => quote!(
+      ::asn1_cereal::oer::serial::seq_of::encode_seq_of(e, self.into_iter(), constraints, writer, false)
+    ),
+    "set of" => quote!(
+      ::asn1_cereal::oer::serial::seq_of::encode_seq_of(e, self.into_iter(), constraints, writer, true)
+    ),
+    "alias" => quote!(
+      ::asn1_cereal::OerSerialize::oer_encode(&self.0, e, constraints, writer)
+    ),
+    "null" => quote!(Ok(())),
+    "bits" => oer_bits_encode(ast),
+    _ => panic!("Unknown serialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::OerSerialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn oer_encode<E: ::asn1_cereal::OerEncRules, W: ::std::io::Write>
+          (&self, e: E, constraints: ::asn1_cereal::constraint::Constraints,
+           writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        #body
+      }
+    }
+  }
+}
+
+/// Generate the OerDeserialize implementation for a form.
+pub fn oer_deserialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" => oer_sequence_decode(ast, false),
+    "set" => oer_sequence_decode(ast, true),
+    "choice" => oer_choice_decode(ast),
+    "enumerated" => oer_enumerated_decode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      let items: Vec<_> = try!(::asn1_cereal::oer::serial::seq_of::decode_seq_of(e, constraints, reader));
+      Ok(items.into_iter().collect())
+    ),
+    "alias" => quote!(
+      Ok(#name(try!(::asn1_cereal::OerDeserialize::oer_decode(e, constraints, reader))))
+    ),
+    "null" => quote!(Ok(#name)),
+    "bits" => {
+      let build = bits_to_struct(ast);
+      quote!(
+        // Any unknown bits are ignored.
+        let bits: ::asn1_cereal::BitString =
+          try!(::asn1_cereal::OerDeserialize::oer_decode(e, constraints, reader));
+        Ok(#build)
+      )
+    },
+    _ => panic!("Unknown deserialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::OerDeserialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn oer_decode<E: ::asn1_cereal::OerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, constraints: ::asn1_cereal::constraint::Constraints,
+           reader: &mut I) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        #body
+      }
+    }
+  }
+}
+
+fn oer_sequence_encode(ast: &syn::MacroInput, is_set: bool) -> Tokens {
+  let fields = set_fields(ast);
+  let order = component_order(fields, is_set);
+  let extensible = is_extensible(ast);
+
+  let mut presence = Vec::new();
+  let mut actions = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = field_info(v, &field, true);
+    let constraints = field.constraints(&info_ty);
+    let encode = quote!(
+      #convert
+      try!(::asn1_cereal::OerSerialize::oer_encode(value, e, #constraints, writer));
+    );
+    if field.optional {
+      presence.push(quote!(#i => preamble.push(self.#ident.is_some()),));
+      actions.push(quote!(
+        #i => if let Some(ref value) = self.#ident {
+          #encode
+        },
+      ));
+    } else if field.default {
+      let default = field.default_value(&v.ty);
+      // A DEFAULT component is only encoded when it isn't the default value.
+      presence.push(quote!(#i => preamble.push(self.#ident != #default),));
+      actions.push(quote!(
+        #i => if self.#ident != #default {
+          let value = &self.#ident;
+          #encode
+        },
+      ));
+    } else {
+      actions.push(quote!(
+        #i => {
+          let value = &self.#ident;
+          #encode
+        },
+      ));
+    }
+  }
+
+  // The preamble has an extension bit, then a bit for each OPTIONAL or
+  // DEFAULT component that is set when it's present.
+  let preamble = match (extensible, presence.is_empty()) {
+    (false, true) => quote!(),
+    (true, true) => quote!(
+      try!(::asn1_cereal::oer::serial::num::write_preamble(writer, &[false]));
+    ),
+    (extensible, false) => quote!(
+      let mut preamble = Vec::new();
+      if #extensible {
+        preamble.push(false);
+      }
+      for &i in &order {
+        match i {
+          #(#presence)*
+          _ => {},
+        }
+      }
+      try!(::asn1_cereal::oer::serial::num::write_preamble(writer, &preamble));
+    ),
+  };
+
+  quote!(
+    #order
+    #preamble
+    for &i in &order {
+      match i {
+        #(#actions)*
+        _ => {},
+      }
+    }
+    Ok(())
+  )
+}
+
+fn oer_sequence_decode(ast: &syn::MacroInput, is_set: bool) -> Tokens {
+  let name = &ast.ident;
+  let fields = set_fields(ast);
+  let order = component_order(fields, is_set);
+  let len = fields.len();
+  let extensible = is_extensible(ast);
+
+  let mut presence = Vec::new();
+  let mut init = Vec::new();
+  let mut actions = Vec::new();
+  let mut checks = Vec::new();
+  let mut assignments = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let f_ident: syn::Ident = format!("field_{}", ident.as_ref().expect("Requires named idents")).into();
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = field_info(v, &field, false);
+    let constraints = field.constraints(&info_ty);
+
+    // An OPTIONAL field is already an Option.
+    if field.optional {
+      presence.push(quote!(#i => present[#i] = bits.next().unwrap_or(false),));
+      init.push(quote!(let mut #f_ident: #ty = None;));
+      assignments.push(quote!(#ident: #f_ident));
+    } else if field.default {
+      let default = field.default_value(&v.ty);
+      presence.push(quote!(#i => present[#i] = bits.next().unwrap_or(false),));
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      checks.push(quote!(
+        if e.canonical() && #f_ident.as_ref().map_or(false, |v| *v == #default) {
+          return Err(::asn1_cereal::err::DecodeError::Custom("DEFAULT component was encoded with its default value"));
+        }
+      ));
+      assignments.push(quote!(#ident: #f_ident.unwrap_or_else(|| #default)));
+    } else {
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(
+        #ident: try!(#f_ident.ok_or(::asn1_cereal::err::DecodeError::MissingComponent))
+      ));
+    }
+    actions.push(quote!(
+      #i => #f_ident = Some({
+        let value = try!(::asn1_cereal::OerDeserialize::oer_decode(e, #constraints, reader));
+        #convert
+      }),
+    ));
+  }
+
+  let count = presence.len() + extensible as usize;
+  let preamble = match (extensible, presence.is_empty()) {
+    (false, true) => quote!(let present = [true; #len];),
+    (true, true) => quote!(
+      let preamble = try!(::asn1_cereal::oer::serial::num::read_preamble(e, reader, 1));
+      let extended = preamble[0];
+      let present = [true; #len];
+    ),
+    (extensible, false) => quote!(
+      let preamble = try!(::asn1_cereal::oer::serial::num::read_preamble(e, reader, #count));
+      let extended = #extensible && preamble[0];
+      let mut present = [true; #len];
+      let mut bits = preamble.into_iter().skip(#extensible as usize);
+      for &i in &order {
+        match i {
+          #(#presence)*
+          _ => {},
+        }
+      }
+    ),
+  };
+  // Extension additions from a later version of this type are ignored.
+  let ext_skip = if extensible {
+    quote!(
+      if extended {
+        try!(::asn1_cereal::oer::serial::num::skip_extensions(e, reader));
+      }
+    )
+  } else {
+    quote!()
+  };
+
+  quote!(
+    #order
+    #preamble
+    #(#init)*
+    for &i in &order {
+      if !present[i] {
+        continue;
+      }
+      match i {
+        #(#actions)*
+        _ => {},
+      }
+    }
+    #ext_skip
+    #(#checks)*
+    Ok(#name {
+      #(#assignments),*
+    })
+  )
+}
+
+fn oer_choice_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+
+  let actions: Vec<_> = variants.iter().map(|v| {
+    let ident = &v.ident;
+    match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        quote!(
+          #name::#ident(ref item) => {
+            let tag = try!(<#inner as ::asn1_cereal::Asn1Info>::asn1_tag()
+              .ok_or(::asn1_cereal::err::EncodeError::Custom("CHOICE alternatives need a tag with OER")));
+            try!(::asn1_cereal::oer::serial::num::write_tag(writer, tag));
+            ::asn1_cereal::OerSerialize::oer_encode(
+              item, e, <#inner as ::asn1_cereal::Asn1Info>::asn1_constraints(), writer)
+          }
+        )
+      },
+      // A unit variant is a NULL, which has no octets.
+      _ => {
+        let tag = unit_variant_tag(v);
+        quote!(
+          #name::#ident => ::asn1_cereal::oer::serial::num::write_tag(writer, #tag)
+        )
+      },
+    }
+  }).collect();
+
+  quote!(
+    match *self {
+      #(#actions),*
+    }
+  )
+}
+
+fn oer_choice_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+
+  let actions: Vec<_> = variants.iter().map(|v| {
+    let ident = &v.ident;
+    match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        quote!(
+          if <#inner as ::asn1_cereal::Asn1Info>::asn1_tag().map_or(false, |t| t.class == class && t.tagnum == tagnum) {
+            return Ok(#name::#ident(try!(::asn1_cereal::OerDeserialize::oer_decode(
+              e, <#inner as ::asn1_cereal::Asn1Info>::asn1_constraints(), reader))));
+          }
+        )
+      },
+      _ => {
+        let tag = unit_variant_tag(v);
+        quote!(
+          if { let t = #tag; t.class == class && t.tagnum == tagnum } {
+            return Ok(#name::#ident);
+          }
+        )
+      },
+    }
+  }).collect();
+
+  // An unknown extension alternative is an open type.
+  let ext_skip = if is_extensible(ast) {
+    quote!(
+      try!(::asn1_cereal::oer::serial::num::read_octets(e, reader));
+      return Err(::asn1_cereal::err::DecodeError::Custom("Unknown CHOICE extension"));
+    )
+  } else {
+    quote!()
+  };
+
+  quote!(
+    let (class, tagnum) = try!(::asn1_cereal::oer::serial::num::read_tag(e, reader));
+    #(#actions)*
+    #ext_skip
+    Err(::asn1_cereal::err::DecodeError::TagTypeMismatch)
+  )
+}
+
+fn oer_enumerated_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = enumerated_variants(ast);
+
+  let mut arms: Vec<_> = variants.named.iter().map(|&(ref ident, value)| {
+    quote!(#name::#ident => #value)
+  }).collect();
+  if let Some(ref ident) = variants.unknown {
+    arms.push(quote!(#name::#ident(value) => value));
+  }
+
+  quote!(
+    let value: i64 = match *self {
+      #(#arms),*
+    };
+    ::asn1_cereal::oer::serial::num::write_enumerated(writer, value)
+  )
+}
+
+fn oer_enumerated_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = enumerated_variants(ast);
+
+  let arms: Vec<_> = variants.named.iter().map(|&(ref ident, value)| {
+    quote!(#value => Ok(#name::#ident))
+  }).collect();
+  let unknown = match variants.unknown {
+    Some(ref ident) => quote!(value => Ok(#name::#ident(value))),
+    None => quote!(_ => Err(::asn1_cereal::err::DecodeError::UnknownEnumerated)),
+  };
+
+  quote!(
+    match try!(::asn1_cereal::oer::serial::num::read_enumerated(e, reader)) {
+      #(#arms,)*
+      #unknown
+    }
+  )
+}
+
+fn oer_bits_encode(ast: &syn::MacroInput) -> Tokens {
+  let bits = struct_to_bits(ast, true);
+
+  quote!(
+    #bits
+    ::asn1_cereal::OerSerialize::oer_encode(&bits, e, constraints, writer)
+  )
+}
@@ -0,0 +1,492 @@
+use quote::Tokens;
+use syn;
+
+use field;
+use is_extensible;
+use bits::{bits_to_struct, struct_to_bits};
+use choice::unit_variant_tag;
+use enumerated::enumerated_variants;
+use set::set_fields;
+
+/// Generate the PerSerialize implementation for a form.
+pub fn per_serialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" => per_sequence_encode(ast, false),
+    "set" => per_sequence_encode(ast, true),
+    "choice" => per_choice_encode(ast),
+    "enumerated" => per_enumerated_encode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      ::asn1_cereal::per::serial::seq_of::encode_seq_of(e, self.into_iter(), constraints, writer)
+    ),
+    "alias" => quote!(
+      ::asn1_cereal::PerSerialize::per_encode(&self.0, e, constraints, writer)
+    ),
+    "null" => quote!(Ok(())),
+    "bits" => per_bits_encode(ast),
+    _ => panic!("Unknown serialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::PerSerialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn per_encode<E: ::asn1_cereal::PerEncRules>
+          (&self, e: E, constraints: ::asn1_cereal::constraint::Constraints,
+           writer: &mut ::asn1_cereal::per::bits::BitWriter) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        #body
+      }
+    }
+  }
+}
+
+/// Generate the PerDeserialize implementation for a form.
+pub fn per_deserialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" => per_sequence_decode(ast, false),
+    "set" => per_sequence_decode(ast, true),
+    "choice" => per_choice_decode(ast),
+    "enumerated" => per_enumerated_decode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      let items: Vec<_> = try!(::asn1_cereal::per::serial::seq_of::decode_seq_of(e, constraints, reader));
+      Ok(items.into_iter().collect())
+    ),
+    "alias" => quote!(
+      Ok(#name(try!(::asn1_cereal::PerDeserialize::per_decode(e, constraints, reader))))
+    ),
+    "null" => quote!(Ok(#name)),
+    "bits" => {
+      let build = bits_to_struct(ast);
+      quote!(
+        // Any unknown bits are ignored.
+        let bits: ::asn1_cereal::BitString =
+          try!(::asn1_cereal::PerDeserialize::per_decode(e, constraints, reader));
+        Ok(#build)
+      )
+    },
+    _ => panic!("Unknown deserialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::PerDeserialize for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn per_decode<E: ::asn1_cereal::PerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, constraints: ::asn1_cereal::constraint::Constraints,
+           reader: &mut ::asn1_cereal::per::bits::BitReader<I>) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        #body
+      }
+    }
+  }
+}
+
+/// Get the order that the components of a SEQUENCE or SET are encoded in.
+/// For a SET, this is the canonical order of their tags.
+pub fn component_order(fields: &[syn::Field], is_set: bool) -> Tokens {
+  let indexes: Vec<usize> = (0..fields.len()).collect();
+  if !is_set {
+    return quote!(let order: Vec<usize> = vec![#(#indexes),*];);
+  }
+  let tags: Vec<_> = fields.iter().enumerate().map(|(i, v)| {
+    field::Field::parse(&v.attrs).base_tag(i as u64)
+  }).collect();
+  quote!(
+    let mut tags: Vec<(u8, u64, usize)> = vec![
+      #({ let tag = #tags; (u8::from(tag.class), tag.tagnum, #indexes) }),*
+    ];
+    tags.sort();
+    let order: Vec<usize> = tags.into_iter().map(|t| t.2).collect();
+  )
+}
+
+/// Get the type a field is encoded as, and how the value is converted.
+pub fn field_info(v: &syn::Field, field: &field::Field, serialize: bool) -> (Tokens, Tokens) {
+  let ty = &v.ty;
+  if field.defined_by.is_some() {
+    panic!("The defined_by attribute is only supported by BER");
+  }
+  match field.time_conversion() {
+    Some((time_ty, to_time, _)) if serialize => (
+      time_ty.clone(),
+      quote!(
+        let value = &try!(::asn1_cereal::ber::serial::time::Asn1Time::#to_time(value)
+          .ok_or(::asn1_cereal::err::EncodeError::Custom("Time can't be represented by this ASN.1 type")));
+      ),
+    ),
+    Some((time_ty, _, from_time)) => (
+      time_ty.clone(),
+      quote!(
+        let value: #time_ty = value;
+        try!(::asn1_cereal::ber::serial::time::Asn1Time::#from_time(value)
+          .ok_or(::asn1_cereal::err::DecodeError::InvalidTime))
+      ),
+    ),
+    None if serialize => (quote!(#ty), quote!()),
+    None => (quote!(#ty), quote!(value)),
+  }
+}
+
+fn per_sequence_encode(ast: &syn::MacroInput, is_set: bool) -> Tokens {
+  let fields = set_fields(ast);
+  let order = component_order(fields, is_set);
+  let ext_bit = if is_extensible(ast) {
+    quote!(writer.write_bit(false);)
+  } else {
+    quote!()
+  };
+
+  let mut presence = Vec::new();
+  let mut actions = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = field_info(v, &field, true);
+    let constraints = field.constraints(&info_ty);
+    let encode = quote!(
+      #convert
+      try!(::asn1_cereal::PerSerialize::per_encode(value, e, #constraints, writer));
+    );
+    if field.optional {
+      presence.push(quote!(#i => writer.write_bit(self.#ident.is_some()),));
+      actions.push(quote!(
+        #i => if let Some(ref value) = self.#ident {
+          #encode
+        },
+      ));
+    } else if field.default {
+      let default = field.default_value(&v.ty);
+      // A DEFAULT component is only encoded when it isn't the default value.
+      presence.push(quote!(#i => writer.write_bit(self.#ident != #default),));
+      actions.push(quote!(
+        #i => if self.#ident != #default {
+          let value = &self.#ident;
+          #encode
+        },
+      ));
+    } else {
+      actions.push(quote!(
+        #i => {
+          let value = &self.#ident;
+          #encode
+        },
+      ));
+    }
+  }
+
+  // The preamble has a bit for each OPTIONAL or DEFAULT component, set when
+  // it's present.
+  let preamble = if presence.is_empty() {
+    quote!()
+  } else {
+    quote!(
+      for &i in &order {
+        match i {
+          #(#presence)*
+          _ => {},
+        }
+      }
+    )
+  };
+
+  quote!(
+    #order
+    #ext_bit
+    #preamble
+    for &i in &order {
+      match i {
+        #(#actions)*
+        _ => {},
+      }
+    }
+    Ok(())
+  )
+}
+
+fn per_sequence_decode(ast: &syn::MacroInput, is_set: bool) -> Tokens {
+  let name = &ast.ident;
+  let fields = set_fields(ast);
+  let order = component_order(fields, is_set);
+  let len = fields.len();
+
+  let mut presence = Vec::new();
+  let mut init = Vec::new();
+  let mut actions = Vec::new();
+  let mut assignments = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let f_ident: syn::Ident = format!("field_{}", ident.as_ref().expect("Requires named idents")).into();
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = field_info(v, &field, false);
+    let constraints = field.constraints(&info_ty);
+
+    // An OPTIONAL field is already an Option.
+    if field.optional {
+      presence.push(quote!(#i => present[#i] = try!(reader.read_bit()),));
+      init.push(quote!(let mut #f_ident: #ty = None;));
+      assignments.push(quote!(#ident: #f_ident));
+    } else if field.default {
+      let default = field.default_value(&v.ty);
+      presence.push(quote!(#i => present[#i] = try!(reader.read_bit()),));
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(#ident: #f_ident.unwrap_or_else(|| #default)));
+    } else {
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(
+        #ident: try!(#f_ident.ok_or(::asn1_cereal::err::DecodeError::MissingComponent))
+      ));
+    }
+    actions.push(quote!(
+      #i => #f_ident = Some({
+        let value = try!(::asn1_cereal::PerDeserialize::per_decode(e, #constraints, reader));
+        #convert
+      }),
+    ));
+  }
+
+  let (ext_read, ext_skip) = if is_extensible(ast) {
+    (
+      quote!(let extended = try!(reader.read_bit());),
+      // Extension additions from a later version of this type are ignored.
+      quote!(
+        if extended {
+          try!(::asn1_cereal::per::serial::num::skip_extensions(e, reader));
+        }
+      ),
+    )
+  } else {
+    (quote!(), quote!())
+  };
+  let preamble = if presence.is_empty() {
+    quote!(let present = [true; #len];)
+  } else {
+    quote!(
+      let mut present = [true; #len];
+      for &i in &order {
+        match i {
+          #(#presence)*
+          _ => {},
+        }
+      }
+    )
+  };
+
+  quote!(
+    #order
+    #ext_read
+    #preamble
+    #(#init)*
+    for &i in &order {
+      if !present[i] {
+        continue;
+      }
+      match i {
+        #(#actions)*
+        _ => {},
+      }
+    }
+    #ext_skip
+    Ok(#name {
+      #(#assignments),*
+    })
+  )
+}
+
+/// Get the order that the alternatives of a CHOICE are numbered in, which
+/// is the canonical order of their tags.
+fn choice_order(variants: &[syn::Variant]) -> Tokens {
+  let keys: Vec<_> = variants.iter().enumerate().map(|(i, v)| {
+    let tag = match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        quote!(<#inner as ::asn1_cereal::Asn1Info>::asn1_tag())
+      },
+      _ => {
+        let tag = unit_variant_tag(v);
+        quote!(Some(#tag))
+      },
+    };
+    // An alternative without a tag (like a CHOICE) is ordered last.
+    quote!((#tag.map_or((u8::max_value(), 0), |t| (u8::from(t.class), t.tagnum)), #i))
+  }).collect();
+  quote!(
+    let mut tags: Vec<((u8, u64), usize)> = vec![#(#keys),*];
+    tags.sort();
+    let order: Vec<usize> = tags.into_iter().map(|t| t.1).collect();
+  )
+}
+
+pub fn choice_variants(ast: &syn::MacroInput) -> &[syn::Variant] {
+  let name = &ast.ident;
+  if let syn::Body::Enum(ref variants) = ast.body {
+    variants
+  } else {
+    panic!("Expected an enum, but type {} was not an enum", name);
+  }
+}
+
+fn per_choice_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+  let order = choice_order(variants);
+  let len = variants.len();
+  let ext_bit = if is_extensible(ast) {
+    quote!(writer.write_bit(false);)
+  } else {
+    quote!()
+  };
+
+  let mut indexes = Vec::new();
+  let mut actions = Vec::new();
+  for (i, v) in variants.iter().enumerate() {
+    let ident = &v.ident;
+    match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        indexes.push(quote!(#name::#ident(..) => #i));
+        actions.push(quote!(
+          #name::#ident(ref item) =>
+            ::asn1_cereal::PerSerialize::per_encode(
+              item, e, <#inner as ::asn1_cereal::Asn1Info>::asn1_constraints(), writer)
+        ));
+      },
+      // A unit variant is a NULL, which has no bits.
+      _ => {
+        indexes.push(quote!(#name::#ident => #i));
+        actions.push(quote!(#name::#ident => Ok(())));
+      },
+    }
+  }
+
+  quote!(
+    #order
+    let variant: usize = match *self {
+      #(#indexes),*
+    };
+    let index = order.iter().position(|&v| v == variant).expect("CHOICE order is missing a variant");
+    #ext_bit
+    ::asn1_cereal::per::serial::num::write_constrained(e, writer, index as u128, #len as u128);
+    match *self {
+      #(#actions),*
+    }
+  )
+}
+
+fn per_choice_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+  let order = choice_order(variants);
+  let len = variants.len();
+  let ext_read = if is_extensible(ast) {
+    quote!(
+      if try!(reader.read_bit()) {
+        return Err(::asn1_cereal::err::DecodeError::Custom("Unknown CHOICE extension"));
+      }
+    )
+  } else {
+    quote!()
+  };
+
+  let actions: Vec<_> = variants.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        quote!(
+          #i => Ok(#name::#ident(try!(::asn1_cereal::PerDeserialize::per_decode(
+            e, <#inner as ::asn1_cereal::Asn1Info>::asn1_constraints(), reader))))
+        )
+      },
+      _ => quote!(#i => Ok(#name::#ident)),
+    }
+  }).collect();
+
+  quote!(
+    #order
+    #ext_read
+    let index = try!(::asn1_cereal::per::serial::num::read_constrained(e, reader, #len as u128));
+    match order[index as usize] {
+      #(#actions,)*
+      _ => Err(::asn1_cereal::err::DecodeError::ConstraintViolation),
+    }
+  )
+}
+
+/// Get the named variants of an ENUMERATED in the order of their values,
+/// which is the order their indexes are encoded in.
+fn sorted_enumerated(ast: &syn::MacroInput) -> Vec<(syn::Ident, i64)> {
+  let mut named = enumerated_variants(ast).named;
+  named.sort_by_key(|&(_, value)| value);
+  named
+}
+
+fn per_enumerated_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let named = sorted_enumerated(ast);
+  let len = named.len();
+  let ext_bit = if is_extensible(ast) {
+    quote!(writer.write_bit(false);)
+  } else {
+    quote!()
+  };
+
+  let mut arms: Vec<_> = named.iter().enumerate().map(|(i, &(ref ident, _))| {
+    quote!(#name::#ident => #i)
+  }).collect();
+  if let Some(ref ident) = enumerated_variants(ast).unknown {
+    arms.push(quote!(
+      #name::#ident(_) =>
+        return Err(::asn1_cereal::err::EncodeError::Custom("Unknown ENUMERATED values can't be encoded with PER"))
+    ));
+  }
+
+  quote!(
+    let index: usize = match *self {
+      #(#arms),*
+    };
+    #ext_bit
+    ::asn1_cereal::per::serial::num::write_constrained(e, writer, index as u128, #len as u128);
+    Ok(())
+  )
+}
+
+fn per_enumerated_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let named = sorted_enumerated(ast);
+  let len = named.len();
+  let ext_read = if is_extensible(ast) {
+    quote!(
+      if try!(reader.read_bit()) {
+        try!(::asn1_cereal::per::serial::num::read_normally_small(e, reader));
+        return Err(::asn1_cereal::err::DecodeError::UnknownEnumerated);
+      }
+    )
+  } else {
+    quote!()
+  };
+
+  let arms: Vec<_> = named.iter().enumerate().map(|(i, &(ref ident, _))| {
+    quote!(#i => Ok(#name::#ident))
+  }).collect();
+
+  quote!(
+    #ext_read
+    let index = try!(::asn1_cereal::per::serial::num::read_constrained(e, reader, #len as u128));
+    match index as usize {
+      #(#arms,)*
+      _ => Err(::asn1_cereal::err::DecodeError::UnknownEnumerated),
+    }
+  )
+}
+
+fn per_bits_encode(ast: &syn::MacroInput) -> Tokens {
+  let bits = struct_to_bits(ast, true);
+
+  quote!(
+    #bits
+    ::asn1_cereal::PerSerialize::per_encode(&bits, e, constraints, writer)
+  )
+}
@@ -0,0 +1,155 @@
+use quote::Tokens;
+use syn;
+
+use field;
+use alias::alias_inner_ty;
+use bits::bits_fields;
+use choice::unit_variant_tag;
+use enumerated::enumerated_variants;
+use per::choice_variants;
+use set::set_fields;
+
+/// Generate the Asn1Schema implementation for a form.
+pub fn asn1_schema(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let (kind, components) = match form {
+    "sequence" | "seq" => (quote!(Sequence), sequence_components(ast)),
+    "set" => (quote!(Set), sequence_components(ast)),
+    "choice" => (quote!(Choice), choice_components(ast)),
+    "enumerated" => (quote!(Enumerated), enumerated_components(ast)),
+    "seq of" | "sequence of" => (quote!(SequenceOf), seq_of_components(ast)),
+    "set of" => (quote!(SetOf), seq_of_components(ast)),
+    "alias" => {
+      let inner_ty = alias_inner_ty(ast);
+      (quote!(Alias), quote!(vec![::asn1_cereal::schema::Component::of::<#inner_ty>("")]))
+    },
+    "null" => (quote!(Null), quote!(Vec::new())),
+    "bits" => (quote!(NamedBits), bits_components(ast)),
+    _ => panic!("Unknown schema form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::Asn1Schema for #name #ty_generics #where_clause {
+      fn asn1_schema() -> ::asn1_cereal::schema::Schema {
+        ::asn1_cereal::schema::Schema {
+          components: #components,
+          .. ::asn1_cereal::schema::Schema::of::<Self>(::asn1_cereal::schema::Kind::#kind)
+        }
+      }
+    }
+  }
+}
+
+/// Build a component that has no type, like an ENUMERATED value.
+fn value_component(name: &str, value: i64) -> Tokens {
+  quote!(
+    ::asn1_cereal::schema::Component {
+      name: #name,
+      tag: None,
+      optional: false,
+      default: false,
+      value: Some(#value),
+      constraints: ::asn1_cereal::constraint::Constraints::default(),
+      schema: None,
+    }
+  )
+}
+
+fn sequence_components(ast: &syn::MacroInput) -> Tokens {
+  let components: Vec<_> = set_fields(ast).iter().enumerate().map(|(i, v)| {
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    let name = field.identifier(v.ident.as_ref().expect("Requires named idents"));
+    // A component is described by the type it's encoded as.
+    let info_ty = match field.time_conversion() {
+      Some((time_ty, _, _)) => time_ty,
+      None if field.defined_by.is_some() => quote!(::asn1_cereal::RawElement),
+      None => quote!(#ty),
+    };
+    let tag = field.base_tag(i as u64);
+    let constraints = field.constraints(&info_ty);
+    let optional = field.optional;
+    let default = field.default;
+    quote!(
+      ::asn1_cereal::schema::Component {
+        tag: Some(#tag),
+        optional: #optional,
+        default: #default,
+        constraints: #constraints,
+        .. ::asn1_cereal::schema::Component::of::<#info_ty>(#name)
+      }
+    )
+  }).collect();
+
+  quote!(vec![#(#components),*])
+}
+
+fn choice_components(ast: &syn::MacroInput) -> Tokens {
+  let components: Vec<_> = choice_variants(ast).iter().map(|v| {
+    let field = field::Field::parse(&v.attrs);
+    let name = field.identifier(&v.ident);
+    match v.data {
+      syn::VariantData::Tuple(ref fields) => {
+        let inner = &fields[0].ty;
+        let tag = match field.tag {
+          Some(ref tag) => quote!(#tag),
+          None => quote!(None),
+        };
+        quote!(
+          ::asn1_cereal::schema::Component {
+            tag: #tag,
+            .. ::asn1_cereal::schema::Component::of::<#inner>(#name)
+          }
+        )
+      },
+      // A unit variant is a NULL.
+      _ => {
+        let tag = unit_variant_tag(v);
+        quote!(
+          ::asn1_cereal::schema::Component {
+            tag: Some(#tag),
+            .. ::asn1_cereal::schema::Component::of::<()>(#name)
+          }
+        )
+      },
+    }
+  }).collect();
+
+  quote!(vec![#(#components),*])
+}
+
+fn enumerated_components(ast: &syn::MacroInput) -> Tokens {
+  let variants = choice_variants(ast);
+  let components: Vec<_> = enumerated_variants(ast).named.iter().map(|&(ref ident, value)| {
+    let v = variants.iter().find(|v| v.ident == *ident).expect("Variant should exist");
+    let name = field::Field::parse(&v.attrs).identifier(ident);
+    value_component(&name, value)
+  }).collect();
+
+  quote!(vec![#(#components),*])
+}
+
+fn bits_components(ast: &syn::MacroInput) -> Tokens {
+  let fields = set_fields(ast);
+  let components: Vec<_> = bits_fields(ast).iter().zip(fields).map(|(&(ref ident, bit), v)| {
+    let name = field::Field::parse(&v.attrs).identifier(ident);
+    value_component(&name, bit as i64)
+  }).collect();
+
+  quote!(vec![#(#components),*])
+}
+
+/// A SEQUENCE/SET OF that wraps a collection, like `struct A(Vec<T>)`, has
+/// the component of that collection. Otherwise the type of the elements
+/// isn't known.
+fn seq_of_components(ast: &syn::MacroInput) -> Tokens {
+  match ast.body {
+    syn::Body::Struct(syn::VariantData::Tuple(ref fields)) if fields.len() == 1 => {
+      let inner = &fields[0].ty;
+      quote!(<#inner as ::asn1_cereal::Asn1Schema>::asn1_schema().components)
+    },
+    _ => quote!(Vec::new()),
+  }
+}
@@ -19,20 +19,43 @@ pub fn ber_sequence_serialize(ast: &syn::MacroInput) -> Tokens {
     panic!("Expected a struct, but type {} was not a struct", name);
   };
 
-  let actions: Vec<_> = fields.iter().map(|v| {
+  let actions: Vec<_> = fields.iter().enumerate().map(|(i, v)| {
     let ident = &v.ident.as_ref().expect("Requires named idents");
     let ty = &v.ty;
     let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = match field.time_conversion() {
+      Some((time_ty, to_time, _)) => (
+        time_ty.clone(),
+        quote!(
+          let value = &try!(::asn1_cereal::ber::serial::time::Asn1Time::#to_time(value)
+            .ok_or(::asn1_cereal::err::EncodeError::Custom("Time can't be represented by this ASN.1 type")));
+        ),
+      ),
+      // An open type can't be implicitly tagged, so it's always explicit.
+      None if field.defined_by.is_some() => (
+        quote!(::asn1_cereal::RawElement),
+        quote!(let is_implicit = false;),
+      ),
+      None => (quote!(#ty), quote!()),
+    };
+    let tag = field.component_tag(&info_ty, i as u64);
     let tag_encode = quote!(
+      #convert
       if is_implicit {
         try!(::asn1_cereal::BerSerialize::serialize_value(value, e, &mut bytes));
       } else {
         try!(::asn1_cereal::BerSerialize::serialize_enc(value, e, &mut bytes));
       }
 
-      let len: ::asn1_cereal::tag::Len = Some(bytes.len() as ::asn1_cereal::tag::LenNum).into();
-      try!(::asn1_cereal::tag::write_taglen(tag, len, writer));
-      try!(writer.write_all(&mut bytes));
+      if tag.constructed && e.len_rules() == ::asn1_cereal::ber::enc::LenEnc::Indefinite {
+        try!(::asn1_cereal::tag::write_taglen(tag, ::asn1_cereal::tag::Len::Indef, writer));
+        try!(writer.write_all(&mut bytes));
+        try!(::asn1_cereal::tag::Len::write_indef_end(writer));
+      } else {
+        let len: ::asn1_cereal::tag::Len = Some(bytes.len() as ::asn1_cereal::tag::LenNum).into();
+        try!(::asn1_cereal::tag::write_taglen(tag, len, writer));
+        try!(writer.write_all(&mut bytes));
+      }
       bytes.clear();
     );
     let encode = if field.optional {
@@ -41,6 +64,15 @@ pub fn ber_sequence_serialize(ast: &syn::MacroInput) -> Tokens {
           #tag_encode
         }
       )
+    } else if field.default {
+      // DER and CER leave out a component that's equal to its DEFAULT value.
+      let default = field.default_value(ty);
+      quote!(
+        let value = &self.#ident;
+        if !e.canonical() || *value != #default {
+          #tag_encode
+        }
+      )
     } else {
       quote!(
         let value = &self.#ident;
@@ -49,20 +81,9 @@ pub fn ber_sequence_serialize(ast: &syn::MacroInput) -> Tokens {
     };
     quote! {
       let is_implicit =
-        E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit;
-
-      let tag = ::asn1_cereal::tag::Tag {
-        class: ::asn1_cereal::tag::Class::ContextSpecific,
-        tagnum: _count,
-        constructed:
-          if is_implicit {
-            <#ty as ::asn1_cereal::Asn1Info>::asn1_constructed(e)
-          } else {
-            true
-          },
-      };
+        e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit;
 
-      _count += 1;
+      let tag = #tag;
 
       #encode
     }
@@ -73,7 +94,6 @@ pub fn ber_sequence_serialize(ast: &syn::MacroInput) -> Tokens {
       fn serialize_value<E: ::asn1_cereal::BerEncRules, W: ::std::io::Write>
           (&self, e: E, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
         let mut bytes = Vec::new();
-        let mut _count = 0u64;
 
         #( { #actions }; )*
         Ok(())
@@ -97,35 +117,58 @@ pub fn ber_sequence_deserialize(ast: &syn::MacroInput) -> Tokens {
     panic!("Expected a struct, but type {} was not a struct", name);
   };
 
-  let build: Vec<_> = fields.iter().map(|v| {
+  let build: Vec<_> = fields.iter().enumerate().map(|(i, v)| {
     let ident = &v.ident;
     let f_ident: syn::Ident = format!("field_{}", ident.as_ref().expect("Requires named idents")).into();
     let ty = &v.ty;
     let field = field::Field::parse(&v.attrs);
+    let (info_ty, convert) = match field.time_conversion() {
+      Some((time_ty, _, from_time)) => (
+        time_ty.clone(),
+        quote!(
+          let value: #time_ty = value;
+          try!(::asn1_cereal::ber::serial::time::Asn1Time::#from_time(value)
+            .ok_or(::asn1_cereal::err::DecodeError::InvalidTime))
+        ),
+      ),
+      None => match field.defined_by {
+        // Decode the open type as a raw element, then use the value of the
+        // field that defines it.
+        Some(ref key) => {
+          let key: syn::Ident = format!("field_{}", key).into();
+          (
+            quote!(::asn1_cereal::RawElement),
+            quote!(
+              let value: ::asn1_cereal::RawElement = value;
+              try!(::asn1_cereal::DefinedBy::decode_defined_by(e, &#key, value))
+            ),
+          )
+        },
+        None => (quote!(#ty), quote!(value)),
+      },
+    };
+    let tag = field.component_tag(&info_ty, i as u64);
     let tag_decode = quote!(
       {
         _tag = None;
-        let len = try!(::asn1_cereal::tag::Len::read_len(reader));
+        let len = try!(::asn1_cereal::tag::Len::read_len_enc(e, reader));
+        try!(::asn1_cereal::ber::enc::check_len(e, this_tag, len));
         // If we are decoding with an implicit tag, deserialize value directly.
-        if is_implicit {
-          try!(::asn1_cereal::BerDeserialize::deserialize_value(e, reader, len))
+        let value = if is_implicit {
+          try!(::asn1_cereal::ber::serial::traits::deserialize_implicit(e, reader, len))
         } else {
-          try!(::asn1_cereal::BerDeserialize::deserialize_enc(e, reader))
-        }
+          let value = try!(::asn1_cereal::BerDeserialize::deserialize_enc(e, reader));
+          if len == ::asn1_cereal::tag::Len::Indef {
+            try!(::asn1_cereal::tag::Len::read_indef_end(reader));
+          }
+          value
+        };
+        #convert
       }
     );
-    // TODO: Actually implement this, it's OPTIONAL, but the no match
-    // case is the default value.
-    // let default = quote!(
-    //   if this_tag == our_tag {
-    //     #tag_decode
-    //   } else {
-    //
-    //   }
-    // );
     let decode = if field.optional {
       quote!(
-        if this_tag == our_tag {
+        if ::asn1_cereal::ber::enc::tag_matches(e, this_tag, our_tag) {
           Some(
             #tag_decode
           )
@@ -133,9 +176,20 @@ pub fn ber_sequence_deserialize(ast: &syn::MacroInput) -> Tokens {
           None
         }
       )
+    } else if field.default {
+      // A DEFAULT component is like an OPTIONAL one, but an absent component
+      // has the default value.
+      let default = field.default_value(ty);
+      quote!(
+        if ::asn1_cereal::ber::enc::tag_matches(e, this_tag, our_tag) {
+          #tag_decode
+        } else {
+          #default
+        }
+      )
     } else {
       quote!(
-        if this_tag != our_tag {
+        if !::asn1_cereal::ber::enc::tag_matches(e, this_tag, our_tag) {
           return Err(::asn1_cereal::err::DecodeError::TagTypeMismatch);
         }
         #tag_decode
@@ -144,7 +198,6 @@ pub fn ber_sequence_deserialize(ast: &syn::MacroInput) -> Tokens {
     // TODO: Add lenth check using ByteReader. We need to check our decoded
     // length, otherwise an OPTIONAL or DEFAULT as the final field means we'll
     // look beyond our element.
-    // TODO: Make context-specific tags optional, implement default
     let length_check =
       quote!(
         if reader.exceeded {
@@ -157,6 +210,13 @@ pub fn ber_sequence_deserialize(ast: &syn::MacroInput) -> Tokens {
           None
         } else
       )
+    } else if field.default {
+      let default = field.default_value(ty);
+      quote!(
+        if reader.reached_limit() {
+          #default
+        } else
+      )
     } else {
       quote!()
     };
@@ -166,26 +226,15 @@ pub fn ber_sequence_deserialize(ast: &syn::MacroInput) -> Tokens {
         let this_tag = match _tag {
           Some(t) => t,
           None => {
-            let t = ::asn1_cereal::tag::Tag::read_tag(reader)?;
+            let t = ::asn1_cereal::tag::Tag::read_tag_enc(e, reader)?;
             _tag = Some(t);
             t
           }
         };
 
         let is_implicit =
-          E::tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit;
-        // TODO: Handle entries without a context specific tag.
-        let our_tag = ::asn1_cereal::tag::Tag {
-          class: ::asn1_cereal::tag::Class::ContextSpecific,
-          tagnum: _count,
-          constructed:
-            if is_implicit {
-              <#ty as ::asn1_cereal::Asn1Info>::asn1_constructed(e)
-            } else {
-              true
-            },
-        };
-        _count += 1;
+          e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit;
+        let our_tag = #tag;
 
         #decode
       };
@@ -200,15 +249,44 @@ pub fn ber_sequence_deserialize(ast: &syn::MacroInput) -> Tokens {
 
   quote! {
     impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      fn _reads_indef_end<E: ::asn1_cereal::BerEncRules>(_: E) -> bool {
+        true
+      }
+
       fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
           (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
-        let mut _count = 0u64;
         let mut _tag: Option<::asn1_cereal::tag::Tag> = None;
         let mut byte_reader = ::asn1_cereal::byte::ByteReader::new(reader, len.into());
         let reader = &mut byte_reader;
 
         #( #build )*
 
+        // Any bytes left in a definite length encoding, including a tag that
+        // was read while looking for an OPTIONAL field, come after the last
+        // component.
+        if len != ::asn1_cereal::tag::Len::Indef && (_tag.is_some() || !reader.reached_limit()) {
+          if !e.tolerate(::asn1_cereal::ber::enc::Deviation::TrailingBytes) {
+            return Err(::asn1_cereal::err::DecodeError::SmallerLen);
+          }
+          while !reader.reached_limit() {
+            try!(reader.read());
+          }
+        }
+
+        // Read the end-of-contents octets, where the tag may have already been
+        // read while looking for an OPTIONAL field.
+        if len == ::asn1_cereal::tag::Len::Indef {
+          match _tag {
+            Some(t) => {
+              if t.tagnum != 0 || t.class != ::asn1_cereal::tag::Class::Universal ||
+                 try!(::asn1_cereal::tag::Len::read_len(reader)) != ::asn1_cereal::tag::Len::Def(0) {
+                return Err(::asn1_cereal::err::DecodeError::IndefiniteLenEnd);
+              }
+            },
+            None => try!(::asn1_cereal::tag::Len::read_indef_end(reader)),
+          }
+        }
+
         Ok(#name {
           #(#assignments),*
         })
@@ -28,8 +28,13 @@ pub fn ber_sequence_of_deserialize(ast: &syn::MacroInput) -> Tokens {
 
   quote! {
     impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      // The end-of-contents octets are read to find the last element.
+      fn _reads_indef_end<E: ::asn1_cereal::BerEncRules>(_: E) -> bool {
+        true
+      }
+
       fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
-          (e: E, reader: &mut I, len: tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+          (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
         struct SeqOfDecoder<T, F, J: Iterator<Item=::std::io::Result<u8>>> {
           len: ::asn1_cereal::tag::Len,
           reader: ::asn1_cereal::byte::ByteReader<J>,
@@ -58,7 +63,7 @@ pub fn ber_sequence_of_deserialize(ast: &syn::MacroInput) -> Tokens {
               Some(::std::cmp::Ordering::Greater) | None => {},
             }
 
-            let (tag, len) = match ::asn1_cereal::tag::read_taglen(&mut self.reader) {
+            let (tag, len) = match ::asn1_cereal::tag::read_taglen_enc(self.e, &mut self.reader) {
               Ok(t) => t,
               Err(e) => return Some(Err(e)),
             };
@@ -69,19 +74,19 @@ pub fn ber_sequence_of_deserialize(ast: &syn::MacroInput) -> Tokens {
               return None;
             }
 
-            Some(::asn1_cereal::BerDeserialize::deserialize_value(self.e, &mut self.reader, len))
+            Some(::asn1_cereal::BerDeserialize::deserialize_with_tag(self.e, &mut self.reader, tag, len))
           }
         }
 
         if len == ::asn1_cereal::tag::Len::Indef &&
-           E::len_rules() == ::asn1_cereal::ber::enc::LenEnc::Definite {
+           e.len_rules() == ::asn1_cereal::ber::enc::LenEnc::Definite {
           return Err(::asn1_cereal::err::DecodeError::IndefiniteLen);
         }
 
         let mut decoder = SeqOfDecoder {
           e: e,
           len: len.into(),
-          reader: ::asn1_cereal::byte::ByteReader::new(reader),
+          reader: ::asn1_cereal::byte::ByteReader::new(reader, None),
           _p: ::std::marker::PhantomData,
         };
         let v: Result<#name, _> = ::std::iter::FromIterator::from_iter(decoder.by_ref());
@@ -90,3 +95,38 @@ pub fn ber_sequence_of_deserialize(ast: &syn::MacroInput) -> Tokens {
     }
   }
 }
+
+pub fn ber_set_of_serialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerSerialize for #name #ty_generics #where_clause {
+      fn serialize_value<E: ::asn1_cereal::BerEncRules, W: ::std::io::Write>
+          (&self, e: E, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        // Elements are sorted with canonical encoding rules.
+        ::asn1_cereal::ber::serial::seq_of::serialize_set_of(e, self.into_iter(), writer)
+      }
+    }
+  }
+}
+
+pub fn ber_set_of_deserialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      // The end-of-contents octets are read to find the last element.
+      fn _reads_indef_end<E: ::asn1_cereal::BerEncRules>(_: E) -> bool {
+        true
+      }
+
+      fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        let items: Vec<_> = try!(::asn1_cereal::ber::serial::seq_of::deserialize_set_of(e, reader, len));
+        Ok(items.into_iter().collect())
+      }
+    }
+  }
+}
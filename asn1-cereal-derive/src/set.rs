@@ -0,0 +1,242 @@
+use quote::Tokens;
+use syn;
+
+use field;
+
+/// Get the fields of a struct used as a SET.
+pub fn set_fields<'a>(ast: &'a syn::MacroInput) -> &'a [syn::Field] {
+  let name = &ast.ident;
+  if let syn::Body::Struct(syn::VariantData::Struct(ref fields)) = ast.body {
+    fields
+  } else {
+    panic!("Expected a struct with named fields, but type {} was not", name);
+  }
+}
+
+pub fn ber_set_serialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let actions: Vec<_> = set_fields(ast).iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident.as_ref().expect("Requires named idents");
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    if field.defined_by.is_some() {
+      panic!("The defined_by attribute isn't supported by a SET");
+    }
+    let (info_ty, convert) = match field.time_conversion() {
+      Some((time_ty, to_time, _)) => (
+        time_ty.clone(),
+        quote!(
+          let value = &try!(::asn1_cereal::ber::serial::time::Asn1Time::#to_time(value)
+            .ok_or(::asn1_cereal::err::EncodeError::Custom("Time can't be represented by this ASN.1 type")));
+        ),
+      ),
+      None => (quote!(#ty), quote!()),
+    };
+    let tag = field.component_tag(&info_ty, i as u64);
+    let tag_encode = quote!(
+      #convert
+      let tag = #tag;
+      let mut bytes = Vec::new();
+      if is_implicit {
+        try!(::asn1_cereal::BerSerialize::serialize_value(value, e, &mut bytes));
+      } else {
+        try!(::asn1_cereal::BerSerialize::serialize_enc(value, e, &mut bytes));
+      }
+
+      let mut component = Vec::new();
+      if tag.constructed && e.len_rules() == ::asn1_cereal::ber::enc::LenEnc::Indefinite {
+        try!(::asn1_cereal::tag::write_taglen(tag, ::asn1_cereal::tag::Len::Indef, &mut component));
+        component.extend(bytes);
+        try!(::asn1_cereal::tag::Len::write_indef_end(&mut component));
+      } else {
+        let len: ::asn1_cereal::tag::Len = Some(bytes.len() as ::asn1_cereal::tag::LenNum).into();
+        try!(::asn1_cereal::tag::write_taglen(tag, len, &mut component));
+        component.extend(bytes);
+      }
+      components.push((tag, component));
+    );
+    if field.optional {
+      quote!(
+        if let &Some(ref value) = &self.#ident {
+          #tag_encode
+        }
+      )
+    } else if field.default {
+      // DER and CER leave out a component that's equal to its DEFAULT value.
+      let default = field.default_value(ty);
+      quote!(
+        let value = &self.#ident;
+        if !e.canonical() || *value != #default {
+          #tag_encode
+        }
+      )
+    } else {
+      quote!(
+        let value = &self.#ident;
+        #tag_encode
+      )
+    }
+  }).collect();
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerSerialize for #name #ty_generics #where_clause {
+      fn serialize_value<E: ::asn1_cereal::BerEncRules, W: ::std::io::Write>
+          (&self, e: E, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        let is_implicit =
+          e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit;
+        let mut components: Vec<(::asn1_cereal::tag::Tag, Vec<u8>)> = Vec::new();
+
+        #( { #actions } )*
+
+        // Canonical encoding rules require the components in tag order.
+        if e.canonical() {
+          components.sort_by_key(|&(tag, _)| (u8::from(tag.class), tag.tagnum));
+        }
+        for (_, component) in components {
+          try!(writer.write_all(&component));
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+pub fn ber_set_deserialize(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+  let fields = set_fields(ast);
+
+  let f_idents: Vec<syn::Ident> = fields.iter().map(|v| {
+    format!("field_{}", v.ident.as_ref().expect("Requires named idents")).into()
+  }).collect();
+  let t_idents: Vec<syn::Ident> = fields.iter().map(|v| {
+    format!("tag_{}", v.ident.as_ref().expect("Requires named idents")).into()
+  }).collect();
+
+  let mut init = Vec::new();
+  let mut arms = Vec::new();
+  let mut assignments = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let f_ident = &f_idents[i];
+    let t_ident = &t_idents[i];
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    if field.defined_by.is_some() {
+      panic!("The defined_by attribute isn't supported by a SET");
+    }
+    let (info_ty, convert) = match field.time_conversion() {
+      Some((time_ty, _, from_time)) => (
+        time_ty.clone(),
+        quote!(
+          let value: #time_ty = value;
+          try!(::asn1_cereal::ber::serial::time::Asn1Time::#from_time(value)
+            .ok_or(::asn1_cereal::err::DecodeError::InvalidTime))
+        ),
+      ),
+      None => (quote!(#ty), quote!(value)),
+    };
+    let tag = field.component_tag(&info_ty, i as u64);
+
+    // An OPTIONAL field is already an Option.
+    if field.optional {
+      init.push(quote!(let mut #f_ident: #ty = None;));
+      assignments.push(quote!(#ident: #f_ident));
+    } else if field.default {
+      let default = field.default_value(ty);
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(#ident: #f_ident.unwrap_or_else(|| #default)));
+    } else {
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(
+        #ident: try!(#f_ident.ok_or(::asn1_cereal::err::DecodeError::MissingComponent))
+      ));
+    }
+    init.push(quote!(let #t_ident = #tag;));
+
+    arms.push(quote!(
+      t if ::asn1_cereal::ber::enc::tag_matches(e, t, #t_ident) => {
+        if #f_ident.is_some() {
+          return Err(::asn1_cereal::err::DecodeError::DuplicateComponent);
+        }
+        try!(::asn1_cereal::ber::enc::check_len(e, t, len));
+        // If we are decoding with an implicit tag, deserialize value directly.
+        let value = if is_implicit {
+          try!(::asn1_cereal::ber::serial::traits::deserialize_implicit(e, reader, len))
+        } else {
+          let value = try!(::asn1_cereal::BerDeserialize::deserialize_enc(e, reader));
+          if len == ::asn1_cereal::tag::Len::Indef {
+            try!(::asn1_cereal::tag::Len::read_indef_end(reader));
+          }
+          value
+        };
+        #f_ident = Some({ #convert });
+      }
+    ));
+  }
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::BerDeserialize for #name #ty_generics #where_clause {
+      fn _reads_indef_end<E: ::asn1_cereal::BerEncRules>(_: E) -> bool {
+        true
+      }
+
+      fn deserialize_value<E: ::asn1_cereal::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, len: ::asn1_cereal::tag::Len) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        let is_implicit =
+          e.tag_rules() == ::asn1_cereal::ber::enc::TagEnc::Implicit;
+        let indefinite = len == ::asn1_cereal::tag::Len::Indef;
+        let mut byte_reader = ::asn1_cereal::byte::ByteReader::new(reader, len.into());
+        let reader = &mut byte_reader;
+
+        #( #init )*
+
+        // Components can appear in any order, except with canonical encoding
+        // rules, where they must be in tag order.
+        let mut prev: Option<::asn1_cereal::tag::Tag> = None;
+        loop {
+          if !indefinite && reader.reached_limit() {
+            break;
+          }
+          let (this_tag, len) = try!(::asn1_cereal::tag::read_taglen_enc(e, reader));
+          // Handle end of indefinite length encoding.
+          if indefinite && this_tag.tagnum == 0 &&
+             this_tag.class == ::asn1_cereal::tag::Class::Universal &&
+             len == ::asn1_cereal::tag::Len::Def(0) {
+            break;
+          }
+          if e.canonical() && prev.map_or(false, |p|
+               (u8::from(p.class), p.tagnum) > (u8::from(this_tag.class), this_tag.tagnum)) {
+            return Err(::asn1_cereal::err::DecodeError::UnsortedSet);
+          }
+          prev = Some(this_tag);
+
+          match this_tag {
+            #( #arms )*
+            // An unknown element in a definite length encoding can be
+            // skipped with the rest of the bytes, like those after the last
+            // component of a SEQUENCE.
+            _ => {
+              if indefinite || !e.tolerate(::asn1_cereal::ber::enc::Deviation::TrailingBytes) {
+                return Err(::asn1_cereal::err::DecodeError::TagTypeMismatch);
+              }
+              while !reader.reached_limit() {
+                try!(reader.read());
+              }
+              break;
+            },
+          }
+        }
+        if reader.exceeded {
+          return Err(::asn1_cereal::err::DecodeError::GreaterLen);
+        }
+
+        Ok(#name {
+          #(#assignments),*
+        })
+      }
+    }
+  }
+}
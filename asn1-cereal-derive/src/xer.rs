@@ -0,0 +1,306 @@
+use quote::Tokens;
+use syn;
+
+use field;
+use bits::{bits_to_struct, struct_to_bits};
+use per::{field_info, choice_variants};
+use set::set_fields;
+
+/// Generate the XerSerialize implementation for a form.
+pub fn xer_serialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" | "set" => xer_sequence_encode(ast),
+    "choice" => xer_choice_encode(ast),
+    "enumerated" => xer_enumerated_encode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      ::asn1_cereal::xer::serial::seq_of::encode_seq_of(self.into_iter(), writer)
+    ),
+    "alias" => quote!(
+      ::asn1_cereal::XerSerialize::xer_encode(&self.0, writer)
+    ),
+    "null" => quote!(Ok(())),
+    "bits" => xer_bits_encode(ast),
+    _ => panic!("Unknown serialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::XerSerialize for #name #ty_generics #where_clause {
+      fn xer_encode<W: ::std::io::Write>(&self, writer: &mut W) -> Result<(), ::asn1_cereal::err::EncodeError> {
+        #body
+      }
+    }
+  }
+}
+
+/// Generate the XerDeserialize implementation for a form.
+pub fn xer_deserialize(ast: &syn::MacroInput, form: &str) -> Tokens {
+  let name = &ast.ident;
+  let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+  let body = match form {
+    "sequence" | "seq" => xer_sequence_decode(ast, false),
+    "set" => xer_sequence_decode(ast, true),
+    "choice" => xer_choice_decode(ast),
+    "enumerated" => xer_enumerated_decode(ast),
+    "seq of" | "sequence of" | "set of" => quote!(
+      let items: Vec<_> = try!(::asn1_cereal::xer::serial::seq_of::decode_seq_of(reader));
+      Ok(items.into_iter().collect())
+    ),
+    "alias" => quote!(
+      Ok(#name(try!(::asn1_cereal::XerDeserialize::xer_decode(reader))))
+    ),
+    "null" => quote!(
+      try!(<() as ::asn1_cereal::XerDeserialize>::xer_decode(reader));
+      Ok(#name)
+    ),
+    "bits" => {
+      let build = bits_to_struct(ast);
+      quote!(
+        // Any unknown bits are ignored.
+        let bits: ::asn1_cereal::BitString = try!(::asn1_cereal::XerDeserialize::xer_decode(reader));
+        Ok(#build)
+      )
+    },
+    _ => panic!("Unknown deserialize form {}", form),
+  };
+
+  quote! {
+    impl #impl_generics ::asn1_cereal::XerDeserialize for #name #ty_generics #where_clause {
+      fn xer_decode<I: Iterator<Item=::std::io::Result<u8>>>
+          (reader: &mut ::asn1_cereal::xer::xml::XmlReader<I>) -> Result<Self, ::asn1_cereal::err::DecodeError> {
+        #body
+      }
+    }
+  }
+}
+
+fn xer_sequence_encode(ast: &syn::MacroInput) -> Tokens {
+  let fields = set_fields(ast);
+
+  let actions: Vec<_> = fields.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    let field = field::Field::parse(&v.attrs);
+    let (_, convert) = field_info(v, &field, true);
+    let encode = quote!(
+      #convert
+      try!(::asn1_cereal::xer::xml::write_start(writer, fields[#i]));
+      try!(::asn1_cereal::XerSerialize::xer_encode(value, writer));
+      try!(::asn1_cereal::xer::xml::write_end(writer, fields[#i]));
+    );
+    if field.optional {
+      quote!(
+        if let Some(ref value) = self.#ident {
+          #encode
+        }
+      )
+    } else {
+      quote!({
+        let value = &self.#ident;
+        #encode
+      })
+    }
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    #(#actions)*
+    Ok(())
+  )
+}
+
+fn xer_sequence_decode(ast: &syn::MacroInput, is_set: bool) -> Tokens {
+  let name = &ast.ident;
+  let fields = set_fields(ast);
+
+  let mut init = Vec::new();
+  let mut actions = Vec::new();
+  let mut assignments = Vec::new();
+  for (i, v) in fields.iter().enumerate() {
+    let ident = &v.ident;
+    let f_ident: syn::Ident = format!("field_{}", ident.as_ref().expect("Requires named idents")).into();
+    let ty = &v.ty;
+    let field = field::Field::parse(&v.attrs);
+    let (_, convert) = field_info(v, &field, false);
+
+    // An OPTIONAL field is already an Option.
+    if field.optional {
+      init.push(quote!(let mut #f_ident: #ty = None;));
+      assignments.push(quote!(#ident: #f_ident));
+    } else if field.default {
+      let default = field.default_value(&v.ty);
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(#ident: #f_ident.unwrap_or_else(|| #default)));
+    } else {
+      init.push(quote!(let mut #f_ident: Option<#ty> = None;));
+      assignments.push(quote!(
+        #ident: try!(#f_ident.ok_or(::asn1_cereal::err::DecodeError::MissingComponent))
+      ));
+    }
+    // The components of a SEQUENCE must be in order, but a SET's can be in
+    // any order.
+    let order = if is_set {
+      quote!()
+    } else {
+      quote!(
+        if #i < next {
+          return Err(::asn1_cereal::err::DecodeError::InvalidXml);
+        }
+        next = #i + 1;
+      )
+    };
+    actions.push(quote!(
+      if child == fields[#i] {
+        #order
+        if #f_ident.is_some() {
+          return Err(::asn1_cereal::err::DecodeError::DuplicateComponent);
+        }
+        #f_ident = Some({
+          let value = try!(::asn1_cereal::XerDeserialize::xer_decode(reader));
+          #convert
+        });
+      } else
+    ));
+  }
+
+  if !is_set {
+    init.push(quote!(let mut next = 0;));
+  }
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    #(#init)*
+    while let Some(child) = try!(reader.child()) {
+      #(#actions)* {
+        return Err(::asn1_cereal::err::DecodeError::InvalidXml);
+      }
+      try!(reader.end(&child));
+    }
+    Ok(#name {
+      #(#assignments),*
+    })
+  )
+}
+
+fn xer_choice_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+
+  let actions: Vec<_> = variants.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    match v.data {
+      syn::VariantData::Tuple(_) => quote!(
+        #name::#ident(ref item) => {
+          try!(::asn1_cereal::xer::xml::write_start(writer, fields[#i]));
+          try!(::asn1_cereal::XerSerialize::xer_encode(item, writer));
+          try!(::asn1_cereal::xer::xml::write_end(writer, fields[#i]));
+        }
+      ),
+      // A unit variant is a NULL, which has no content.
+      _ => quote!(
+        #name::#ident => try!(::asn1_cereal::xer::xml::write_empty(writer, fields[#i]))
+      ),
+    }
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    match *self {
+      #(#actions),*
+    }
+    Ok(())
+  )
+}
+
+fn xer_choice_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let variants = choice_variants(ast);
+
+  let actions: Vec<_> = variants.iter().enumerate().map(|(i, v)| {
+    let ident = &v.ident;
+    let value = match v.data {
+      syn::VariantData::Tuple(_) => quote!(
+        #name::#ident(try!(::asn1_cereal::XerDeserialize::xer_decode(reader)))
+      ),
+      _ => quote!(#name::#ident),
+    };
+    quote!(
+      if child == fields[#i] {
+        #value
+      } else
+    )
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    let child = try!(try!(reader.child()).ok_or(::asn1_cereal::err::DecodeError::InvalidXml));
+    let value = #(#actions)* {
+      return Err(::asn1_cereal::err::DecodeError::InvalidXml);
+    };
+    try!(reader.end(&child));
+    Ok(value)
+  )
+}
+
+/// Get the variants of an ENUMERATED with their index, skipping the variant
+/// for unknown values.
+pub fn enumerated_named(ast: &syn::MacroInput) -> Vec<(usize, &syn::Ident)> {
+  choice_variants(ast).iter().enumerate().filter(|&(_, v)| v.data == syn::VariantData::Unit)
+    .map(|(i, v)| (i, &v.ident)).collect()
+}
+
+fn xer_enumerated_encode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let unknown: Vec<_> = choice_variants(ast).iter().filter(|v| v.data != syn::VariantData::Unit).map(|v| {
+    let ident = &v.ident;
+    quote!(
+      #name::#ident(..) =>
+        return Err(::asn1_cereal::err::EncodeError::Custom("Unknown ENUMERATED values can't be encoded with XER"))
+    )
+  }).collect();
+  let arms: Vec<_> = enumerated_named(ast).into_iter().map(|(i, ident)| {
+    quote!(#name::#ident => fields[#i])
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    let identifier = match *self {
+      #(#arms,)*
+      #(#unknown,)*
+    };
+    try!(::asn1_cereal::xer::xml::write_empty(writer, identifier));
+    Ok(())
+  )
+}
+
+fn xer_enumerated_decode(ast: &syn::MacroInput) -> Tokens {
+  let name = &ast.ident;
+  let actions: Vec<_> = enumerated_named(ast).into_iter().map(|(i, ident)| {
+    quote!(
+      if child == fields[#i] {
+        #name::#ident
+      } else
+    )
+  }).collect();
+
+  quote!(
+    let fields = <Self as ::asn1_cereal::Asn1Info>::asn1_fields();
+    let child = try!(try!(reader.child()).ok_or(::asn1_cereal::err::DecodeError::InvalidXml));
+    let value = #(#actions)* {
+      return Err(::asn1_cereal::err::DecodeError::UnknownEnumerated);
+    };
+    try!(reader.end(&child));
+    Ok(value)
+  )
+}
+
+fn xer_bits_encode(ast: &syn::MacroInput) -> Tokens {
+  let bits = struct_to_bits(ast, false);
+
+  quote!(
+    #bits
+    ::asn1_cereal::XerSerialize::xer_encode(&bits, writer)
+  )
+}
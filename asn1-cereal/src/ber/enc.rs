@@ -9,8 +9,26 @@
 //! let mut bytes: Vec<u8> = Vec::new();
 //! let _ = BerSerialize::serialize_enc(&4u64, DER, &mut bytes);
 //! ```
+//!
+//! When the encoding rules are only known at runtime, like from configuration,
+//! `EncodingRules` can be used instead.
+//!
+//! ```
+//! use asn1_cereal::{BerSerialize, EncodingRules};
+//!
+//! let rules: EncodingRules = "DER".parse().unwrap();
+//! let mut bytes: Vec<u8> = Vec::new();
+//! let _ = BerSerialize::serialize_enc(&4u64, rules, &mut bytes);
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use tag;
+use err;
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 /// An enum which determines how tags are encoded.
 pub enum TagEnc {
   /// When encoding, all tags will be encoded as explicit tags.
@@ -25,7 +43,7 @@ pub enum TagEnc {
   Implicit,
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 /// An enum which determines how lengths are encoded.
 pub enum LenEnc {
   /// When encoding, all lengths are encoded using the definite form.
@@ -41,15 +59,120 @@ pub enum LenEnc {
 /// A trait to define encoding rules to use while encoding ASN.1.
 pub trait BerEncRules: Copy {
   /// Get the encoding rules for tags.
-  fn tag_rules() -> TagEnc;
+  fn tag_rules(self) -> TagEnc;
 
   /// Get the encoding rules for lengths.
-  fn len_rules() -> LenEnc;
+  fn len_rules(self) -> LenEnc;
 
   /// Returns true when primitive elements should be encoded using
   /// the shortest form. If this is true when decoding, it should
   /// be an error to not use the shortest form.
-  fn shortest_form() -> bool;
+  fn shortest_form(self) -> bool;
+
+  /// Returns true when these rules are canonical, so every value has exactly
+  /// one encoding. When encoding, the elements of a SET OF are sorted. When
+  /// decoding, it's an error to use any other encoding.
+  fn canonical(self) -> bool {
+    false
+  }
+
+  /// Get the maximum number of content octets in a primitive string
+  /// (like OCTET STRING or BIT STRING). When encoding, longer strings are split
+  /// into segments of this size using the constructed form. When decoding, it's
+  /// an error for segments to be any other size.
+  ///
+  /// If this is None, strings are always encoded using the primitive form.
+  fn segment_size(self) -> Option<tag::LenNum> {
+    None
+  }
+
+  /// Returns true when a known deviation from these rules is accepted when
+  /// decoding, without reporting it.
+  fn tolerates(self, deviation: Deviation) -> bool {
+    let _ = deviation;
+    false
+  }
+
+  /// Called when decoding finds a known deviation from these rules. Returns
+  /// true when the deviation should be accepted, rather than returning an
+  /// error.
+  fn tolerate(self, deviation: Deviation) -> bool {
+    self.tolerates(deviation)
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// A known deviation from the encoding rules, made by some real-world
+/// encoders, which `Lenient` encoding rules can accept when decoding.
+pub enum Deviation {
+  /// A tag has the wrong constructed bit, but otherwise matches.
+  ConstructedBit,
+  /// An INTEGER has redundant leading octets, where the encoding rules
+  /// require the shortest form.
+  PaddedInteger,
+  /// The unused bits of a BIT STRING aren't zero, where the encoding rules
+  /// require them to be.
+  UnusedBits,
+  /// A definite length SEQUENCE has bytes after its last component, or a
+  /// definite length SET has an unknown element, which is skipped with the
+  /// bytes after it.
+  TrailingBytes,
+}
+
+impl Deviation {
+  /// All known deviations.
+  pub fn all() -> &'static [Deviation] {
+    &[Deviation::ConstructedBit, Deviation::PaddedInteger, Deviation::UnusedBits,
+      Deviation::TrailingBytes]
+  }
+
+  fn bit(self) -> u8 {
+    1 << self as u8
+  }
+}
+
+/// Check that the length of an element is allowed by a set of encoding rules.
+pub fn check_len<E: BerEncRules>(e: E, tag: tag::Tag, len: tag::Len) -> Result<(), err::DecodeError> {
+  if len == tag::Len::Indef {
+    // Return an error if the encoding rules only allow definite length
+    // encoding.
+    if e.len_rules() == LenEnc::Definite {
+      warn!("Encountered indefinite length encoding, but encoding rules don't allow this");
+      return Err(err::DecodeError::IndefiniteLen);
+    // If this element is primitve, the length isn't allowed to be indefinite length.
+    } else if !tag.constructed {
+      warn!("Encountered indefinite length encoding, but this is a primitive element");
+      return Err(err::DecodeError::PrimIndef);
+    }
+  } else if tag.constructed && e.canonical() && e.len_rules() == LenEnc::Indefinite {
+    warn!("Encountered definite length encoding, but encoding rules require indefinite length");
+    return Err(err::DecodeError::DefiniteLen);
+  }
+  Ok(())
+}
+
+/// Returns true when a decoded tag matches the expected tag. A tag that only
+/// has the wrong constructed bit matches when the encoding rules tolerate
+/// `Deviation::ConstructedBit`.
+pub fn tag_matches<E: BerEncRules>(e: E, tag: tag::Tag, expected: tag::Tag) -> bool {
+  if tag == expected {
+    return true;
+  }
+  if tag.class == expected.class && tag.tagnum == expected.tagnum &&
+     e.tolerate(Deviation::ConstructedBit) {
+    warn!("Accepted tag {} with the wrong constructed bit, expected {}", tag, expected);
+    return true;
+  }
+  false
+}
+
+/// Like `tag_matches`, but a tolerated deviation isn't reported. This is for
+/// checking a tag that is checked again when the element is decoded, like
+/// the alternatives of a CHOICE.
+pub fn tag_may_match<E: BerEncRules>(e: E, tag: tag::Tag, expected: tag::Tag) -> bool {
+  tag == expected ||
+    (tag.class == expected.class && tag.tagnum == expected.tagnum &&
+     e.tolerates(Deviation::ConstructedBit))
 }
 
 #[derive(Copy, Clone)]
@@ -62,15 +185,19 @@ pub trait BerEncRules: Copy {
 pub struct DER;
 
 impl BerEncRules for DER {
-  fn tag_rules() -> TagEnc {
+  fn tag_rules(self) -> TagEnc {
     TagEnc::Implicit
   }
 
-  fn len_rules() -> LenEnc {
+  fn len_rules(self) -> LenEnc {
     LenEnc::Definite
   }
 
-  fn shortest_form() -> bool {
+  fn shortest_form(self) -> bool {
+    true
+  }
+
+  fn canonical(self) -> bool {
     true
   }
 }
@@ -85,15 +212,15 @@ impl BerEncRules for DER {
 pub struct BER;
 
 impl BerEncRules for BER {
-  fn tag_rules() -> TagEnc {
+  fn tag_rules(self) -> TagEnc {
     TagEnc::Explicit
   }
 
-  fn len_rules() -> LenEnc {
+  fn len_rules(self) -> LenEnc {
     LenEnc::Definite
   }
 
-  fn shortest_form() -> bool {
+  fn shortest_form(self) -> bool {
     false
   }
 }
@@ -112,15 +239,277 @@ impl BerEncRules for BER {
 pub struct BERAlt;
 
 impl BerEncRules for BERAlt {
-  fn tag_rules() -> TagEnc {
+  fn tag_rules(self) -> TagEnc {
+    TagEnc::Implicit
+  }
+
+  fn len_rules(self) -> LenEnc {
+    LenEnc::Indefinite
+  }
+
+  fn shortest_form(self) -> bool {
+    true
+  }
+}
+
+#[derive(Copy, Clone)]
+/// Canonical Encoding Rules are a subset of BER, and like DER provide a
+/// single encoding for every value. Implicit tags and indefinite length
+/// encoding are used for all constructed elements, and strings longer than
+/// 1000 octets are split into segments, so large values can be streamed.
+///
+/// Note: When decoding elements, invalid CER will cause an error.
+pub struct CER;
+
+impl BerEncRules for CER {
+  fn tag_rules(self) -> TagEnc {
     TagEnc::Implicit
   }
 
-  fn len_rules() -> LenEnc {
+  fn len_rules(self) -> LenEnc {
     LenEnc::Indefinite
   }
 
-  fn shortest_form() -> bool {
+  fn shortest_form(self) -> bool {
+    true
+  }
+
+  fn canonical(self) -> bool {
+    true
+  }
+
+  fn segment_size(self) -> Option<tag::LenNum> {
+    Some(1000)
+  }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Encoding rules that are chosen at runtime, like from configuration.
+///
+/// Each option matches a method of `BerEncRules`. Since this is a single type,
+/// code using it is only generated once, rather than for each of DER, BER,
+/// BERAlt and CER.
+pub struct EncodingRules {
+  /// The encoding rules for tags.
+  pub tags: TagEnc,
+  /// The encoding rules for lengths.
+  pub lengths: LenEnc,
+  /// Whether primitive elements use the shortest form.
+  pub shortest_form: bool,
+  /// Whether every value has exactly one encoding.
+  pub canonical: bool,
+  /// The maximum number of content octets in a primitive string.
+  pub segment_size: Option<tag::LenNum>,
+}
+
+impl EncodingRules {
+  /// Get the options of another set of encoding rules, like
+  /// `EncodingRules::new(DER)`.
+  pub fn new<E: BerEncRules>(e: E) -> Self {
+    EncodingRules {
+      tags: e.tag_rules(),
+      lengths: e.len_rules(),
+      shortest_form: e.shortest_form(),
+      canonical: e.canonical(),
+      segment_size: e.segment_size(),
+    }
+  }
+}
+
+impl BerEncRules for EncodingRules {
+  fn tag_rules(self) -> TagEnc {
+    self.tags
+  }
+
+  fn len_rules(self) -> LenEnc {
+    self.lengths
+  }
+
+  fn shortest_form(self) -> bool {
+    self.shortest_form
+  }
+
+  fn canonical(self) -> bool {
+    self.canonical
+  }
+
+  fn segment_size(self) -> Option<tag::LenNum> {
+    self.segment_size
+  }
+}
+
+impl FromStr for EncodingRules {
+  type Err = ParseRulesError;
+
+  /// Get encoding rules by name, which is one of `BER`, `DER`, `CER` or
+  /// `BERAlt`, ignoring case.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_uppercase().as_str() {
+      "BER" => Ok(EncodingRules::new(BER)),
+      "DER" => Ok(EncodingRules::new(DER)),
+      "CER" => Ok(EncodingRules::new(CER)),
+      "BERALT" => Ok(EncodingRules::new(BERAlt)),
+      _ => Err(ParseRulesError),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An error returned when a string isn't the name of a set of encoding rules.
+pub struct ParseRulesError;
+
+impl fmt::Display for ParseRulesError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "unknown encoding rules")
+  }
+}
+
+impl Error for ParseRulesError {}
+
+#[derive(Copy, Clone)]
+/// Encoding rules for decoding data from encoders that don't follow the
+/// rules, which accept a list of known deviations.
+///
+/// Other than the tolerated deviations, another set of encoding rules are
+/// used. Deviations that those rules already allow, like redundant INTEGER
+/// octets with BER, aren't reported. Every tolerated deviation is passed to a
+/// warning callback, instead of returning an error. The callback is borrowed,
+/// so it can capture context like the peer that sent the data.
+///
+/// ```
+/// use asn1_cereal::{BerDeserialize, Lenient, DER};
+/// use asn1_cereal::ber::enc::Deviation;
+///
+/// let peer = "192.0.2.1";
+/// let warning = |deviation: Deviation| {
+///   println!("Accepted invalid DER from {}: {:?}", peer, deviation);
+/// };
+///
+/// let rules = Lenient::new(DER, &warning).only(&[Deviation::PaddedInteger]);
+/// let bytes = [0x02, 0x02, 0x00, 0x05];
+/// let value = u32::deserialize_enc(rules, &mut bytes.iter().map(|&b| Ok(b)));
+/// assert_eq!(value.unwrap(), 5);
+/// ```
+pub struct Lenient<'a> {
+  /// The encoding rules used, other than the tolerated deviations.
+  pub rules: EncodingRules,
+  tolerated: u8,
+  warning: &'a dyn Fn(Deviation),
+}
+
+impl<'a> Lenient<'a> {
+  /// Create lenient encoding rules based on another set of encoding rules,
+  /// which tolerate every known deviation, and report each one to `warning`.
+  pub fn new<E: BerEncRules>(e: E, warning: &'a dyn Fn(Deviation)) -> Self {
+    Lenient {
+      rules: EncodingRules::new(e),
+      tolerated: Deviation::all().iter().fold(0, |bits, d| bits | d.bit()),
+      warning: warning,
+    }
+  }
+
+  /// Only tolerate the given deviations.
+  pub fn only(self, deviations: &[Deviation]) -> Self {
+    Lenient {
+      tolerated: deviations.iter().fold(0, |bits, d| bits | d.bit()),
+      .. self
+    }
+  }
+}
+
+impl<'a> fmt::Debug for Lenient<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Lenient")
+      .field("rules", &self.rules)
+      .field("tolerated", &self.tolerated)
+      .finish()
+  }
+}
+
+impl<'a> BerEncRules for Lenient<'a> {
+  fn tag_rules(self) -> TagEnc {
+    self.rules.tags
+  }
+
+  fn len_rules(self) -> LenEnc {
+    self.rules.lengths
+  }
+
+  fn shortest_form(self) -> bool {
+    self.rules.shortest_form
+  }
+
+  fn canonical(self) -> bool {
+    self.rules.canonical
+  }
+
+  fn segment_size(self) -> Option<tag::LenNum> {
+    self.rules.segment_size
+  }
+
+  fn tolerates(self, deviation: Deviation) -> bool {
+    self.tolerated & deviation.bit() != 0
+  }
+
+  fn tolerate(self, deviation: Deviation) -> bool {
+    if !self.tolerates(deviation) {
+      return false;
+    }
+    (self.warning)(deviation);
     true
   }
 }
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use ::{BerSerialize, BerDeserialize, OctetString};
+
+#[test]
+fn runtime_rules() {
+  let value = OctetString(vec![7; 1001]);
+  for name in &["BER", "der", "CER", "BERAlt"] {
+    let rules: EncodingRules = name.parse().unwrap();
+    let mut expected = Vec::new();
+    let mut buf = Vec::new();
+    match *name {
+      "BER" => value.serialize_enc(BER, &mut expected).unwrap(),
+      "der" => value.serialize_enc(DER, &mut expected).unwrap(),
+      "CER" => value.serialize_enc(CER, &mut expected).unwrap(),
+      _ => value.serialize_enc(BERAlt, &mut expected).unwrap(),
+    }
+    value.serialize_enc(rules, &mut buf).unwrap();
+    assert_eq!(buf, expected);
+    assert_eq!(OctetString::deserialize_enc(rules, buf.bytes().by_ref()).unwrap().0, value.0);
+  }
+  assert_eq!(EncodingRules::new(CER).segment_size, Some(1000));
+  assert!("PER".parse::<EncodingRules>().is_err());
+}
+
+#[test]
+fn lenient_rules() {
+  let ignore = |_| {};
+  let padded = b"\x02\x02\x00\x05";
+  let constructed = b"\x22\x01\x05";
+  let unused = b"\x03\x02\x07\x81";
+  match u32::deserialize_enc(DER, padded.bytes().by_ref()) {
+    Err(err::DecodeError::NonMinimalInteger) => {},
+    res => panic!("Expected NonMinimalInteger, got {:?}", res),
+  }
+  match u32::deserialize_enc(DER, constructed.bytes().by_ref()) {
+    Err(err::DecodeError::TagTypeMismatch) => {},
+    res => panic!("Expected TagTypeMismatch, got {:?}", res),
+  }
+
+  let rules = Lenient::new(DER, &ignore);
+  assert_eq!(u32::deserialize_enc(rules, padded.bytes().by_ref()).unwrap(), 5);
+  assert_eq!(u32::deserialize_enc(rules, constructed.bytes().by_ref()).unwrap(), 5);
+  let bits = ::BitString::deserialize_enc(rules, unused.bytes().by_ref()).unwrap();
+  assert_eq!(bits.as_bytes(), &[0x80]);
+
+  // Only the listed deviations are tolerated.
+  let rules = rules.only(&[Deviation::PaddedInteger]);
+  assert_eq!(u32::deserialize_enc(rules, padded.bytes().by_ref()).unwrap(), 5);
+  assert!(u32::deserialize_enc(rules, constructed.bytes().by_ref()).is_err());
+  assert!(::BitString::deserialize_enc(rules, unused.bytes().by_ref()).is_err());
+}
@@ -0,0 +1,306 @@
+//! Implementation of BIT STRING.
+//!
+//! A BIT STRING is encoded as an initial octet giving the number of unused bits
+//! in the final octet, followed by the bits themselves, most significant bit first.
+//!
+//! When decoding with encoding rules that don't require the shortest form, the
+//! constructed (segmented) form is also accepted. With CER, BIT STRINGs longer
+//! than 1000 octets are always segmented.
+
+use std::io;
+
+use ::{BerSerialize, BerDeserialize, Asn1Info};
+use tag;
+use err;
+use ber::enc::Deviation;
+use byte::{read_byte, write_byte, ByteReader};
+use ber::serial::segment;
+
+/// A Rust wrapper for BIT STRING.
+///
+/// Any unused bits in the final octet are always kept as zero, as required by DER.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BitString {
+  bytes: Vec<u8>,
+  unused: u8,
+}
+
+asn1_info!(BitString, [PRIM UNIVERSAL 3], "BIT STRING");
+impl ::Asn1Schema for BitString {}
+
+impl BitString {
+  /// Create an empty BIT STRING.
+  pub fn new() -> Self {
+    BitString::default()
+  }
+
+  /// Create a BIT STRING of `len` bits, all set to zero.
+  pub fn with_len(len: u64) -> Self {
+    let mut bits = BitString::new();
+    bits.resize(len);
+    bits
+  }
+
+  /// Create a BIT STRING from bytes and a count of unused bits in the final byte.
+  /// Any unused bits that are set will be cleared.
+  pub fn from_bytes(mut bytes: Vec<u8>, unused: u8) -> Result<Self, err::DecodeError> {
+    if unused > 7 || (bytes.is_empty() && unused != 0) {
+      return Err(err::DecodeError::InvalidBitString);
+    }
+    if let Some(last) = bytes.last_mut() {
+      *last &= 0xff << unused;
+    }
+    Ok(BitString {
+      bytes: bytes,
+      unused: unused,
+    })
+  }
+
+  /// Get the bytes holding the bits in this BIT STRING.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.bytes
+  }
+
+  /// Get the number of unused bits in the final byte.
+  pub fn unused_bits(&self) -> u8 {
+    self.unused
+  }
+
+  /// Get the number of bits in this BIT STRING.
+  pub fn len(&self) -> u64 {
+    self.bytes.len() as u64 * 8 - self.unused as u64
+  }
+
+  /// Returns true when this BIT STRING has no bits.
+  pub fn is_empty(&self) -> bool {
+    self.bytes.is_empty()
+  }
+
+  /// Get the bit at index `i`, where bit 0 is the most significant bit of the
+  /// first byte. Bits beyond the end are treated as zero.
+  pub fn get(&self, i: u64) -> bool {
+    if i >= self.len() {
+      return false;
+    }
+    self.bytes[(i / 8) as usize] & (0x80 >> (i % 8)) != 0
+  }
+
+  /// Set the bit at index `i`, growing the BIT STRING if required.
+  pub fn set(&mut self, i: u64, value: bool) {
+    if i >= self.len() {
+      if !value {
+        return;
+      }
+      self.resize(i + 1);
+    }
+    let mask = 0x80 >> (i % 8);
+    if value {
+      self.bytes[(i / 8) as usize] |= mask;
+    } else {
+      self.bytes[(i / 8) as usize] &= !mask;
+    }
+  }
+
+  /// Change the number of bits in this BIT STRING. New bits are set to zero.
+  pub fn resize(&mut self, len: u64) {
+    self.bytes.resize(((len + 7) / 8) as usize, 0);
+    self.unused = ((8 - len % 8) % 8) as u8;
+    if let Some(last) = self.bytes.last_mut() {
+      *last &= 0xff << self.unused;
+    }
+  }
+
+  /// Remove any trailing zero bits. DER requires this for BIT STRINGs that
+  /// are defined with a list of named bits.
+  pub fn trim(&mut self) {
+    let mut len = self.len();
+    while len > 0 && !self.get(len - 1) {
+      len -= 1;
+    }
+    self.resize(len);
+  }
+
+  /// Get an iterator over each bit in this BIT STRING.
+  pub fn iter(&self) -> Bits {
+    Bits {
+      bits: self,
+      pos: 0,
+    }
+  }
+
+  /// Append the bits from another BIT STRING to the end of this one.
+  pub fn extend(&mut self, other: &BitString) {
+    if self.unused == 0 {
+      self.bytes.extend_from_slice(&other.bytes);
+      self.unused = other.unused;
+    } else {
+      for bit in other.iter() {
+        let len = self.len();
+        self.resize(len + 1);
+        self.set(len, bit);
+      }
+    }
+  }
+
+  /// Decode the content of a constructed BIT STRING, which is a series of
+  /// BIT STRING segments. Every segment except the last must have no unused bits.
+  pub fn deserialize_constructed<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let mut bits = BitString::new();
+    let mut reader = ByteReader::new(reader, None);
+    let tagnum = Self::asn1_tag().map_or(3, |t| t.tagnum);
+    try!(segment::read_segments(e, &mut reader, len, tagnum, &mut |reader, seg_len| {
+      // Only the final segment may have unused bits.
+      if bits.unused != 0 {
+        return Err(err::DecodeError::InvalidBitString);
+      }
+      let segment = try!(BitString::deserialize_value(e, reader, tag::Len::Def(seg_len)));
+      bits.extend(&segment);
+      Ok(())
+    }));
+    Ok(bits)
+  }
+}
+
+impl From<Vec<u8>> for BitString {
+  fn from(bytes: Vec<u8>) -> Self {
+    BitString {
+      bytes: bytes,
+      unused: 0,
+    }
+  }
+}
+
+/// An iterator over the bits in a BIT STRING.
+pub struct Bits<'a> {
+  bits: &'a BitString,
+  pos: u64,
+}
+
+impl<'a> Iterator for Bits<'a> {
+  type Item = bool;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.pos >= self.bits.len() {
+      return None;
+    }
+    self.pos += 1;
+    Some(self.bits.get(self.pos - 1))
+  }
+}
+
+impl BerSerialize for BitString {
+  fn _serialize_enc<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Option<Result<(), err::EncodeError>> {
+    let tagnum = Self::asn1_tag().map_or(3, |t| t.tagnum);
+    segment::write_segmented(e, Self::asn1_tag(), tagnum, &self.bytes, Some(self.unused), writer)
+  }
+
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(write_byte(writer, self.unused));
+    try!(writer.write_all(&self.bytes));
+    Ok(())
+  }
+}
+
+impl BerDeserialize for BitString {
+  fn _deserialize_with_tag<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, tag: tag::Tag, len: tag::Len) -> Option<Result<Self, err::DecodeError>> {
+    // Handle the constructed form here, the primitive form uses the normal behaviour.
+    if segment::is_constructed_form(e, Self::asn1_tag(), tag) {
+      Some(Self::deserialize_constructed(e, reader, len))
+    } else {
+      None
+    }
+  }
+
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    if len_num == 0 {
+      return Err(err::DecodeError::InvalidBitString);
+    }
+    try!(segment::check_primitive(e, len_num));
+
+    let unused = try!(read_byte(reader));
+    let bytes: Result<Vec<u8>, _> = reader.take(len_num as usize - 1).collect();
+    let bytes = try!(bytes);
+    if bytes.len() as u64 != len_num - 1 {
+      return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                "Got unexpected EOF while reading stream").into());
+    }
+
+    // DER requires unused bits to be zero. Tolerated unused bits are cleared.
+    if e.shortest_form() && unused < 8 &&
+       bytes.last().map_or(false, |b| b & !(0xff << unused) != 0) &&
+       !e.tolerate(Deviation::UnusedBits) {
+      warn!("Encountered non-zero unused bits in a BIT STRING");
+      return Err(err::DecodeError::InvalidBitString);
+    }
+    BitString::from_bytes(bytes, unused)
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn bit_string_simple() {
+  let bytes = b"\x03\x03\x06\x6e\x40";
+  let mut bits = BitString::with_len(10);
+  for i in &[1, 2, 4, 5, 6, 9] {
+    bits.set(*i, true);
+  }
+  assert_eq!(bits.unused_bits(), 6);
+  assert_eq!(bits.len(), 10);
+  bits.resize(18);
+  bits.set(11, true);
+  bits.set(12, true);
+  bits.set(13, true);
+  assert_eq!(bits.iter().filter(|b| *b).count(), 9);
+  bits.trim();
+  assert_eq!(bits.len(), 14);
+  bits.resize(10);
+
+  let mut buf: Vec<u8> = Vec::new();
+  bits.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  let decoded = BitString::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(decoded, bits);
+  assert!(decoded.get(9));
+  assert!(!decoded.get(10));
+}
+
+#[test]
+fn bit_string_unused_bits() {
+  // BER allows non-zero unused bits, which are cleared.
+  let bytes = b"\x03\x02\x04\xff";
+  let decoded = BitString::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(decoded.as_bytes(), &[0xf0]);
+  match BitString::deserialize_enc(::DER, bytes.bytes().by_ref()) {
+    Err(err::DecodeError::InvalidBitString) => {},
+    res => panic!("Expected InvalidBitString, got {:?}", res),
+  }
+  // An empty BIT STRING can't have unused bits.
+  assert!(BitString::deserialize(b"\x03\x01\x01".bytes().by_ref()).is_err());
+  assert!(BitString::deserialize(b"\x03\x01\x00".bytes().by_ref()).unwrap().is_empty());
+}
+
+#[test]
+fn bit_string_constructed() {
+  let definite = b"\x23\x0c\x03\x03\x00\x6e\x5d\x23\x05\x03\x03\x06\xc0\x80";
+  let indefinite = b"\x23\x80\x03\x03\x00\x6e\x5d\x03\x03\x06\xc0\x80\x00\x00";
+  for bytes in &[&definite[..], &indefinite[..]] {
+    let decoded = BitString::deserialize(bytes.bytes().by_ref()).unwrap();
+    assert_eq!(decoded.as_bytes(), &[0x6e, 0x5d, 0xc0, 0x80]);
+    assert_eq!(decoded.len(), 26);
+  }
+  match BitString::deserialize_enc(::DER, definite.bytes().by_ref()) {
+    Err(err::DecodeError::SegmentedString) => {},
+    res => panic!("Expected SegmentedString, got {:?}", res),
+  }
+  // Only the final segment can have unused bits.
+  let bad = b"\x23\x0a\x03\x03\x01\x6e\x5c\x03\x03\x06\xc0\x80";
+  assert!(BitString::deserialize(bad.bytes().by_ref()).is_err());
+}
@@ -8,25 +8,54 @@ use err;
 use byte::{read_byte, write_byte};
 
 asn1_info!(bool, [PRIM UNIVERSAL 1], "BOOLEAN");
+impl ::Asn1Schema for bool {}
 
 impl BerSerialize for bool {
   fn serialize_value<E: ::BerEncRules, W: io::Write>
       (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
-    try!(write_byte(writer, if *self { 0x01 } else { 0x00 }));
+    try!(write_byte(writer, if *self { 0xff } else { 0x00 }));
     Ok(())
   }
 }
 
 impl BerDeserialize for bool {
   fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
-      (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
     let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    if len_num != 1 {
+      warn!("Encountered a BOOLEAN with {} content octets", len_num);
+      return Err(err::DecodeError::InvalidBool);
+    }
 
-    if len_num > 0 {
-      let byte = try!(read_byte(reader));
-      Ok(byte != 0x00)
-    } else {
-      Ok(false)
+    match try!(read_byte(reader)) {
+      0x00 => Ok(false),
+      0xff => Ok(true),
+      // DER requires true to be encoded as 0xFF.
+      _ if e.shortest_form() => {
+        warn!("Encountered a BOOLEAN that wasn't 0x00 or 0xFF");
+        Err(err::DecodeError::InvalidBool)
+      },
+      _ => Ok(true),
     }
   }
 }
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn bool_strict() {
+  let mut buf: Vec<u8> = Vec::new();
+  true.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x01\x01\xff");
+  assert_eq!(bool::deserialize_enc(::DER, buf.bytes().by_ref()).unwrap(), true);
+
+  // Any non-zero octet is true with BER, but not DER.
+  assert_eq!(bool::deserialize(b"\x01\x01\x01".bytes().by_ref()).unwrap(), true);
+  match bool::deserialize_enc(::DER, b"\x01\x01\x01".bytes().by_ref()) {
+    Err(err::DecodeError::InvalidBool) => {},
+    res => panic!("Expected InvalidBool, got {:?}", res),
+  }
+  assert!(bool::deserialize(b"\x01\x00".bytes().by_ref()).is_err());
+  assert!(bool::deserialize(b"\x01\x02\x00\x00".bytes().by_ref()).is_err());
+}
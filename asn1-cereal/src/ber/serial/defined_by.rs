@@ -0,0 +1,118 @@
+//! Support for ANY DEFINED BY, where the type of an open type field is
+//! determined by the value of another field (usually an OBJECT IDENTIFIER
+//! or INTEGER).
+//!
+//! A `Registry` maps each known key to a decoder, which turns the `RawElement`
+//! for the open type into a typed value. Keys that aren't registered fall back
+//! to a variant holding the `RawElement`.
+//!
+//! When deriving a SEQUENCE, an open type field can be marked with
+//! `#[asn1(defined_by="field")]`, where `field` is an earlier field. The
+//! field's type must implement `DefinedBy`, which is given the value of the
+//! earlier field when decoding.
+//!
+//! The open type is decoded with the same encoding rules as the element that
+//! holds it, so DER is still enforced inside it. A decoder in a `Registry` is
+//! given these as `EncodingRules`, so the deviations tolerated by `Lenient`
+//! aren't passed on.
+//!
+//! ```
+//! use asn1_cereal::{ObjectIdentifier, RawElement, Registry, DER};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Params {
+//!   Null(()),
+//!   Unknown(RawElement),
+//! }
+//!
+//! let mut registry = Registry::new();
+//! registry.register("1.2.840.113549.1.1.1".parse().unwrap(), Params::Null);
+//!
+//! let raw = RawElement::encode(&()).unwrap();
+//! let rsa: ObjectIdentifier = "1.2.840.113549.1.1.1".parse().unwrap();
+//! assert_eq!(registry.decode(DER, &rsa, raw.clone(), Params::Unknown).unwrap(), Params::Null(()));
+//! let other: ObjectIdentifier = "1.2.3".parse().unwrap();
+//! assert_eq!(registry.decode(DER, &other, raw.clone(), Params::Unknown).unwrap(), Params::Unknown(raw));
+//! ```
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use ::{BerSerialize, BerDeserialize, BerEncRules};
+use ber::enc::EncodingRules;
+use ber::serial::raw::RawElement;
+use err;
+
+/// A function that decodes an open type from a raw element, using a set of
+/// encoding rules.
+type Decoder<T> = Box<dyn Fn(EncodingRules, &RawElement) -> Result<T, err::DecodeError> + Send + Sync>;
+
+/// A registry mapping keys (like OBJECT IDENTIFIERs or integers) to decoders
+/// for an open type `T`.
+pub struct Registry<K: Eq + Hash, T> {
+  decoders: HashMap<K, Decoder<T>>,
+}
+
+impl<K: Eq + Hash, T: 'static> Registry<K, T> {
+  /// Create an empty registry.
+  pub fn new() -> Self {
+    Registry {
+      decoders: HashMap::new(),
+    }
+  }
+
+  /// Register a type for a key. The element is decoded as this type, and then
+  /// wrapped (usually by an enum variant) to create the open type.
+  pub fn register<V: BerDeserialize + 'static>(&mut self, key: K, wrap: fn(V) -> T) -> &mut Self {
+    self.register_fn(key, move |e: EncodingRules, raw: &RawElement| raw.decode_enc::<V, _>(e).map(wrap))
+  }
+
+  /// Register a custom decoder function for a key, which is given the encoding
+  /// rules of the element holding the open type.
+  pub fn register_fn<F>(&mut self, key: K, decoder: F) -> &mut Self
+      where F: Fn(EncodingRules, &RawElement) -> Result<T, err::DecodeError> + Send + Sync + 'static {
+    self.decoders.insert(key, Box::new(decoder));
+    self
+  }
+
+  /// Returns true when this key has a decoder registered.
+  pub fn contains(&self, key: &K) -> bool {
+    self.decoders.contains_key(key)
+  }
+
+  /// Decode an element using the decoder registered for a key, or pass it
+  /// to `fallback` if the key isn't registered.
+  pub fn decode<E: BerEncRules>(&self, e: E, key: &K, raw: RawElement, fallback: fn(RawElement) -> T)
+      -> Result<T, err::DecodeError> {
+    match self.decoders.get(key) {
+      Some(decoder) => decoder(EncodingRules::new(e), &raw),
+      None => {
+        debug!("No decoder registered for open type, using a raw element");
+        Ok(fallback(raw))
+      },
+    }
+  }
+}
+
+impl<K: Eq + Hash, T: 'static> Default for Registry<K, T> {
+  fn default() -> Self {
+    Registry::new()
+  }
+}
+
+/// An open type (ANY DEFINED BY), that is decoded based on the value of
+/// another field with the type `K`.
+///
+/// This is used by fields with the `defined_by` attribute when deriving
+/// a SEQUENCE. It's encoded using `BerSerialize`, like a CHOICE.
+pub trait DefinedBy<K>: BerSerialize + Sized {
+  /// Decode this open type from a raw element, given the value of the field
+  /// that defines it, and the encoding rules of the element holding it.
+  fn decode_defined_by<E: BerEncRules>(e: E, key: &K, raw: RawElement) -> Result<Self, err::DecodeError>;
+}
+
+impl<K> DefinedBy<K> for RawElement {
+  fn decode_defined_by<E: BerEncRules>(_: E, _: &K, raw: RawElement) -> Result<Self, err::DecodeError> {
+    Ok(raw)
+  }
+}
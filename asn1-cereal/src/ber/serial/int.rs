@@ -1,132 +1,303 @@
 //! Implementation of the serialization traits for Rust integers.
 //!
 //! This defines for all rust integers.
+//!
+//! An INTEGER is encoded as big-endian two's complement, using the minimum
+//! number of octets. This means unsigned values with their highest bit set need
+//! an extra leading zero octet, so they aren't decoded as negative.
+//!
+//! When decoding with BER, redundant leading octets are accepted, but they're
+//! an error with rules that require the shortest form (like DER). Values that
+//! can't be represented by the Rust type return an `IntegerOverflow` error.
+//!
+//! For values larger than the Rust integer types, use `Integer`, or enable the
+//! `num-bigint` feature to use `BigInt` and `BigUint`.
 
+use std::convert::TryFrom;
 use std::io;
+use std::mem;
 
-use ::{BerSerialize, BerDeserialize, Asn1Info};
+use ::{BerSerialize, BerDeserialize};
 use tag;
 use err;
+use ber::enc::Deviation;
 use byte::{read_byte, write_byte};
 
-use std::cmp;
-
 /// Generate the ASN.1 int implementation for an int type.
 macro_rules! ber_cereal_int {
-  ($rs_type:ty, 1, $unsigned:expr) => (
-    impl Asn1Info for $rs_type {
-      fn asn1_tag() -> Option<tag::Tag> {
-        Some(tag::Tag {
-          class: tag::Class::Universal,
-          tagnum: 2u8.into(),
-          constructed: false,
-        })
-      }
-
-      fn asn1_type() -> tag::Type {
-        tag::Type::from("INTEGER")
-      }
-    }
+  ($rs_type:ty, $unsigned:expr) => (
+    asn1_info!($rs_type, [PRIM UNIVERSAL 2], "INTEGER");
+    impl ::Asn1Schema for $rs_type {}
 
     impl BerSerialize for $rs_type {
       fn serialize_value<E: ::BerEncRules, W: io::Write>
           (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
-        try!(write_byte(writer, *self as u8));
-        Ok(())
+        write_int(&self.to_be_bytes(), $unsigned, writer)
       }
     }
-  );
-  ($rs_type:ty, $size:expr, $unsigned:expr) => (
-    impl Asn1Info for $rs_type {
-      fn asn1_tag() -> Option<tag::Tag> {
-        Some(tag::Tag {
-          class: tag::Class::Universal,
-          tagnum: 2u8.into(),
-          constructed: false,
-        })
-      }
 
-      fn asn1_type() -> tag::Type {
-        tag::Type::from("INTEGER")
+    impl BerDeserialize for $rs_type {
+      fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+        let bytes = try!(read_content(reader, len));
+        if e.shortest_form() && trim_int(&bytes).len() != bytes.len() &&
+           !e.tolerate(Deviation::PaddedInteger) {
+          warn!("Encountered an INTEGER with redundant leading octets");
+          return Err(err::DecodeError::NonMinimalInteger);
+        }
+        let mut buf = [0u8; mem::size_of::<$rs_type>()];
+        try!(read_int(&bytes, $unsigned, &mut buf));
+        Ok(<$rs_type>::from_be_bytes(buf))
       }
     }
 
-    impl BerSerialize for $rs_type {
-      fn serialize_value<E: ::BerEncRules, W: io::Write>
-          (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
-        let mut started = false;
-        // Loop through bytes in int backwards, start writing when first non-zero byte is encounted.
-        for offset in (0..$size).rev() {
-          let shifted: $rs_type = self >> (offset * 8);
-          let byte: u8 = (shifted & 0xff) as u8;
-          if !started {
-            // Skip starting 0 bytes.
-            if byte == 0 {
-              continue;
-            // If starting byte has the highest bit set to 1, and this is an unsigned
-            // number, write a zero byte first. This allows the decoder to know that
-            // this is actually a positive number in ones complement.
-            } else if byte & 0x80 != 0 && $unsigned {
-              try!(write_byte(writer, 0x00));
-            }
-            started = true;
-          }
-          try!(write_byte(writer, byte));
-        }
-
-        // If we haven't written anything, write a zero byte.
-        if !started {
-          try!(write_byte(writer, 0 as u8));
-        }
-        Ok(())
+    impl From<$rs_type> for Integer {
+      fn from(int: $rs_type) -> Self {
+        let mut bytes = Vec::new();
+        write_int(&int.to_be_bytes(), $unsigned, &mut bytes).expect("Writing to a Vec can't fail");
+        Integer(bytes)
       }
     }
 
-    impl BerDeserialize for $rs_type {
-      fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
-          (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
-        let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
-
-        let mut int: $rs_type = 0;
-        let size = cmp::min($size, len_num);
-
-        // If this is a u8/i8, just read a single byte.
-        if $size == 1 {
-          // FIXME: This doesn't use all len_num bytes.
-          int = try!(read_byte(reader)) as $rs_type;
-        } else {
-          for _ in 0..size {
-            let byte = try!(read_byte(reader));
-            int = (int << 8) + (byte as $rs_type);
-          }
-        }
+    impl TryFrom<Integer> for $rs_type {
+      type Error = err::DecodeError;
 
-        Ok(int)
+      fn try_from(int: Integer) -> Result<Self, Self::Error> {
+        let mut buf = [0u8; mem::size_of::<$rs_type>()];
+        try!(read_int(&int.0, $unsigned, &mut buf));
+        Ok(<$rs_type>::from_be_bytes(buf))
       }
     }
   );
 }
 
-ber_cereal_int!(i8, 1, false);
-ber_cereal_int!(i16, 2, false);
-ber_cereal_int!(i32, 4, false);
-ber_cereal_int!(i64, 8, false);
-
-ber_cereal_int!(u8, 1, true);
-ber_cereal_int!(u16, 2, true);
-ber_cereal_int!(u32, 4, true);
-ber_cereal_int!(u64, 8, true);
-
-#[cfg(target_pointer_width = "16")]
-ber_cereal_int! { isize, 2, false }
-#[cfg(target_pointer_width = "32")]
-ber_cereal_int! { isize, 4, false }
-#[cfg(target_pointer_width = "64")]
-ber_cereal_int! { isize, 8, false }
-
-#[cfg(target_pointer_width = "16")]
-ber_cereal_int! { usize, 2, true }
-#[cfg(target_pointer_width = "32")]
-ber_cereal_int! { usize, 4, true }
-#[cfg(target_pointer_width = "64")]
-ber_cereal_int! { usize, 8, true }
+/// A Rust wrapper for an INTEGER of any size.
+///
+/// This holds the minimal big-endian two's complement encoding of the value,
+/// and can be converted to and from the Rust integer types.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Integer(Vec<u8>);
+
+asn1_info!(Integer, [PRIM UNIVERSAL 2], "INTEGER");
+impl ::Asn1Schema for Integer {}
+
+impl Integer {
+  /// Create an INTEGER from big-endian two's complement bytes, returning an
+  /// error if there are none. Redundant leading bytes are removed.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, err::DecodeError> {
+    if bytes.is_empty() {
+      return Err(err::DecodeError::InvalidInteger);
+    }
+    Ok(Integer(trim_int(bytes).to_vec()))
+  }
+
+  /// Create a non-negative INTEGER from big-endian unsigned bytes, like an RSA
+  /// modulus. No bytes is treated as zero.
+  pub fn from_unsigned_bytes(bytes: &[u8]) -> Self {
+    let mut content = Vec::with_capacity(bytes.len() + 1);
+    content.push(0x00);
+    content.extend_from_slice(bytes);
+    Integer(trim_int(&content).to_vec())
+  }
+
+  /// Get the minimal big-endian two's complement bytes of this INTEGER.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.0
+  }
+
+  /// Returns true when this INTEGER is less than zero.
+  pub fn is_negative(&self) -> bool {
+    self.0[0] & 0x80 != 0
+  }
+
+  /// Get the minimal big-endian unsigned bytes of this INTEGER, or None if
+  /// it's negative.
+  pub fn to_unsigned_bytes(&self) -> Option<&[u8]> {
+    if self.is_negative() {
+      return None;
+    }
+    match self.0.split_first() {
+      Some((&0x00, rest)) if !rest.is_empty() => Some(rest),
+      _ => Some(&self.0),
+    }
+  }
+}
+
+impl BerSerialize for Integer {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(writer.write_all(&self.0));
+    Ok(())
+  }
+}
+
+impl BerDeserialize for Integer {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let bytes = try!(read_content(reader, len));
+    if e.shortest_form() && trim_int(&bytes).len() != bytes.len() &&
+       !e.tolerate(Deviation::PaddedInteger) {
+      warn!("Encountered an INTEGER with redundant leading octets");
+      return Err(err::DecodeError::NonMinimalInteger);
+    }
+    Integer::from_bytes(&bytes)
+  }
+}
+
+/// Read the content octets of an INTEGER.
+fn read_content<I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut I, len: tag::Len) -> Result<Vec<u8>, err::DecodeError> {
+  let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+  // Don't trust the length for the initial allocation.
+  let mut bytes = Vec::with_capacity(::std::cmp::min(len_num, 4096) as usize);
+  for _ in 0..len_num {
+    bytes.push(try!(read_byte(reader)));
+  }
+  Ok(bytes)
+}
+
+/// Remove any leading octets that only repeat the sign of a two's complement
+/// number, leaving at least one octet.
+fn trim_int(mut bytes: &[u8]) -> &[u8] {
+  while bytes.len() > 1 &&
+        ((bytes[0] == 0x00 && bytes[1] & 0x80 == 0) ||
+         (bytes[0] == 0xff && bytes[1] & 0x80 != 0)) {
+    bytes = &bytes[1..];
+  }
+  bytes
+}
+
+/// Write a big-endian integer using the minimal two's complement encoding.
+fn write_int<W: io::Write>(bytes: &[u8], unsigned: bool, writer: &mut W)
+    -> Result<(), err::EncodeError> {
+  // An unsigned number with the highest bit set needs a zero octet first,
+  // otherwise it would be decoded as negative.
+  if unsigned && bytes[0] & 0x80 != 0 {
+    try!(write_byte(writer, 0x00));
+    try!(writer.write_all(bytes));
+  } else {
+    try!(writer.write_all(trim_int(bytes)));
+  }
+  Ok(())
+}
+
+/// Decode two's complement content octets into a big-endian buffer the size of
+/// the Rust type, sign-extending as required.
+fn read_int(bytes: &[u8], unsigned: bool, buf: &mut [u8]) -> Result<(), err::DecodeError> {
+  if bytes.is_empty() {
+    warn!("Encountered an INTEGER with no content");
+    return Err(err::DecodeError::InvalidInteger);
+  }
+  let negative = bytes[0] & 0x80 != 0;
+  if unsigned && negative {
+    return Err(err::DecodeError::IntegerOverflow);
+  }
+
+  let mut bytes = trim_int(bytes);
+  // An unsigned type can use the leading zero octet as well.
+  if unsigned && bytes.len() > 1 && bytes[0] == 0x00 {
+    bytes = &bytes[1..];
+  }
+  if bytes.len() > buf.len() {
+    return Err(err::DecodeError::IntegerOverflow);
+  }
+
+  let offset = buf.len() - bytes.len();
+  for byte in &mut buf[..offset] {
+    *byte = if negative { 0xff } else { 0x00 };
+  }
+  buf[offset..].copy_from_slice(bytes);
+  Ok(())
+}
+
+ber_cereal_int!(i8, false);
+ber_cereal_int!(i16, false);
+ber_cereal_int!(i32, false);
+ber_cereal_int!(i64, false);
+ber_cereal_int!(i128, false);
+ber_cereal_int!(isize, false);
+
+ber_cereal_int!(u8, true);
+ber_cereal_int!(u16, true);
+ber_cereal_int!(u32, true);
+ber_cereal_int!(u64, true);
+ber_cereal_int!(u128, true);
+ber_cereal_int!(usize, true);
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn int_minimal() {
+  fn encode<T: BerSerialize>(value: T) -> Vec<u8> {
+    let mut buf: Vec<u8> = Vec::new();
+    value.serialize(&mut buf).unwrap();
+    buf
+  }
+  assert_eq!(encode(0u64), b"\x02\x01\x00");
+  assert_eq!(encode(127i32), b"\x02\x01\x7f");
+  assert_eq!(encode(128i32), b"\x02\x02\x00\x80");
+  assert_eq!(encode(255u8), b"\x02\x02\x00\xff");
+  assert_eq!(encode(-1i64), b"\x02\x01\xff");
+  assert_eq!(encode(-128i16), b"\x02\x01\x80");
+  assert_eq!(encode(-129i16), b"\x02\x02\xff\x7f");
+  assert_eq!(encode(u128::max_value()), &b"\x02\x11\x00\xff\xff\xff\xff\xff\xff\xff\xff\
+                                           \xff\xff\xff\xff\xff\xff\xff\xff"[..]);
+}
+
+#[test]
+fn int_sign_extend() {
+  assert_eq!(i32::deserialize(b"\x02\x02\x00\x80".bytes().by_ref()).unwrap(), 128);
+  assert_eq!(i32::deserialize(b"\x02\x01\x80".bytes().by_ref()).unwrap(), -128);
+  assert_eq!(i64::deserialize(b"\x02\x02\xff\x7f".bytes().by_ref()).unwrap(), -129);
+  assert_eq!(i8::deserialize(b"\x02\x01\xff".bytes().by_ref()).unwrap(), -1);
+  assert_eq!(u8::deserialize(b"\x02\x02\x00\xff".bytes().by_ref()).unwrap(), 255);
+  // Redundant leading octets are allowed in BER.
+  assert_eq!(i16::deserialize(b"\x02\x04\xff\xff\xff\x80".bytes().by_ref()).unwrap(), -128);
+  match i16::deserialize_enc(::DER, b"\x02\x02\xff\x80".bytes().by_ref()) {
+    Err(err::DecodeError::NonMinimalInteger) => {},
+    res => panic!("Expected NonMinimalInteger, got {:?}", res),
+  }
+  assert_eq!(u8::deserialize_enc(::DER, b"\x02\x02\x00\xff".bytes().by_ref()).unwrap(), 255);
+}
+
+#[test]
+fn int_overflow() {
+  for bytes in &[&b"\x02\x02\x01\x00"[..], &b"\x02\x01\x80"[..], &b"\x02\x03\x00\x80\x00"[..]] {
+    match u8::deserialize(bytes.bytes().by_ref()) {
+      Err(err::DecodeError::IntegerOverflow) => {},
+      res => panic!("Expected IntegerOverflow, got {:?}", res),
+    }
+  }
+  assert!(i8::deserialize(b"\x02\x02\x00\x80".bytes().by_ref()).is_err());
+  assert!(u64::deserialize(b"\x02\x00".bytes().by_ref()).is_err());
+  // A huge length is an error once the input runs out.
+  let bytes = b"\x02\x88\x7f\xff\xff\xff\xff\xff\xff\xff\x01";
+  assert!(u64::deserialize(bytes.bytes().by_ref()).is_err());
+}
+
+#[test]
+fn integer_big() {
+  // A 2048-bit style modulus, with its highest bit set.
+  let modulus = [0xc0u8, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+                 0x0e, 0x0f, 0x10];
+  let int = Integer::from_unsigned_bytes(&modulus);
+  assert_eq!(int.as_bytes().len(), 18);
+  assert_eq!(int.to_unsigned_bytes(), Some(&modulus[..]));
+  assert!(u128::try_from(int.clone()).is_err());
+
+  let mut buf: Vec<u8> = Vec::new();
+  int.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..3], b"\x02\x12\x00");
+  assert_eq!(Integer::deserialize(buf.bytes().by_ref()).unwrap(), int);
+
+  assert_eq!(Integer::from(-129i32).as_bytes(), b"\xff\x7f");
+  assert_eq!(i64::try_from(Integer::from(-129i32)).unwrap(), -129);
+  assert_eq!(Integer::from(200u8).as_bytes(), b"\x00\xc8");
+  assert_eq!(Integer::from_bytes(b"\xff\xff\x80").unwrap(), Integer::from(-128i8));
+
+  match Integer::deserialize_enc(::DER, b"\x02\x02\x00\x01".bytes().by_ref()) {
+    Err(err::DecodeError::NonMinimalInteger) => {},
+    res => panic!("Expected NonMinimalInteger, got {:?}", res),
+  }
+}
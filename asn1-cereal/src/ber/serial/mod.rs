@@ -6,10 +6,30 @@
 //! types (like structs and enumms), macros are provided to generate
 //! an implementation for them.
 
+/// Return an Option early from a function, if it's `None`.
+macro_rules! try_opt {
+  ($e:expr) => (match $e { Some(v) => v, None => return None })
+}
+
 pub mod traits;
 pub mod prim;
 
 pub mod int;
 pub mod str;
 pub mod bool;
+pub mod null;
+pub mod real;
 pub mod seq_of;
+pub mod oid;
+pub mod bit_str;
+pub mod raw;
+pub mod segment;
+pub mod defined_by;
+#[macro_use]
+pub mod time;
+#[cfg(feature = "chrono")]
+pub mod with_chrono;
+#[cfg(feature = "time")]
+pub mod with_time;
+#[cfg(feature = "num-bigint")]
+pub mod with_bigint;
@@ -0,0 +1,53 @@
+//! Implementation of the serialization traits for the Rust unit type, as NULL.
+//!
+//! A NULL has no content, so it's always encoded with a length of zero.
+
+use std::io;
+
+use ::{BerSerialize, BerDeserialize};
+use tag;
+use err;
+
+asn1_info!((), [PRIM UNIVERSAL 5], "NULL");
+
+impl ::Asn1Schema for () {
+  fn asn1_schema() -> ::schema::Schema {
+    ::schema::Schema::of::<Self>(::schema::Kind::Null)
+  }
+}
+
+impl BerSerialize for () {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, _: &mut W) -> Result<(), err::EncodeError> {
+    Ok(())
+  }
+}
+
+impl BerDeserialize for () {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, _: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    if len_num != 0 {
+      warn!("Encountered a NULL with a length of {}", len_num);
+      return Err(err::DecodeError::InvalidNull);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn null_simple() {
+  let bytes = b"\x05\x00";
+  let mut buf: Vec<u8> = Vec::new();
+  ().serialize(&mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  <()>::deserialize(bytes.bytes().by_ref()).unwrap();
+
+  match <()>::deserialize(b"\x05\x01\x00".bytes().by_ref()) {
+    Err(err::DecodeError::InvalidNull) => {},
+    res => panic!("Expected InvalidNull, got {:?}", res),
+  }
+}
@@ -0,0 +1,649 @@
+//! Implementation of OBJECT IDENTIFIER and RELATIVE-OID.
+//!
+//! Both types are stored as their encoded content octets, so arcs of any
+//! size can be represented. Each arc is encoded base-128, with the high bit set
+//! on every byte except the last. For an OBJECT IDENTIFIER the first two arcs
+//! are combined into a single arc, as `first * 40 + second`, while a RELATIVE-OID
+//! encodes every arc separately.
+//!
+//! Constant identifiers can be built at compile time with the `oid!` macro.
+//!
+//! ```
+//! #[macro_use] extern crate asn1_cereal; fn main() {
+//! use asn1_cereal::ObjectIdentifier;
+//!
+//! const RSA: ObjectIdentifier = oid!(1.2.840.113549);
+//! let parsed: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+//! assert_eq!(RSA, parsed);
+//! assert_eq!(RSA.to_string(), "1.2.840.113549");
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use ::{BerSerialize, BerDeserialize};
+use tag;
+use err;
+use ber::serial::segment;
+
+/// A Rust wrapper for OBJECT IDENTIFIER.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectIdentifier(Cow<'static, [u8]>);
+
+asn1_info!(ObjectIdentifier, [PRIM UNIVERSAL 6], "OBJECT IDENTIFIER");
+impl ::Asn1Schema for ObjectIdentifier {}
+
+impl ObjectIdentifier {
+  /// Create an OBJECT IDENTIFIER from its encoded content octets, returning an
+  /// error if they are malformed.
+  pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, err::DecodeError> {
+    if !valid_arcs(&bytes) {
+      return Err(err::DecodeError::InvalidOid);
+    }
+    Ok(ObjectIdentifier(bytes.into()))
+  }
+
+  #[doc(hidden)]
+  /// Create an OBJECT IDENTIFIER from static content octets, without checking them.
+  /// This is used by the `oid!` macro, which has already validated the octets.
+  pub const fn from_static(bytes: &'static [u8]) -> Self {
+    ObjectIdentifier(Cow::Borrowed(bytes))
+  }
+
+  /// Create an OBJECT IDENTIFIER from a list of arcs.
+  pub fn from_arcs(arcs: &[u64]) -> Result<Self, ParseOidError> {
+    if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+      return Err(ParseOidError);
+    }
+    let mut bytes = Vec::new();
+    let first = BigArc::from(arcs[1]).add(arcs[0] * 40);
+    first.write_base128(&mut bytes);
+    write_arcs(&arcs[2..], &mut bytes);
+    Ok(ObjectIdentifier(bytes.into()))
+  }
+
+  /// Get the encoded content octets of this OBJECT IDENTIFIER.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.0
+  }
+
+  /// Get the arcs of this OBJECT IDENTIFIER, or `None` if any arc is too large
+  /// to fit in a `u64`.
+  pub fn to_arcs(&self) -> Option<Vec<u64>> {
+    let mut arcs = Vec::new();
+    for (i, arc) in ArcIter(&self.0).enumerate() {
+      let arc = BigArc::from_base128(arc);
+      if i == 0 {
+        let (first, second) = arc.split_first();
+        arcs.push(first);
+        arcs.push(try_opt!(second.to_u64()));
+      } else {
+        arcs.push(try_opt!(arc.to_u64()));
+      }
+    }
+    Some(arcs)
+  }
+
+  /// Append a RELATIVE-OID to this OBJECT IDENTIFIER, returning the combined
+  /// OBJECT IDENTIFIER.
+  pub fn append(&self, rel: &RelativeOid) -> ObjectIdentifier {
+    let mut bytes = self.0.to_vec();
+    bytes.extend_from_slice(&rel.0);
+    ObjectIdentifier(bytes.into())
+  }
+
+  /// If `base` is a strict prefix of this OBJECT IDENTIFIER, return the
+  /// remaining arcs as a RELATIVE-OID.
+  pub fn relative_to(&self, base: &ObjectIdentifier) -> Option<RelativeOid> {
+    if self.0.len() > base.0.len() && self.0.starts_with(&base.0) {
+      Some(RelativeOid(self.0[base.0.len()..].to_vec().into()))
+    } else {
+      None
+    }
+  }
+}
+
+impl fmt::Display for ObjectIdentifier {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, arc) in ArcIter(&self.0).enumerate() {
+      let arc = BigArc::from_base128(arc);
+      if i == 0 {
+        let (first, second) = arc.split_first();
+        try!(write!(f, "{}.{}", first, second));
+      } else {
+        try!(write!(f, ".{}", arc));
+      }
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Debug for ObjectIdentifier {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "ObjectIdentifier({})", self)
+  }
+}
+
+impl FromStr for ObjectIdentifier {
+  type Err = ParseOidError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parts = s.split('.');
+    let first = match parts.next() {
+      Some("0") => 0,
+      Some("1") => 1,
+      Some("2") => 2,
+      _ => return Err(ParseOidError),
+    };
+    let second = try!(parts.next().ok_or(ParseOidError).and_then(BigArc::parse));
+    if first < 2 && second.to_u64().map_or(true, |s| s >= 40) {
+      return Err(ParseOidError);
+    }
+
+    let mut bytes = Vec::new();
+    second.add(first * 40).write_base128(&mut bytes);
+    try!(write_str_arcs(parts, &mut bytes));
+    Ok(ObjectIdentifier(bytes.into()))
+  }
+}
+
+impl BerSerialize for ObjectIdentifier {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(writer.write_all(&self.0));
+    Ok(())
+  }
+}
+
+impl BerDeserialize for ObjectIdentifier {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    ObjectIdentifier::from_bytes(try!(segment::read_content(reader, len_num)))
+  }
+}
+
+/// A Rust wrapper for RELATIVE-OID.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RelativeOid(Cow<'static, [u8]>);
+
+asn1_info!(RelativeOid, [PRIM UNIVERSAL 13], "RELATIVE-OID");
+impl ::Asn1Schema for RelativeOid {}
+
+impl RelativeOid {
+  /// Create a RELATIVE-OID from its encoded content octets, returning an
+  /// error if they are malformed.
+  pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, err::DecodeError> {
+    if !valid_arcs(&bytes) {
+      return Err(err::DecodeError::InvalidOid);
+    }
+    Ok(RelativeOid(bytes.into()))
+  }
+
+  /// Create a RELATIVE-OID from a list of arcs.
+  pub fn from_arcs(arcs: &[u64]) -> Result<Self, ParseOidError> {
+    if arcs.is_empty() {
+      return Err(ParseOidError);
+    }
+    let mut bytes = Vec::new();
+    write_arcs(arcs, &mut bytes);
+    Ok(RelativeOid(bytes.into()))
+  }
+
+  /// Get the encoded content octets of this RELATIVE-OID.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.0
+  }
+
+  /// Get the arcs of this RELATIVE-OID, or `None` if any arc is too large
+  /// to fit in a `u64`.
+  pub fn to_arcs(&self) -> Option<Vec<u64>> {
+    ArcIter(&self.0).map(|arc| BigArc::from_base128(arc).to_u64()).collect()
+  }
+}
+
+impl fmt::Display for RelativeOid {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, arc) in ArcIter(&self.0).enumerate() {
+      if i != 0 {
+        try!(write!(f, "."));
+      }
+      try!(write!(f, "{}", BigArc::from_base128(arc)));
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Debug for RelativeOid {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "RelativeOid({})", self)
+  }
+}
+
+impl FromStr for RelativeOid {
+  type Err = ParseOidError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut bytes = Vec::new();
+    try!(write_str_arcs(s.split('.'), &mut bytes));
+    Ok(RelativeOid(bytes.into()))
+  }
+}
+
+impl BerSerialize for RelativeOid {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(writer.write_all(&self.0));
+    Ok(())
+  }
+}
+
+impl BerDeserialize for RelativeOid {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    RelativeOid::from_bytes(try!(segment::read_content(reader, len_num)))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An error returned when a string or list of arcs is not a valid
+/// OBJECT IDENTIFIER or RELATIVE-OID.
+pub struct ParseOidError;
+
+impl fmt::Display for ParseOidError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "invalid object identifier")
+  }
+}
+
+impl Error for ParseOidError {}
+
+/// Write each arc base-128 to a Vec.
+fn write_arcs(arcs: &[u64], bytes: &mut Vec<u8>) {
+  for &arc in arcs {
+    BigArc::from(arc).write_base128(bytes);
+  }
+}
+
+/// Parse each decimal arc, and write it base-128 to a Vec.
+fn write_str_arcs<'a, I: Iterator<Item=&'a str>>(arcs: I, bytes: &mut Vec<u8>)
+    -> Result<(), ParseOidError> {
+  for arc in arcs {
+    try!(BigArc::parse(arc)).write_base128(bytes);
+  }
+  Ok(())
+}
+
+/// Check that encoded content octets are a valid list of arcs. There must be at
+/// least one arc, the last byte must not have the continuation bit set, and
+/// arcs must not start with a padding 0x80 byte.
+fn valid_arcs(bytes: &[u8]) -> bool {
+  if bytes.last().map_or(true, |b| b & 0x80 != 0) {
+    return false;
+  }
+  ArcIter(bytes).all(|arc| arc[0] != 0x80)
+}
+
+/// An iterator over the base-128 encoded arcs in a slice of content octets.
+struct ArcIter<'a>(&'a [u8]);
+
+impl<'a> Iterator for ArcIter<'a> {
+  type Item = &'a [u8];
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.0.is_empty() {
+      return None;
+    }
+    // Each arc ends on the first byte without the continuation bit.
+    let end = self.0.iter().position(|b| b & 0x80 == 0)
+      .map_or(self.0.len(), |p| p + 1);
+    let (arc, rest) = self.0.split_at(end);
+    self.0 = rest;
+    Some(arc)
+  }
+}
+
+/// An arbitrarily large arc, stored as little-endian 32-bit limbs.
+struct BigArc(Vec<u32>);
+
+impl BigArc {
+  /// Parse a decimal string into an arc.
+  fn parse(s: &str) -> Result<Self, ParseOidError> {
+    // Reject empty arcs, and leading zeroes.
+    if s.is_empty() || (s.len() > 1 && s.starts_with('0')) {
+      return Err(ParseOidError);
+    }
+    let mut arc = BigArc(vec![0]);
+    for c in s.chars() {
+      let digit = try!(c.to_digit(10).ok_or(ParseOidError));
+      arc.mul_add(10, digit);
+    }
+    Ok(arc)
+  }
+
+  /// Decode a base-128 encoded arc.
+  fn from_base128(bytes: &[u8]) -> Self {
+    let mut arc = BigArc(vec![0]);
+    for b in bytes {
+      arc.mul_add(128, (b & 0x7f) as u32);
+    }
+    arc
+  }
+
+  /// Multiply this arc by `mul`, then add `add`.
+  fn mul_add(&mut self, mul: u32, add: u32) {
+    let mut carry = add as u64;
+    for limb in &mut self.0 {
+      let v = *limb as u64 * mul as u64 + carry;
+      *limb = v as u32;
+      carry = v >> 32;
+    }
+    if carry != 0 {
+      self.0.push(carry as u32);
+    }
+  }
+
+  /// Add a small value to this arc.
+  fn add(mut self, value: u64) -> Self {
+    let mut carry = value;
+    for limb in &mut self.0 {
+      if carry == 0 {
+        break;
+      }
+      let v = *limb as u64 + (carry & 0xffffffff);
+      *limb = v as u32;
+      carry = (carry >> 32) + (v >> 32);
+    }
+    while carry != 0 {
+      self.0.push(carry as u32);
+      carry >>= 32;
+    }
+    self
+  }
+
+  /// Divide this arc by `div` in place, returning the remainder.
+  fn div_rem(&mut self, div: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in self.0.iter_mut().rev() {
+      let v = (rem << 32) | *limb as u64;
+      *limb = (v / div as u64) as u32;
+      rem = v % div as u64;
+    }
+    while self.0.len() > 1 && self.0.last() == Some(&0) {
+      self.0.pop();
+    }
+    rem as u32
+  }
+
+  fn is_zero(&self) -> bool {
+    self.0.iter().all(|&l| l == 0)
+  }
+
+  fn to_u64(&self) -> Option<u64> {
+    let mut value = 0u64;
+    for (i, &limb) in self.0.iter().enumerate() {
+      match i {
+        0 => value |= limb as u64,
+        1 => value |= (limb as u64) << 32,
+        _ if limb != 0 => return None,
+        _ => {},
+      }
+    }
+    Some(value)
+  }
+
+  /// Split the combined first arc into the first and second arcs.
+  fn split_first(self) -> (u64, BigArc) {
+    match self.to_u64() {
+      Some(v) if v < 80 => (v / 40, BigArc::from(v % 40)),
+      _ => {
+        // Anything 80 or larger has a first arc of 2, so subtract 80.
+        let mut arc = self;
+        let mut borrow = 80u64;
+        for limb in &mut arc.0 {
+          let (v, under) = (*limb as u64).overflowing_sub(borrow);
+          *limb = v as u32;
+          borrow = if under { 1 } else { 0 };
+        }
+        (2, arc)
+      },
+    }
+  }
+
+  /// Write this arc base-128 to a Vec, setting the high bit on every
+  /// byte except the last.
+  fn write_base128(mut self, bytes: &mut Vec<u8>) {
+    let mut parts = Vec::new();
+    loop {
+      parts.push(self.div_rem(128) as u8);
+      if self.is_zero() {
+        break;
+      }
+    }
+    for (i, part) in parts.iter().enumerate().rev() {
+      bytes.push(if i != 0 { part | 0x80 } else { *part });
+    }
+  }
+}
+
+impl From<u64> for BigArc {
+  fn from(value: u64) -> Self {
+    BigArc(vec![value as u32, (value >> 32) as u32])
+  }
+}
+
+impl fmt::Display for BigArc {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // Take chunks of nine decimal digits, starting from the least significant.
+    let mut arc = BigArc(self.0.clone());
+    let mut chunks = Vec::new();
+    loop {
+      chunks.push(arc.div_rem(1000000000));
+      if arc.is_zero() {
+        break;
+      }
+    }
+    let mut chunks = chunks.iter().rev();
+    try!(write!(f, "{}", chunks.next().unwrap()));
+    for chunk in chunks {
+      try!(write!(f, "{:09}", chunk));
+    }
+    Ok(())
+  }
+}
+
+#[doc(hidden)]
+/// The maximum number of content octets an `oid!` constant can have.
+pub const OID_CONST_LEN: usize = 128;
+
+#[doc(hidden)]
+/// Encode a dotted string into content octets at compile time, for the `oid!`
+/// macro. Returns a buffer and the number of octets used. Unlike `FromStr`,
+/// arcs are limited to 128 bits.
+pub const fn encode_oid_const(s: &str) -> ([u8; OID_CONST_LEN], usize) {
+  let s = s.as_bytes();
+  let mut buf = [0u8; OID_CONST_LEN];
+  let mut len = 0;
+  let mut arc_count = 0;
+  let mut first: u128 = 0;
+  let mut i = 0;
+  while i <= s.len() {
+    // Parse the next decimal arc, skipping any whitespace.
+    let mut arc: u128 = 0;
+    let mut digits = 0;
+    while i < s.len() && s[i] != b'.' {
+      let c = s[i];
+      i += 1;
+      if c == b' ' {
+        continue;
+      }
+      if c < b'0' || c > b'9' {
+        panic!("oid!: arcs must be decimal numbers");
+      }
+      if digits == 1 && arc == 0 {
+        panic!("oid!: arcs must not have leading zeroes");
+      }
+      arc = match arc.checked_mul(10) {
+        Some(a) => match a.checked_add((c - b'0') as u128) {
+          Some(a) => a,
+          None => panic!("oid!: arc is too large"),
+        },
+        None => panic!("oid!: arc is too large"),
+      };
+      digits += 1;
+    }
+    i += 1;
+    if digits == 0 {
+      panic!("oid!: empty arc");
+    }
+
+    arc_count += 1;
+    if arc_count == 1 {
+      if arc > 2 {
+        panic!("oid!: the first arc must be 0, 1 or 2");
+      }
+      first = arc;
+      continue;
+    } else if arc_count == 2 {
+      if first < 2 && arc >= 40 {
+        panic!("oid!: the second arc must be less than 40");
+      }
+      arc = match arc.checked_add(first * 40) {
+        Some(a) => a,
+        None => panic!("oid!: arc is too large"),
+      };
+    }
+
+    // Write the arc base-128, most significant part first.
+    let mut parts = 1;
+    while parts < 19 && arc >> (7 * parts) != 0 {
+      parts += 1;
+    }
+    if len + parts > OID_CONST_LEN {
+      panic!("oid!: object identifier is too long");
+    }
+    while parts > 0 {
+      parts -= 1;
+      let part = ((arc >> (7 * parts)) & 0x7f) as u8;
+      buf[len] = if parts != 0 { part | 0x80 } else { part };
+      len += 1;
+    }
+  }
+  if arc_count < 2 {
+    panic!("oid!: an object identifier needs at least two arcs");
+  }
+  (buf, len)
+}
+
+#[macro_export]
+/// Build an `ObjectIdentifier` constant at compile time from its dotted form.
+///
+/// ```
+/// #[macro_use] extern crate asn1_cereal; fn main() {
+/// use asn1_cereal::ObjectIdentifier;
+///
+/// const COMMON_NAME: ObjectIdentifier = oid!(2.5.4.3);
+/// assert_eq!(COMMON_NAME.as_bytes(), &[0x55, 0x04, 0x03]);
+/// }
+/// ```
+macro_rules! oid {
+  ($($arcs:tt)+) => ({
+    const ENCODED: ([u8; $crate::ber::serial::oid::OID_CONST_LEN], usize) =
+      $crate::ber::serial::oid::encode_oid_const(stringify!($($arcs)+));
+    const BUF: &'static [u8; $crate::ber::serial::oid::OID_CONST_LEN] = &ENCODED.0;
+    const BYTES: &'static [u8] = BUF.split_at(ENCODED.1).0;
+    $crate::ber::serial::oid::ObjectIdentifier::from_static(BYTES)
+  });
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn oid_rsa() {
+  let bytes = b"\x06\x06\x2a\x86\x48\x86\xf7\x0d";
+  let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+  assert_eq!(oid, oid!(1.2.840.113549));
+  assert_eq!(oid.to_arcs(), Some(vec![1, 2, 840, 113549]));
+
+  let mut buf: Vec<u8> = Vec::new();
+  oid.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  let decoded = ObjectIdentifier::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(decoded.to_string(), "1.2.840.113549");
+}
+
+#[test]
+fn oid_first_arcs() {
+  let oid: ObjectIdentifier = "2.999.3".parse().unwrap();
+  assert_eq!(oid.as_bytes(), &[0x88, 0x37, 0x03]);
+  assert_eq!(oid.to_string(), "2.999.3");
+  assert_eq!(oid, ObjectIdentifier::from_arcs(&[2, 999, 3]).unwrap());
+  assert_eq!(oid!(0.39).as_bytes(), &[0x27]);
+  assert!("1.40".parse::<ObjectIdentifier>().is_err());
+  assert!("3.1".parse::<ObjectIdentifier>().is_err());
+  assert!("1".parse::<ObjectIdentifier>().is_err());
+  assert!("1.2.03".parse::<ObjectIdentifier>().is_err());
+  assert!("1..2".parse::<ObjectIdentifier>().is_err());
+}
+
+#[test]
+fn oid_large_arcs() {
+  let uuid = "2.25.329800735698586629295641978511506172918";
+  let oid: ObjectIdentifier = uuid.parse().unwrap();
+  assert_eq!(oid.to_string(), uuid);
+  assert_eq!(oid.to_arcs(), None);
+
+  let large = "1.3.6.1.4.1.123456789012345678901234567890123456789";
+  let oid: ObjectIdentifier = large.parse().unwrap();
+  assert_eq!(oid.to_string(), large);
+  let decoded = ObjectIdentifier::from_bytes(oid.as_bytes().to_vec()).unwrap();
+  assert_eq!(decoded.to_string(), large);
+}
+
+#[test]
+fn oid_invalid_bytes() {
+  // Empty, truncated, and padded arcs are all invalid.
+  for bytes in &[&b""[..], b"\x2a\x86", b"\x2a\x80\x01"] {
+    match ObjectIdentifier::from_bytes(bytes.to_vec()) {
+      Err(err::DecodeError::InvalidOid) => {},
+      res => panic!("Expected InvalidOid, got {:?}", res),
+    }
+  }
+
+  // The content can't be shorter than the length.
+  assert!(ObjectIdentifier::deserialize(b"\x06\x05\x2a\x03\x04".bytes().by_ref()).is_err());
+}
+
+#[test]
+fn relative_oid() {
+  let bytes = b"\x0d\x04\xc2\x7b\x03\x02";
+  let rel: RelativeOid = "8571.3.2".parse().unwrap();
+  assert_eq!(rel.to_arcs(), Some(vec![8571, 3, 2]));
+  assert_eq!(rel, RelativeOid::from_arcs(&[8571, 3, 2]).unwrap());
+
+  let mut buf: Vec<u8> = Vec::new();
+  rel.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  let decoded = RelativeOid::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(decoded.to_string(), "8571.3.2");
+  assert!(RelativeOid::deserialize(b"\x0d\x05\xc2\x7b\x03".bytes().by_ref()).is_err());
+  assert!("".parse::<RelativeOid>().is_err());
+  assert!("1.-2".parse::<RelativeOid>().is_err());
+}
+
+#[test]
+fn relative_oid_append() {
+  let base = oid!(1.3.6.1.4.1);
+  let rel: RelativeOid = "8571.3.2".parse().unwrap();
+  let full = base.append(&rel);
+  assert_eq!(full.to_string(), "1.3.6.1.4.1.8571.3.2");
+  assert_eq!(full.relative_to(&base), Some(rel));
+  assert_eq!(base.relative_to(&full), None);
+  assert_eq!(base.relative_to(&base), None);
+}
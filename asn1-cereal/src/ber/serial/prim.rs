@@ -2,20 +2,35 @@
 
 use std::io;
 
-use ::{BerSerialize, BerDeserialize};
+use ::{BerSerialize, BerDeserialize, Asn1Info};
 use tag;
 use err;
+use ber::serial::segment;
+
+pub use ber::serial::int::Integer;
+pub use ber::serial::oid::{ObjectIdentifier, RelativeOid};
+pub use ber::serial::bit_str::BitString;
+pub use ber::serial::raw::RawElement;
+pub use ber::serial::str::{Utf8String, NumericString, PrintableString, TeletexString, T61String,
+                           Ia5String, VisibleString, UniversalString, BmpString};
+pub use ber::serial::time::{UtcTime, GeneralizedTime, TimeZone, Asn1Time};
 
 /// A Rust wrapper for OCTET STRING.
 ///
 /// To access the internal element, call `a.0`.
 ///
 /// (Use this instead of Vec<u8>, since Vec is used for SEQUENCE OF).
-pub struct OctetString(Vec<u8>);
+pub struct OctetString(pub(crate) Vec<u8>);
 
 asn1_info!(OctetString, [PRIM UNIVERSAL 4], "OCTET STRING");
+impl ::Asn1Schema for OctetString {}
 
 impl BerSerialize for OctetString {
+  fn _serialize_enc<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Option<Result<(), err::EncodeError>> {
+    segment::write_segmented(e, Self::asn1_tag(), segment::OCTET_STRING_TAGNUM, &self.0, None, writer)
+  }
+
   fn serialize_value<E: ::BerEncRules, W: io::Write>
       (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
     try!(writer.write_all(&self.0));
@@ -24,9 +39,20 @@ impl BerSerialize for OctetString {
 }
 
 impl BerDeserialize for OctetString {
+  fn _deserialize_with_tag<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, tag: tag::Tag, len: tag::Len) -> Option<Result<Self, err::DecodeError>> {
+    // Handle the constructed form here, the primitive form uses the normal behaviour.
+    if segment::is_constructed_form(e, Self::asn1_tag(), tag) {
+      Some(segment::read_octets(e, reader, len).map(OctetString))
+    } else {
+      None
+    }
+  }
+
   fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
-      (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
     let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    try!(segment::check_primitive(e, len_num));
     let bytes: Result<Vec<u8>, _> = reader.take(len_num as usize).collect();
     Ok(OctetString(try!(bytes)))
   }
@@ -44,4 +70,18 @@ impl<T: ::Asn1Info> ::Asn1Info for Option<T> {
   fn asn1_constructed<E: ::BerEncRules>(e: E) -> bool {
     <T as ::Asn1Info>::asn1_constructed(e)
   }
+
+  fn asn1_constraints() -> ::constraint::Constraints {
+    <T as ::Asn1Info>::asn1_constraints()
+  }
+
+  fn asn1_fields() -> &'static [&'static str] {
+    <T as ::Asn1Info>::asn1_fields()
+  }
+}
+
+impl<T: ::Asn1Schema> ::Asn1Schema for Option<T> {
+  fn asn1_schema() -> ::schema::Schema {
+    <T as ::Asn1Schema>::asn1_schema()
+  }
 }
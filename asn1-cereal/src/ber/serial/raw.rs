@@ -0,0 +1,276 @@
+//! Implementation of RawElement, which can hold any ASN.1 element.
+//!
+//! This is useful for ANY, and open types, where the type of an element
+//! isn't known until later. When decoding, the whole element is captured
+//! from the stream (including any nested indefinite length encoding), and it's
+//! written back out unchanged when encoding. It can then be decoded into
+//! a specific type with `RawElement::decode`.
+//!
+//! Like ANY, a RawElement can't be implicitly tagged, so when it's used
+//! with an implicit tag, the tag will always be explicit.
+
+use std::io;
+
+use ::{BerSerialize, BerDeserialize, Asn1Info};
+use tag;
+use err;
+use ber::enc;
+use byte::ByteReader;
+
+/// An ASN.1 element of any type, holding its tag and content.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RawElement {
+  /// The tag of this element.
+  pub tag: tag::Tag,
+  /// True when this element is encoded with an indefinite length.
+  pub indefinite: bool,
+  /// The content octets of this element. For indefinite length encoding,
+  /// this doesn't include the end-of-contents octets.
+  pub content: Vec<u8>,
+}
+
+impl Asn1Info for RawElement {
+  fn asn1_tag() -> Option<tag::Tag> {
+    None
+  }
+
+  fn asn1_type() -> tag::Type {
+    tag::Type::from("ANY")
+  }
+
+  fn asn1_constructed<E: enc::BerEncRules>(_: E) -> bool {
+    true
+  }
+}
+
+impl ::Asn1Schema for RawElement {
+  fn asn1_schema() -> ::schema::Schema {
+    ::schema::Schema::of::<Self>(::schema::Kind::Open)
+  }
+}
+
+impl RawElement {
+  /// Create an element with a definite length.
+  pub fn new(tag: tag::Tag, content: Vec<u8>) -> Self {
+    RawElement {
+      tag: tag,
+      indefinite: false,
+      content: content,
+    }
+  }
+
+  /// Create an element by encoding a value with DER.
+  pub fn encode<T: BerSerialize>(value: &T) -> Result<Self, err::EncodeError> {
+    let mut bytes: Vec<u8> = Vec::new();
+    try!(value.serialize(&mut bytes));
+    RawElement::deserialize(&mut bytes.into_iter().map(Ok))
+      .map_err(|_| err::EncodeError::Custom("Value didn't encode as a single element"))
+  }
+
+  /// Decode this element as a specific type, accepting any valid BER.
+  pub fn decode<T: BerDeserialize>(&self) -> Result<T, err::DecodeError> {
+    self.decode_enc(enc::BER)
+  }
+
+  /// Decode this element as a specific type, using a specific set of encoding rules.
+  pub fn decode_enc<T: BerDeserialize, E: enc::BerEncRules>(&self, e: E) -> Result<T, err::DecodeError> {
+    let mut bytes: Vec<u8> = Vec::new();
+    try!(self.write(&mut bytes).map_err(|_|
+      err::DecodeError::Custom("Couldn't write RawElement")
+    ));
+    let mut reader = bytes.into_iter().map(Ok);
+    let value = try!(T::deserialize_enc(e, &mut reader));
+    if reader.next().is_some() {
+      return Err(err::DecodeError::SmallerLen);
+    }
+    Ok(value)
+  }
+
+  /// Write this element, with its tag and length.
+  fn write<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    if self.indefinite {
+      try!(tag::write_taglen(self.tag, tag::Len::Indef, writer));
+      try!(writer.write_all(&self.content));
+      try!(tag::Len::write_indef_end(writer));
+    } else {
+      let len = tag::Len::Def(self.content.len() as tag::LenNum);
+      try!(tag::write_taglen(self.tag, len, writer));
+      try!(writer.write_all(&self.content));
+    }
+    Ok(())
+  }
+
+  /// Read the content of an element with the given tag and length.
+  fn read<I: Iterator<Item=io::Result<u8>>>
+      (reader: &mut I, tag: tag::Tag, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let mut content = Vec::new();
+    try!(copy_content(&mut Recorder { reader: reader, bytes: &mut content }, tag, len));
+    Ok(RawElement {
+      tag: tag,
+      indefinite: len == tag::Len::Indef,
+      content: content,
+    })
+  }
+}
+
+/// An iterator that records every byte that is read from it.
+struct Recorder<'a, I: 'a + Iterator<Item=io::Result<u8>>> {
+  reader: &'a mut I,
+  bytes: &'a mut Vec<u8>,
+}
+
+impl<'a, I: Iterator<Item=io::Result<u8>>> Iterator for Recorder<'a, I> {
+  type Item = io::Result<u8>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let byte = self.reader.next();
+    if let Some(Ok(b)) = byte {
+      self.bytes.push(b);
+    }
+    byte
+  }
+}
+
+/// Read the content of an element into the recorder. For indefinite length
+/// encoding, this reads every nested element until the end-of-contents octets,
+/// which are removed.
+///
+/// Nested elements are counted rather than read recursively, so deeply nested
+/// indefinite length encoding can't overflow the stack.
+fn copy_content<'a, I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut Recorder<'a, I>, tag: tag::Tag, len: tag::Len) -> Result<(), err::DecodeError> {
+  if len == tag::Len::Indef && !tag.constructed {
+    warn!("Encountered indefinite length encoding, but this is a primitive element");
+    return Err(err::DecodeError::PrimIndef);
+  }
+  // The number of indefinite length elements that haven't ended.
+  let mut depth: u64 = if len == tag::Len::Indef { 1 } else { 0 };
+  if let tag::Len::Def(len) = len {
+    try!(skip(reader, len));
+  }
+  while depth > 0 {
+    let start = reader.bytes.len();
+    let (tag, len) = try!(tag::read_taglen(reader));
+    match len {
+      tag::Len::Def(0) if tag.class == tag::Class::Universal && tag.tagnum == 0 &&
+                          !tag.constructed => {
+        depth -= 1;
+        if depth == 0 {
+          reader.bytes.truncate(start);
+        }
+      },
+      tag::Len::Def(len) => try!(skip(reader, len)),
+      tag::Len::Indef => {
+        if !tag.constructed {
+          warn!("Encountered indefinite length encoding, but this is a primitive element");
+          return Err(err::DecodeError::PrimIndef);
+        }
+        depth += 1;
+      },
+    }
+  }
+  Ok(())
+}
+
+/// Read `len` bytes into the recorder.
+fn skip<'a, I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut Recorder<'a, I>, len: tag::LenNum) -> Result<(), err::DecodeError> {
+  for _ in 0..len {
+    try!(::byte::read_byte(reader));
+  }
+  Ok(())
+}
+
+impl BerSerialize for RawElement {
+  fn _serialize_enc<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, writer: &mut W) -> Option<Result<(), err::EncodeError>> {
+    Some(self.write(writer))
+  }
+
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    // Since this can't be implicitly tagged, the value includes the tag.
+    self.write(writer)
+  }
+}
+
+impl BerDeserialize for RawElement {
+  fn _deserialize_with_tag<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, reader: &mut I, tag: tag::Tag, len: tag::Len) -> Option<Result<Self, err::DecodeError>> {
+    Some(RawElement::read(reader, tag, len))
+  }
+
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    // This is only called for an implicit tag, which is really an explicit
+    // tag around the whole element.
+    let mut reader = ByteReader::new(reader, len.into());
+    let res = tag::read_taglen(&mut reader)
+      .and_then(|(tag, inner_len)| RawElement::read(&mut reader, tag, inner_len));
+    if reader.exceeded {
+      return Err(err::DecodeError::GreaterLen);
+    }
+    let element = try!(res);
+    // The element must fill the whole explicit tag.
+    match len {
+      tag::Len::Def(len) if reader.count != len => return Err(err::DecodeError::SmallerLen),
+      _ => {},
+    }
+    Ok(element)
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn raw_element_definite() {
+  let bytes = b"\x30\x06\x02\x01\x05\x01\x01\xff";
+  let raw = RawElement::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(raw.tag, tag::Tag { class: tag::Class::Universal, tagnum: 16, constructed: true });
+  assert_eq!(raw.content, &bytes[2..]);
+
+  let mut buf: Vec<u8> = Vec::new();
+  raw.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(RawElement::encode(&5u32).unwrap().decode::<u32>().unwrap(), 5);
+  assert!(raw.decode::<u32>().is_err());
+}
+
+#[test]
+fn raw_element_indefinite() {
+  // Nested indefinite length encoding, followed by another element.
+  let bytes = b"\x30\x80\x24\x80\x04\x01\x01\x00\x00\x02\x01\x05\x00\x00\x05\x00";
+  let mut reader = bytes.bytes();
+  let raw = RawElement::deserialize(reader.by_ref()).unwrap();
+  assert!(raw.indefinite);
+  assert_eq!(raw.content, &bytes[2..12]);
+  <()>::deserialize(reader.by_ref()).unwrap();
+
+  let mut buf: Vec<u8> = Vec::new();
+  raw.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..14]);
+
+  // A primitive element can't use indefinite length encoding.
+  assert!(RawElement::deserialize(b"\x04\x80\x00\x00".bytes().by_ref()).is_err());
+}
+
+#[test]
+fn raw_element_nesting() {
+  // Deeply nested indefinite length encoding doesn't overflow the stack.
+  let bytes: Vec<u8> = ::std::iter::repeat(&[0x30u8, 0x80]).take(500000).flat_map(|b| b.iter().cloned()).collect();
+  assert!(RawElement::deserialize(&mut bytes.into_iter().map(Ok)).is_err());
+
+  // An implicit tag around an element must hold exactly that element.
+  let bytes = b"\x02\x01\x05\x05\x00";
+  let raw = RawElement::deserialize_value(enc::BER, bytes.bytes().by_ref(), tag::Len::Def(3)).unwrap();
+  assert_eq!(raw.content, &[5]);
+  match RawElement::deserialize_value(enc::BER, bytes.bytes().by_ref(), tag::Len::Def(5)) {
+    Err(err::DecodeError::SmallerLen) => {},
+    res => panic!("Expected SmallerLen, got {:?}", res),
+  }
+  match RawElement::deserialize_value(enc::BER, bytes.bytes().by_ref(), tag::Len::Def(2)) {
+    Err(err::DecodeError::GreaterLen) => {},
+    res => panic!("Expected GreaterLen, got {:?}", res),
+  }
+}
@@ -0,0 +1,317 @@
+//! Implementation of the serialization traits for Rust floats, as REAL.
+//!
+//! Finite values are encoded in the binary form, with base 2, a scaling factor
+//! of zero and an odd mantissa. This is the canonical form required by DER and
+//! CER, and is also valid BER. Zero is encoded with no content, and the special
+//! values PLUS-INFINITY, MINUS-INFINITY, NOT-A-NUMBER and minus zero each use
+//! a single octet.
+//!
+//! When decoding, the binary form with base 2, 8 or 16 and any scaling factor,
+//! and the ISO 6093 decimal forms NR1, NR2 and NR3 are all accepted. If the
+//! encoding rules require the shortest form, the binary form must be canonical
+//! and the decimal form must be NR3.
+
+use std::io;
+
+use ::{BerSerialize, BerDeserialize};
+use tag;
+use err;
+
+/// Special real values, encoded as a single octet.
+const PLUS_INFINITY: u8 = 0x40;
+const MINUS_INFINITY: u8 = 0x41;
+const NOT_A_NUMBER: u8 = 0x42;
+const MINUS_ZERO: u8 = 0x43;
+
+/// Generate the ASN.1 REAL implementation for a float type.
+macro_rules! ber_cereal_real {
+  ($rs_type:ty) => (
+    asn1_info!($rs_type, [PRIM UNIVERSAL 9], "REAL");
+    impl ::Asn1Schema for $rs_type {}
+
+    impl BerSerialize for $rs_type {
+      fn serialize_value<E: ::BerEncRules, W: io::Write>
+          (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+        try!(writer.write_all(&encode_real(*self as f64)));
+        Ok(())
+      }
+    }
+
+    impl BerDeserialize for $rs_type {
+      fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+        let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+        let bytes: Result<Vec<u8>, _> = reader.take(len_num as usize).collect();
+        let bytes = try!(bytes);
+        if bytes.len() as u64 != len_num {
+          return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                                    "Got unexpected EOF while reading stream").into());
+        }
+        Ok(try!(decode_real(&bytes, e.shortest_form())) as $rs_type)
+      }
+    }
+  );
+}
+
+ber_cereal_real!(f32);
+ber_cereal_real!(f64);
+
+/// Encode a float as the content octets of a REAL.
+fn encode_real(value: f64) -> Vec<u8> {
+  if value.is_nan() {
+    return vec![NOT_A_NUMBER];
+  } else if value.is_infinite() {
+    return vec![if value > 0.0 { PLUS_INFINITY } else { MINUS_INFINITY }];
+  } else if value == 0.0 {
+    return if value.is_sign_negative() { vec![MINUS_ZERO] } else { vec![] };
+  }
+
+  // Split the float into an integer mantissa and base 2 exponent.
+  let bits = value.to_bits();
+  let biased_exp = ((bits >> 52) & 0x7ff) as i64;
+  let fraction = bits & 0xfffffffffffff;
+  let (mut mantissa, mut exp) = if biased_exp == 0 {
+    (fraction, -1074)
+  } else {
+    (fraction | (1 << 52), biased_exp - 1075)
+  };
+  // The mantissa must be odd in the canonical form.
+  while mantissa & 1 == 0 {
+    mantissa >>= 1;
+    exp += 1;
+  }
+
+  let exp_bytes = minimal_signed(exp);
+  let mut first = 0x80;
+  if value < 0.0 {
+    first |= 0x40;
+  }
+  first |= (exp_bytes.len() - 1) as u8;
+
+  let mut bytes = vec![first];
+  bytes.extend_from_slice(&exp_bytes);
+  let mantissa_bytes = mantissa.to_be_bytes();
+  let start = mantissa_bytes.iter().position(|&b| b != 0).unwrap_or(7);
+  bytes.extend_from_slice(&mantissa_bytes[start..]);
+  bytes
+}
+
+/// Get the minimal two's complement encoding of an exponent.
+fn minimal_signed(value: i64) -> Vec<u8> {
+  let bytes = value.to_be_bytes();
+  let mut start = 0;
+  while start < 7 &&
+        ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0) ||
+         (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0)) {
+    start += 1;
+  }
+  bytes[start..].to_vec()
+}
+
+/// Decode the content octets of a REAL. When `canonical` is true, only the
+/// canonical binary form, or the NR3 decimal form is accepted.
+fn decode_real(bytes: &[u8], canonical: bool) -> Result<f64, err::DecodeError> {
+  let first = match bytes.first() {
+    Some(&b) => b,
+    None => return Ok(0.0),
+  };
+
+  if first & 0x80 != 0 {
+    decode_binary(bytes, canonical)
+  } else if first & 0x40 != 0 {
+    if bytes.len() != 1 {
+      return Err(err::DecodeError::InvalidReal);
+    }
+    match first {
+      PLUS_INFINITY => Ok(::std::f64::INFINITY),
+      MINUS_INFINITY => Ok(::std::f64::NEG_INFINITY),
+      NOT_A_NUMBER => Ok(::std::f64::NAN),
+      MINUS_ZERO => Ok(-0.0),
+      _ => Err(err::DecodeError::InvalidReal),
+    }
+  } else {
+    decode_decimal(first & 0x3f, &bytes[1..], canonical)
+  }
+}
+
+/// Decode the binary form of a REAL.
+fn decode_binary(bytes: &[u8], canonical: bool) -> Result<f64, err::DecodeError> {
+  let first = bytes[0];
+  let negative = first & 0x40 != 0;
+  let base_bits = match (first >> 4) & 0x3 {
+    0 => 1,
+    1 => 3,
+    2 => 4,
+    _ => return Err(err::DecodeError::InvalidReal),
+  };
+  let scale = ((first >> 2) & 0x3) as i64;
+
+  // Find the exponent octets.
+  let (exp_start, exp_len) = match first & 0x3 {
+    3 => (2, *try!(bytes.get(1).ok_or(err::DecodeError::InvalidReal)) as usize),
+    n => (1, n as usize + 1),
+  };
+  if exp_len == 0 || exp_len > 8 || bytes.len() <= exp_start + exp_len {
+    return Err(err::DecodeError::InvalidReal);
+  }
+  let exp_bytes = &bytes[exp_start..exp_start + exp_len];
+  let mut exp: i64 = if exp_bytes[0] & 0x80 != 0 { -1 } else { 0 };
+  for &b in exp_bytes {
+    exp = (exp << 8) | b as i64;
+  }
+  let mantissa_bytes = &bytes[exp_start + exp_len..];
+
+  if canonical &&
+     (base_bits != 1 || scale != 0 || mantissa_bytes[0] == 0 ||
+      mantissa_bytes[mantissa_bytes.len() - 1] & 1 == 0 ||
+      minimal_signed(exp).len() != exp_len) {
+    warn!("Encountered a REAL that isn't in canonical form");
+    return Err(err::DecodeError::InvalidReal);
+  }
+
+  let mantissa = if mantissa_bytes.len() <= 8 {
+    mantissa_bytes.iter().fold(0u64, |m, &b| (m << 8) | b as u64) as f64
+  } else {
+    mantissa_bytes.iter().fold(0f64, |m, &b| m * 256.0 + b as f64)
+  };
+  // value = mantissa * 2^scale * base^exp
+  let exp2 = try!(exp.checked_mul(base_bits)
+    .and_then(|e| e.checked_add(scale))
+    .ok_or(err::DecodeError::InvalidReal));
+  let value = scale_by_pow2(mantissa, exp2);
+  Ok(if negative { -value } else { value })
+}
+
+/// Multiply a float by 2^exp, without overflowing intermediate values.
+fn scale_by_pow2(mut value: f64, mut exp: i64) -> f64 {
+  let step = 2f64.powi(1000);
+  while exp > 1000 && value.is_finite() && value != 0.0 {
+    value *= step;
+    exp -= 1000;
+  }
+  while exp < -1000 && value != 0.0 {
+    value /= step;
+    exp += 1000;
+  }
+  value * 2f64.powi(exp as i32)
+}
+
+/// Decode the ISO 6093 decimal form of a REAL.
+fn decode_decimal(form: u8, bytes: &[u8], canonical: bool) -> Result<f64, err::DecodeError> {
+  let s = try!(::std::str::from_utf8(bytes).map_err(|_| err::DecodeError::InvalidReal));
+  let s = s.trim_start_matches(' ');
+  let (mantissa, exp) = match s.find(|c| c == 'E' || c == 'e') {
+    Some(p) => (&s[..p], Some(&s[p + 1..])),
+    None => (s, None),
+  };
+  let unsigned = mantissa.trim_start_matches(|c| c == '+' || c == '-');
+  let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+  let signed_digits = |s: &str| {
+    let unsigned = s.trim_start_matches(|c| c == '+' || c == '-');
+    s.len() - unsigned.len() <= 1 && digits(unsigned)
+  };
+  // Digits with an optional decimal mark, with at least one digit.
+  let number = |s: &str| {
+    let mut parts = s.splitn(2, |c| c == '.' || c == ',');
+    let whole = parts.next().unwrap_or("");
+    let frac = parts.next().unwrap_or("");
+    (digits(whole) || digits(frac)) &&
+      (whole.is_empty() || digits(whole)) && (frac.is_empty() || digits(frac))
+  };
+  let has_mark = mantissa.contains(|c| c == '.' || c == ',');
+
+  let valid = mantissa.len() - unsigned.len() <= 1 &&
+    match (form, exp) {
+      // NR1: An integer.
+      (1, None) => digits(unsigned),
+      // NR2: A number with a decimal mark.
+      (2, None) => has_mark && number(unsigned),
+      // NR3: A number with an exponent.
+      (3, Some(exp)) => number(unsigned) && signed_digits(exp),
+      _ => false,
+    };
+  if !valid || (canonical && form != 3) {
+    warn!("Encountered an invalid decimal REAL");
+    return Err(err::DecodeError::InvalidReal);
+  }
+
+  let normalised = s.replace(',', ".");
+  normalised.parse().map_err(|_| err::DecodeError::InvalidReal)
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn real_binary() {
+  let cases: Vec<(f64, &[u8])> = vec![
+    (0.0, b"\x09\x00"),
+    (1.0, b"\x09\x03\x80\x00\x01"),
+    (0.5, b"\x09\x03\x80\xff\x01"),
+    (-40.0, b"\x09\x03\xc0\x03\x05"),
+    (0.1, b"\x09\x09\x80\xc9\x0c\xcc\xcc\xcc\xcc\xcc\xcd"),
+    (::std::f64::MAX, b"\x09\x0a\x81\x03\xcb\x1f\xff\xff\xff\xff\xff\xff"),
+  ];
+  for (value, bytes) in cases {
+    let mut buf: Vec<u8> = Vec::new();
+    value.serialize(&mut buf).unwrap();
+    assert_eq!(&buf[..], bytes);
+    assert_eq!(f64::deserialize_enc(::DER, bytes.bytes().by_ref()).unwrap(), value);
+  }
+  let mut buf: Vec<u8> = Vec::new();
+  0.25f32.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x09\x03\x80\xfe\x01");
+  assert_eq!(f32::deserialize(buf.bytes().by_ref()).unwrap(), 0.25);
+}
+
+#[test]
+fn real_special() {
+  let mut buf: Vec<u8> = Vec::new();
+  (-0.0f64).serialize(&mut buf).unwrap();
+  ::std::f64::INFINITY.serialize(&mut buf).unwrap();
+  ::std::f64::NEG_INFINITY.serialize(&mut buf).unwrap();
+  ::std::f64::NAN.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x09\x01\x43\x09\x01\x40\x09\x01\x41\x09\x01\x42");
+
+  let mut reader = buf.bytes();
+  let zero = f64::deserialize(reader.by_ref()).unwrap();
+  assert!(zero == 0.0 && zero.is_sign_negative());
+  assert_eq!(f64::deserialize(reader.by_ref()).unwrap(), ::std::f64::INFINITY);
+  assert_eq!(f64::deserialize(reader.by_ref()).unwrap(), ::std::f64::NEG_INFINITY);
+  assert!(f64::deserialize(reader.by_ref()).unwrap().is_nan());
+}
+
+#[test]
+fn real_non_canonical() {
+  // 16 as base 16 exponent 1, base 8 with a scaling factor, and an even mantissa.
+  let cases: Vec<&[u8]> = vec![
+    b"\x09\x03\xa0\x01\x01",
+    b"\x09\x03\x94\x01\x01",
+    b"\x09\x03\x80\x03\x02",
+  ];
+  for bytes in cases {
+    assert_eq!(f64::deserialize(bytes.bytes().by_ref()).unwrap(), 16.0);
+    match f64::deserialize_enc(::DER, bytes.bytes().by_ref()) {
+      Err(err::DecodeError::InvalidReal) => {},
+      res => panic!("Expected InvalidReal, got {:?}", res),
+    }
+  }
+}
+
+#[test]
+fn real_decimal() {
+  let cases: Vec<(&[u8], f64)> = vec![
+    (b"\x09\x06\x01  -12", -12.0),
+    (b"\x09\x05\x02\x31\x2c\x35\x30", 1.5),
+    (b"\x09\x04\x02\x2e\x32\x35", 0.25),
+    (b"\x09\x07\x03\x31\x2e\x35\x45\x2b\x33", 1500.0),
+    (b"\x09\x06\x03\x32\x35\x45\x2d\x32", 0.25),
+  ];
+  for (bytes, value) in cases {
+    assert_eq!(f64::deserialize(bytes.bytes().by_ref()).unwrap(), value);
+  }
+  assert!(f64::deserialize_enc(::DER, b"\x09\x06\x01  -12".bytes().by_ref()).is_err());
+  // NR1 can't have a decimal mark, and NR2 can't have an exponent.
+  assert!(f64::deserialize(b"\x09\x04\x01\x31\x2e\x35".bytes().by_ref()).is_err());
+  assert!(f64::deserialize(b"\x09\x04\x02\x31\x45\x31".bytes().by_ref()).is_err());
+}
@@ -0,0 +1,282 @@
+//! Support for the constructed (segmented) form of string types.
+//!
+//! With BER, an OCTET STRING, BIT STRING or character string can be split into
+//! a series of segments using the constructed form. DER doesn't allow this,
+//! and CER requires it for strings longer than 1000 octets, where every segment
+//! but the last must be exactly 1000 octets.
+//!
+//! Each segment of an OCTET STRING or character string is an OCTET STRING,
+//! and each segment of a BIT STRING is a BIT STRING.
+
+use std::io;
+
+use ::BerEncRules;
+use tag;
+use err;
+use ber::enc;
+use byte::{read_byte, ByteReader};
+
+/// The tag number of an OCTET STRING, which is used for segments of strings.
+pub const OCTET_STRING_TAGNUM: tag::TagNum = 4;
+
+/// Returns true when a tag is the constructed form of a string type's tag.
+///
+/// When the encoding rules don't allow the constructed form, but tolerate
+/// `Deviation::ConstructedBit`, the tag is instead treated as the primitive
+/// form with the wrong constructed bit.
+pub fn is_constructed_form<E: BerEncRules>(e: E, our_tag: Option<tag::Tag>, tag: tag::Tag) -> bool {
+  match our_tag {
+    Some(our_tag) if tag.constructed && !our_tag.constructed &&
+                     tag.class == our_tag.class && tag.tagnum == our_tag.tagnum =>
+      !(e.shortest_form() && e.segment_size().is_none() && e.tolerates(enc::Deviation::ConstructedBit)),
+    _ => false,
+  }
+}
+
+/// Write the content of a string type in the constructed form, if it's longer
+/// than the segment size of the encoding rules. Returns None when the primitive
+/// form should be used instead.
+///
+/// When `unused` is given, this is a BIT STRING. Each segment starts with an
+/// unused bits octet, which is zero for every segment except the last.
+pub fn write_segmented<E: BerEncRules, W: io::Write>
+    (e: E, tag: Option<tag::Tag>, seg_tagnum: tag::TagNum, content: &[u8], unused: Option<u8>, writer: &mut W)
+    -> Option<Result<(), err::EncodeError>> {
+  let tag = try_opt!(tag);
+  let size = try_opt!(e.segment_size());
+  let header = if unused.is_some() { 1 } else { 0 };
+  if (content.len() + header) as tag::LenNum <= size {
+    return None;
+  }
+
+  let mut bytes: Vec<u8> = Vec::new();
+  let mut chunks = content.chunks(size as usize - header).peekable();
+  while let Some(chunk) = chunks.next() {
+    let seg_tag = tag::Tag {
+      class: tag::Class::Universal,
+      tagnum: seg_tagnum,
+      constructed: false,
+    };
+    let len = tag::Len::Def((chunk.len() + header) as tag::LenNum);
+    if let Err(e) = tag::write_taglen(seg_tag, len, &mut bytes) {
+      return Some(Err(e));
+    }
+    if let Some(unused) = unused {
+      bytes.push(if chunks.peek().is_none() { unused } else { 0 });
+    }
+    bytes.extend_from_slice(chunk);
+  }
+
+  Some(write_constructed(e, tag::Tag { constructed: true, .. tag }, &bytes, writer))
+}
+
+/// Write a constructed element, using the length encoding of the encoding rules.
+fn write_constructed<E: BerEncRules, W: io::Write>
+    (e: E, tag: tag::Tag, content: &[u8], writer: &mut W) -> Result<(), err::EncodeError> {
+  if e.len_rules() == enc::LenEnc::Indefinite {
+    try!(tag::write_taglen(tag, tag::Len::Indef, writer));
+    try!(writer.write_all(content));
+    try!(tag::Len::write_indef_end(writer));
+  } else {
+    try!(tag::write_taglen(tag, tag::Len::Def(content.len() as tag::LenNum), writer));
+    try!(writer.write_all(content));
+  }
+  Ok(())
+}
+
+/// Check the length of a string in the primitive form, which can't be longer
+/// than the segment size of the encoding rules.
+pub fn check_primitive<E: BerEncRules>(e: E, len: tag::LenNum) -> Result<(), err::DecodeError> {
+  match e.segment_size() {
+    Some(size) if len > size => {
+      warn!("Encountered a primitive string longer than {} octets", size);
+      Err(err::DecodeError::SegmentedString)
+    },
+    _ => Ok(()),
+  }
+}
+
+/// Read the content octets of a primitive string.
+pub fn read_content<I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut I, len: tag::LenNum) -> Result<Vec<u8>, err::DecodeError> {
+  // Don't trust the length for the initial allocation.
+  let mut bytes = Vec::with_capacity(::std::cmp::min(len, 4096) as usize);
+  for _ in 0..len {
+    bytes.push(try!(read_byte(reader)));
+  }
+  Ok(bytes)
+}
+
+/// Read the content octets of a string type in the constructed form, where
+/// each segment is an OCTET STRING.
+pub fn read_octets<E: BerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I, len: tag::Len) -> Result<Vec<u8>, err::DecodeError> {
+  let mut bytes = Vec::new();
+  let mut reader = ByteReader::new(reader, None);
+  try!(read_segments(e, &mut reader, len, OCTET_STRING_TAGNUM, &mut |reader, seg_len| {
+    bytes.extend(try!(read_content(reader, seg_len)));
+    Ok(())
+  }));
+  Ok(bytes)
+}
+
+/// Read the segments of a string type in the constructed form, until `len`
+/// bytes have been read, or the end of indefinite length encoding is reached.
+/// The content of each primitive segment is read by `segment`.
+pub fn read_segments<E, I, F>(e: E, reader: &mut ByteReader<I>, len: tag::Len, seg_tagnum: tag::TagNum,
+                              segment: &mut F) -> Result<(), err::DecodeError>
+    where E: BerEncRules,
+          I: Iterator<Item=io::Result<u8>>,
+          F: FnMut(&mut ByteReader<I>, tag::LenNum) -> Result<(), err::DecodeError> {
+  if e.shortest_form() && e.segment_size().is_none() {
+    warn!("Encountered a constructed string, but encoding rules require the primitive form");
+    return Err(err::DecodeError::SegmentedString);
+  }
+  if e.canonical() && e.len_rules() == enc::LenEnc::Indefinite && len != tag::Len::Indef {
+    warn!("Encountered definite length encoding, but encoding rules require indefinite length");
+    return Err(err::DecodeError::DefiniteLen);
+  }
+
+  let mut sizes = Vec::new();
+  try!(read_nested(e, reader, len, seg_tagnum, &mut sizes, segment));
+
+  // Every segment but the last must be the segment size, and the string
+  // must be too long for the primitive form.
+  if let Some(size) = e.segment_size() {
+    let valid = match sizes.split_last() {
+      Some((&last, rest)) => !rest.is_empty() && rest.iter().all(|&s| s == size) &&
+                             last > 0 && last <= size,
+      None => false,
+    };
+    if !valid {
+      warn!("Encountered a constructed string without {} octet segments", size);
+      return Err(err::DecodeError::SegmentedString);
+    }
+  }
+  Ok(())
+}
+
+/// Read segments, including nested constructed segments where the encoding
+/// rules allow them, recording the size of each primitive segment.
+fn read_nested<E, I, F>(e: E, reader: &mut ByteReader<I>, len: tag::Len, seg_tagnum: tag::TagNum,
+                        sizes: &mut Vec<tag::LenNum>, segment: &mut F) -> Result<(), err::DecodeError>
+    where E: BerEncRules,
+          I: Iterator<Item=io::Result<u8>>,
+          F: FnMut(&mut ByteReader<I>, tag::LenNum) -> Result<(), err::DecodeError> {
+  // The end of each constructed segment being read, or None for indefinite
+  // length. This is a stack rather than recursion, so deeply nested segments
+  // can't overflow the call stack.
+  let mut ends = vec![len.as_num().map(|l| reader.count + l)];
+  while let Some(&end) = ends.last() {
+    if let Some(end) = end {
+      if reader.count == end {
+        ends.pop();
+        continue;
+      } else if reader.count > end {
+        return Err(err::DecodeError::GreaterLen);
+      }
+    }
+
+    let (tag, seg_len) = try!(tag::read_taglen_enc(e, reader));
+    // Handle end of indefinite length encoding.
+    if end.is_none() && tag.tagnum == 0 && tag.class == tag::Class::Universal &&
+       seg_len == tag::Len::Def(0) {
+      ends.pop();
+      continue;
+    }
+    if tag.class != tag::Class::Universal || tag.tagnum != seg_tagnum {
+      warn!("Expected a string segment, but found tag {}", tag);
+      return Err(err::DecodeError::TagTypeMismatch);
+    }
+
+    if tag.constructed {
+      if e.segment_size().is_some() {
+        warn!("Encountered a nested constructed string segment");
+        return Err(err::DecodeError::SegmentedString);
+      }
+      ends.push(seg_len.as_num().map(|l| reader.count + l));
+    } else {
+      let seg_len = try!(seg_len.as_num().ok_or(err::DecodeError::PrimIndef));
+      sizes.push(seg_len);
+      try!(segment(reader, seg_len));
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use ::{BerSerialize, BerDeserialize};
+
+#[test]
+fn segment_cer_string() {
+  let s: String = ::std::iter::repeat('a').take(2100).collect();
+  let mut buf: Vec<u8> = Vec::new();
+  s.serialize_enc(::CER, &mut buf).unwrap();
+  assert_eq!(&buf[..6], b"\x33\x80\x04\x82\x03\xe8");
+  assert_eq!(&buf[1006..1010], b"\x04\x82\x03\xe8");
+  assert_eq!(&buf[2010..2012], b"\x04\x64");
+  assert_eq!(&buf[buf.len() - 2..], b"\x00\x00");
+  assert_eq!(String::deserialize_enc(::CER, buf.bytes().by_ref()).unwrap(), s);
+  match String::deserialize_enc(::DER, buf.bytes().by_ref()) {
+    Err(err::DecodeError::SegmentedString) => {},
+    res => panic!("Expected SegmentedString, got {:?}", res),
+  }
+
+  // Short strings are primitive, and long primitive strings aren't valid CER.
+  buf.clear();
+  "abc".to_string().serialize_enc(::CER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x13\x03abc");
+  buf.clear();
+  s.serialize(&mut buf).unwrap();
+  assert!(String::deserialize_enc(::CER, buf.bytes().by_ref()).is_err());
+
+  // CER segments must be 1000 octets, and use indefinite length.
+  let bytes = b"\x33\x06\x04\x01a\x04\x01b";
+  assert_eq!(String::deserialize(bytes.bytes().by_ref()).unwrap(), "ab");
+  let bytes = b"\x33\x80\x04\x01a\x04\x01b\x00\x00";
+  assert!(String::deserialize_enc(::CER, bytes.bytes().by_ref()).is_err());
+}
+
+#[test]
+fn segment_cer_bit_string() {
+  let mut bits = ::BitString::from(vec![0xff; 1500]);
+  bits.resize(11996);
+  let mut buf: Vec<u8> = Vec::new();
+  bits.serialize_enc(::CER, &mut buf).unwrap();
+  // Segments of 999 octets, with the unused bits only in the last segment.
+  assert_eq!(&buf[..7], b"\x23\x80\x03\x82\x03\xe8\x00");
+  assert_eq!(&buf[1006..1010], b"\x03\x82\x01\xf6");
+  assert_eq!(buf[1010], 4);
+  assert_eq!(::BitString::deserialize_enc(::CER, buf.bytes().by_ref()).unwrap(), bits);
+
+  // Definite length isn't allowed for constructed elements with CER.
+  buf.clear();
+  let bytes = b"\x23\x06\x03\x01\x00\x03\x01\x00";
+  match ::BitString::deserialize_enc(::CER, bytes.bytes().by_ref()) {
+    Err(err::DecodeError::DefiniteLen) => {},
+    res => panic!("Expected DefiniteLen, got {:?}", res),
+  }
+}
+
+#[test]
+fn segment_huge_length() {
+  // A length larger than the input is an error, rather than an allocation.
+  let primitive = b"\x0c\x88\x7f\xff\xff\xff\xff\xff\xff\xff\x01";
+  assert!(::ber::serial::str::Utf8String::deserialize(primitive.bytes().by_ref()).is_err());
+  let segmented = b"\x24\x80\x04\x88\x7f\xff\xff\xff\xff\xff\xff\xff\x01";
+  assert!(::OctetString::deserialize(segmented.bytes().by_ref()).is_err());
+}
+
+#[test]
+fn segment_nesting() {
+  let bytes = b"\x24\x80\x24\x80\x04\x01a\x00\x00\x24\x03\x04\x01b\x00\x00";
+  assert_eq!(&::OctetString::deserialize(bytes.bytes().by_ref()).unwrap().0[..], b"ab");
+
+  // Deeply nested segments don't overflow the stack.
+  let bytes: Vec<u8> = ::std::iter::repeat(&[0x24u8, 0x80]).take(500000).flat_map(|b| b.iter().cloned()).collect();
+  assert!(::OctetString::deserialize(&mut bytes.into_iter().map(Ok)).is_err());
+  let bytes: Vec<u8> = ::std::iter::repeat(&[0x23u8, 0x80]).take(500000).flat_map(|b| b.iter().cloned()).collect();
+  assert!(::BitString::deserialize(&mut bytes.into_iter().map(Ok)).is_err());
+}
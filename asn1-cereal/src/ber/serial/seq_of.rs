@@ -38,6 +38,106 @@ macro_rules! ber_sequence_of_serialize {
   );
 }
 
+#[macro_export]
+/// Implement BerSerialize and BerDeserialize for a SET OF type.
+///
+/// ```
+/// #[macro_use] extern crate asn1_cereal; fn main() {
+/// use asn1_cereal::{BerSerialize, DER};
+///
+/// // For Ports ::= SET OF INTEGER
+/// struct Ports(Vec<u16>);
+/// asn1_info!(Ports, asn1_cereal::tag::Class::Universal, 17, true, "Ports");
+/// ber_set_of!(Ports);
+///
+/// impl<'a> IntoIterator for &'a Ports {
+///   type Item = &'a u16;
+///   type IntoIter = std::slice::Iter<'a, u16>;
+///   fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+/// }
+///
+/// impl std::iter::FromIterator<u16> for Ports {
+///   fn from_iter<I: IntoIterator<Item=u16>>(iter: I) -> Self { Ports(iter.into_iter().collect()) }
+/// }
+///
+/// let mut buf = Vec::new();
+/// Ports(vec![443, 80]).serialize_enc(DER, &mut buf).unwrap();
+/// assert_eq!(buf, b"\x31\x07\x02\x01\x50\x02\x02\x01\xbb");
+/// }
+/// ```
+macro_rules! ber_set_of {
+  ($($token:tt)*) => (
+    ber_set_of_serialize!($($token)*);
+    ber_set_of_deserialize!($($token)*);
+  );
+}
+
+#[macro_export]
+/// Implement BerSerialize for a SET OF type, by iterating over each element,
+/// and calling serialize_enc on each element. With canonical encoding rules
+/// (like DER), the encoded elements are sorted.
+///
+/// Requires that a reference to the type implement IntoIterator.
+macro_rules! ber_set_of_serialize {
+  (impl: $rs_type:ty) => (
+    fn serialize_value<E: $crate::BerEncRules, W: ::std::io::Write>
+        (&self, e: E, writer: &mut W) -> Result<(), $crate::err::EncodeError> {
+      $crate::ber::serial::seq_of::serialize_set_of(e, self.into_iter(), writer)
+    }
+  );
+  ($rs_type:ty) => (
+    impl $crate::BerSerialize for $rs_type {
+      ber_set_of_serialize!{impl: $rs_type}
+    }
+  );
+  ($rs_type:ty => $gen:ident) => (
+    impl<$gen: $crate::BerSerialize> $crate::BerSerialize for $rs_type {
+      ber_set_of_serialize!{impl: $rs_type}
+    }
+  );
+  ($rs_type:ty => $gen:ident, $($where_attr:tt)*) => (
+    impl<$gen: $crate::BerSerialize> $crate::BerSerialize for $rs_type where $($where_attr)* {
+      ber_set_of_serialize!{impl: $rs_type}
+    }
+  );
+}
+
+#[macro_export]
+/// Implement BerDeserialize for a SET OF type, by collecting the decoded elements.
+/// With canonical encoding rules (like DER), it's an error for the encoded
+/// elements to not be sorted.
+///
+/// Requires that the type implement FromIterator.
+macro_rules! ber_set_of_deserialize {
+  (impl: $rs_type:ty) => (
+    // The end-of-contents octets are read to find the last element.
+    fn _reads_indef_end<E: $crate::BerEncRules>(_: E) -> bool {
+      true
+    }
+
+    fn deserialize_value<E: $crate::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+        (e: E, reader: &mut I, len: $crate::tag::Len) -> Result<Self, $crate::err::DecodeError> {
+      let items: Vec<_> = try!($crate::ber::serial::seq_of::deserialize_set_of(e, reader, len));
+      Ok(items.into_iter().collect())
+    }
+  );
+  ($rs_type:ty) => (
+    impl $crate::BerDeserialize for $rs_type {
+      ber_set_of_deserialize!{impl: $rs_type}
+    }
+  );
+  ($rs_type:ty => $gen:ident) => (
+    impl<$gen: $crate::BerDeserialize> $crate::BerDeserialize for $rs_type {
+      ber_set_of_deserialize!{impl: $rs_type}
+    }
+  );
+  ($rs_type:ty => $gen:ident, $($where_attr:tt)*) => (
+    impl<$gen: $crate::BerDeserialize> $crate::BerDeserialize for $rs_type where $($where_attr)* {
+      ber_set_of_deserialize!{impl: $rs_type}
+    }
+  );
+}
+
 #[macro_export]
 /// Implement BerDeserialize for a type, by collecting the elements from an iterator
 /// built by calling deserialize_enc on the stream continually.
@@ -46,6 +146,11 @@ macro_rules! ber_sequence_of_serialize {
 /// SEQUENCE/SET OF.
 macro_rules! ber_sequence_of_deserialize {
   (impl: $rs_type:ty) => (
+    // The end-of-contents octets are read to find the last element.
+    fn _reads_indef_end<E: $crate::BerEncRules>(_: E) -> bool {
+      true
+    }
+
     fn deserialize_value<E: $crate::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
         (e: E, reader: &mut I, len: $crate::tag::Len) -> Result<Self, $crate::err::DecodeError> {
       struct SeqOfDecoder<T, F, J: Iterator<Item=::std::io::Result<u8>>> {
@@ -75,7 +180,7 @@ macro_rules! ber_sequence_of_deserialize {
             Some(::std::cmp::Ordering::Greater) | None => {},
           }
 
-          let (tag, len) = match $crate::tag::read_taglen(&mut self.reader) {
+          let (tag, len) = match $crate::tag::read_taglen_enc(self.e, &mut self.reader) {
             Ok(t) => t,
             Err(e) => return Some(Err(e)),
           };
@@ -86,12 +191,12 @@ macro_rules! ber_sequence_of_deserialize {
             return None;
           }
 
-          Some($crate::BerDeserialize::deserialize_value(self.e, &mut self.reader, len))
+          Some($crate::BerDeserialize::deserialize_with_tag(self.e, &mut self.reader, tag, len))
         }
       }
 
       if len == $crate::tag::Len::Indef &&
-         E::len_rules() == $crate::ber::enc::LenEnc::Definite {
+         e.len_rules() == $crate::ber::enc::LenEnc::Definite {
         return Err($crate::err::DecodeError::IndefiniteLen);
       }
 
@@ -122,10 +227,173 @@ macro_rules! ber_sequence_of_deserialize {
   );
 }
 
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet};
 use std::hash::Hash;
+use std::io;
+
+use ::{BerSerialize, BerDeserialize};
+use ber::serial::raw::RawElement;
+use tag;
+use err;
 
 asn1_info!(Vec<T> => T, ::tag::Class::Universal, 16, true, "SEQUENCE OF");
 ber_sequence_of!(Vec<T> => T);
 asn1_info!(HashSet<T> => T, ::tag::Class::Universal, 17, true, "SET OF");
-ber_sequence_of!(HashSet<T> => T, T: Eq + Hash);
+ber_set_of!(HashSet<T> => T, T: Eq + Hash);
+asn1_info!(BTreeSet<T> => T, ::tag::Class::Universal, 17, true, "SET OF");
+ber_set_of!(BTreeSet<T> => T, T: Ord);
+
+/// Implement Asn1Schema for a SEQUENCE/SET OF type, with a component for the
+/// type of the elements.
+macro_rules! seq_of_schema {
+  ($rs_type:ty, $kind:ident) => (
+    impl<T: ::Asn1Schema> ::Asn1Schema for $rs_type {
+      fn asn1_schema() -> ::schema::Schema {
+        ::schema::Schema {
+          components: vec![::schema::Component::of::<T>("")],
+          .. ::schema::Schema::of::<Self>(::schema::Kind::$kind)
+        }
+      }
+    }
+  );
+}
+
+seq_of_schema!(Vec<T>, SequenceOf);
+seq_of_schema!(HashSet<T>, SetOf);
+seq_of_schema!(BTreeSet<T>, SetOf);
+
+/// Serialize the elements of a SET OF. With canonical encoding rules,
+/// the encoded elements are written in ascending order.
+pub fn serialize_set_of<'a, E, W, T, It>(e: E, items: It, writer: &mut W) -> Result<(), err::EncodeError>
+    where E: ::BerEncRules, W: io::Write, T: 'a + BerSerialize, It: Iterator<Item=&'a T> {
+  if !e.canonical() {
+    for item in items {
+      try!(item.serialize_enc(e, writer));
+    }
+    return Ok(());
+  }
+
+  let mut encoded: Vec<Vec<u8>> = Vec::new();
+  for item in items {
+    let mut bytes = Vec::new();
+    try!(item.serialize_enc(e, &mut bytes));
+    encoded.push(bytes);
+  }
+  encoded.sort_by(|a, b| cmp_padded(a, b));
+  for bytes in &encoded {
+    try!(writer.write_all(bytes));
+  }
+  Ok(())
+}
+
+/// Deserialize the elements of a SET OF. With canonical encoding rules,
+/// it's an error for the encoded elements to not be in ascending order.
+pub fn deserialize_set_of<E, I, T>(e: E, reader: &mut I, len: tag::Len) -> Result<Vec<T>, err::DecodeError>
+    where E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>, T: BerDeserialize {
+  if !e.canonical() {
+    return Vec::deserialize_value(e, reader, len);
+  }
+
+  let raw: Vec<RawElement> = try!(Vec::deserialize_value(e, reader, len));
+  let mut prev: Option<Vec<u8>> = None;
+  let mut items = Vec::with_capacity(raw.len());
+  for element in &raw {
+    let mut bytes = Vec::new();
+    try!(element.serialize(&mut bytes).map_err(|_|
+      err::DecodeError::Custom("Couldn't write RawElement")
+    ));
+    if prev.as_ref().map_or(false, |p| cmp_padded(p, &bytes) == Ordering::Greater) {
+      warn!("Encountered a SET OF with elements out of order");
+      return Err(err::DecodeError::UnsortedSet);
+    }
+    items.push(try!(element.decode_enc(e)));
+    prev = Some(bytes);
+  }
+  Ok(items)
+}
+
+/// Compare two encoded elements, where the shorter is padded with trailing
+/// zero octets, as required for the canonical order of a SET OF.
+fn cmp_padded(a: &[u8], b: &[u8]) -> Ordering {
+  let len = a.len().max(b.len());
+  let a = a.iter().chain(::std::iter::repeat(&0u8)).take(len);
+  let b = b.iter().chain(::std::iter::repeat(&0u8)).take(len);
+  a.cmp(b)
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn set_of_canonical() {
+  let set: HashSet<u32> = [256, 1, 3, 2].iter().cloned().collect();
+  let sorted = b"\x31\x0d\x02\x01\x01\x02\x01\x02\x02\x01\x03\x02\x02\x01\x00";
+  let mut buf: Vec<u8> = Vec::new();
+  set.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &sorted[..]);
+  assert_eq!(HashSet::<u32>::deserialize_enc(::DER, buf.bytes().by_ref()).unwrap(), set);
+
+  buf.clear();
+  set.serialize_enc(::CER, &mut buf).unwrap();
+  assert_eq!(&buf[..2], b"\x31\x80");
+  assert_eq!(&buf[2..15], &sorted[2..]);
+  assert_eq!(HashSet::<u32>::deserialize_enc(::CER, buf.bytes().by_ref()).unwrap(), set);
+
+  let unsorted = b"\x31\x06\x02\x01\x02\x02\x01\x01";
+  assert_eq!(HashSet::<u32>::deserialize(unsorted.bytes().by_ref()).unwrap().len(), 2);
+  match HashSet::<u32>::deserialize_enc(::DER, unsorted.bytes().by_ref()) {
+    Err(err::DecodeError::UnsortedSet) => {},
+    res => panic!("Expected UnsortedSet, got {:?}", res),
+  }
+
+  let set: BTreeSet<u32> = set.into_iter().collect();
+  buf.clear();
+  set.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &sorted[..]);
+  assert_eq!(BTreeSet::<u32>::deserialize_enc(::DER, buf.bytes().by_ref()).unwrap(), set);
+  assert!(BTreeSet::<u32>::deserialize_enc(::DER, unsorted.bytes().by_ref()).is_err());
+}
+
+#[test]
+fn seq_of_indefinite() {
+  let value = vec![vec![1u32, 2], vec![], vec![3]];
+  let bytes = b"\x30\x80\x30\x80\x02\x01\x01\x02\x01\x02\x00\x00\x30\x80\x00\x00\
+                \x30\x80\x02\x01\x03\x00\x00\x00\x00";
+  let mut buf: Vec<u8> = Vec::new();
+  value.serialize_enc(::CER, &mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Vec::<Vec<u32>>::deserialize_enc(::CER, buf.bytes().by_ref()).unwrap(), value);
+
+  // Element tags are checked, and CER requires indefinite length.
+  assert!(Vec::<u32>::deserialize(b"\x30\x03\x01\x01\xff".bytes().by_ref()).is_err());
+  match Vec::<u32>::deserialize_enc(::CER, b"\x30\x03\x02\x01\x01".bytes().by_ref()) {
+    Err(err::DecodeError::DefiniteLen) => {},
+    res => panic!("Expected DefiniteLen, got {:?}", res),
+  }
+}
+
+/// A constructed type whose `deserialize_value` leaves the end-of-contents
+/// octets to `deserialize_with_tag`, like most hand-written implementations.
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+struct Pair(u32, u32);
+
+#[cfg(test)]
+asn1_info!(Pair, [APPLICATION 1], "Pair");
+
+#[cfg(test)]
+impl BerDeserialize for Pair {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, _: tag::Len) -> Result<Self, err::DecodeError> {
+    Ok(Pair(try!(u32::deserialize_enc(e, reader)), try!(u32::deserialize_enc(e, reader))))
+  }
+}
+
+#[test]
+fn seq_of_indefinite_elements() {
+  let bytes = b"\x30\x80\x61\x80\x02\x01\x01\x02\x01\x02\x00\x00\
+                \x61\x80\x02\x01\x03\x02\x01\x04\x00\x00\x00\x00";
+  let pairs = Vec::<Pair>::deserialize_enc(::CER, bytes.bytes().by_ref()).unwrap();
+  assert_eq!(pairs, vec![Pair(1, 2), Pair(3, 4)]);
+}
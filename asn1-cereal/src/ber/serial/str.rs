@@ -1,20 +1,32 @@
-//! Implementation of the serialization traits for String.
+//! Implementation of the serialization traits for String, and the
+//! restricted character string types.
 //!
-//! This currently uses OCTET STRING, as ASN.1 was invented before UTF8,
-//! and OCTET STRING is the only thing that can actually hold this.
+//! A Rust `String` is encoded as a PrintableString, but its content isn't
+//! checked, so any UTF-8 will be written and accepted.
 //!
-//! If you require specific types of strings, please use the assoicated
-//! rust type in the prim module.
+//! If you require specific types of strings, please use the associated
+//! wrapper types from this module (also available in the prim module).
+//! These check that their content only uses the permitted alphabet, both
+//! when encoding and decoding.
 
+use std::fmt;
 use std::io;
+use std::str::FromStr;
 
-use ::{BerSerialize, BerDeserialize};
+use ::{BerSerialize, BerDeserialize, Asn1Info};
 use tag;
 use err;
+use ber::serial::segment;
 
-asn1_info!(String, [PRIM UNIVERSAL 19], "OCTET STRING");
+asn1_info!(String, [PRIM UNIVERSAL 19], "PrintableString");
+impl ::Asn1Schema for String {}
 
 impl BerSerialize for String {
+  fn _serialize_enc<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Option<Result<(), err::EncodeError>> {
+    segment::write_segmented(e, Self::asn1_tag(), segment::OCTET_STRING_TAGNUM, self.as_bytes(), None, writer)
+  }
+
   fn serialize_value<E: ::BerEncRules, W: io::Write>
       (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
     try!(writer.write_all(self.as_bytes()));
@@ -23,13 +35,332 @@ impl BerSerialize for String {
 }
 
 impl BerDeserialize for String {
+  fn _deserialize_with_tag<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, tag: tag::Tag, len: tag::Len) -> Option<Result<Self, err::DecodeError>> {
+    // Handle the constructed form here, the primitive form uses the normal behaviour.
+    if segment::is_constructed_form(e, Self::asn1_tag(), tag) {
+      Some(segment::read_octets(e, reader, len).and_then(decode_string))
+    } else {
+      None
+    }
+  }
+
   fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
-      (_: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
     let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+    try!(segment::check_primitive(e, len_num));
     let bytes: Result<Vec<u8>, _> = reader.take(len_num as usize).collect();
-    match String::from_utf8(try!(bytes)) {
-      Ok(str) => Ok(str),
-      Err(_) => Err(err::DecodeError::Custom("Error decoding PrintableString as UTF8")),
+    decode_string(try!(bytes))
+  }
+}
+
+/// Decode the content of a Rust `String`.
+pub(crate) fn decode_string(bytes: Vec<u8>) -> Result<String, err::DecodeError> {
+  match String::from_utf8(bytes) {
+    Ok(str) => Ok(str),
+    Err(_) => Err(err::DecodeError::Custom("Error decoding PrintableString as UTF8")),
+  }
+}
+
+/// Generate a wrapper type for a restricted character string. The encode and
+/// decode functions convert between a Rust string and the content octets, and
+/// return None if any character isn't permitted.
+macro_rules! ber_restricted_string {
+  ($(#[$attr:meta])* $name:ident, $tagnum:expr, $asn1_ty:expr, $encode:expr, $decode:expr) => (
+    $(#[$attr])*
+    #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+    pub struct $name(pub String);
+
+    asn1_info!($name, [PRIM UNIVERSAL $tagnum], $asn1_ty);
+    impl ::Asn1Schema for $name {}
+
+    impl $name {
+      /// Create this string type, returning an error if any character isn't
+      /// in its permitted alphabet.
+      pub fn new<S: Into<String>>(s: S) -> Result<Self, err::DecodeError> {
+        let s = s.into();
+        if $encode(&s).is_none() {
+          return Err(err::DecodeError::InvalidCharset);
+        }
+        Ok($name(s))
+      }
+
+      /// Get this string as a `&str`.
+      pub fn as_str(&self) -> &str {
+        &self.0
+      }
+
+      /// Convert this into a Rust `String`.
+      pub fn into_string(self) -> String {
+        self.0
+      }
+    }
+
+    impl From<$name> for String {
+      fn from(s: $name) -> Self {
+        s.0
+      }
+    }
+
+    impl FromStr for $name {
+      type Err = err::DecodeError;
+
+      fn from_str(s: &str) -> Result<Self, Self::Err> {
+        $name::new(s)
+      }
+    }
+
+    impl fmt::Display for $name {
+      fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+      }
+    }
+
+    impl $name {
+      /// Get the content octets of this string.
+      pub(crate) fn content(&self) -> Result<Vec<u8>, err::EncodeError> {
+        $encode(&self.0).ok_or(
+          err::EncodeError::Custom(concat!("Invalid character in ", $asn1_ty))
+        )
+      }
+
+      /// Create this string from its content octets.
+      pub(crate) fn from_content(bytes: Vec<u8>) -> Result<Self, err::DecodeError> {
+        match $decode(bytes) {
+          Some(s) => Ok($name(s)),
+          None => {
+            warn!("Encountered an invalid character in a {}", $asn1_ty);
+            Err(err::DecodeError::InvalidCharset)
+          },
+        }
+      }
     }
+
+    impl BerSerialize for $name {
+      fn _serialize_enc<E: ::BerEncRules, W: io::Write>
+          (&self, e: E, writer: &mut W) -> Option<Result<(), err::EncodeError>> {
+        if e.segment_size().is_none() {
+          return None;
+        }
+        match self.content() {
+          Ok(bytes) => segment::write_segmented(e, Self::asn1_tag(), segment::OCTET_STRING_TAGNUM,
+                                                        &bytes, None, writer),
+          Err(e) => Some(Err(e)),
+        }
+      }
+
+      fn serialize_value<E: ::BerEncRules, W: io::Write>
+          (&self, _: E, writer: &mut W) -> Result<(), err::EncodeError> {
+        try!(writer.write_all(&try!(self.content())));
+        Ok(())
+      }
+    }
+
+    impl BerDeserialize for $name {
+      fn _deserialize_with_tag<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, reader: &mut I, tag: tag::Tag, len: tag::Len) -> Option<Result<Self, err::DecodeError>> {
+        // Handle the constructed form here, the primitive form uses the normal behaviour.
+        if segment::is_constructed_form(e, Self::asn1_tag(), tag) {
+          Some(segment::read_octets(e, reader, len).and_then($name::from_content))
+        } else {
+          None
+        }
+      }
+
+      fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+        let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+        try!(segment::check_primitive(e, len_num));
+        $name::from_content(try!(segment::read_content(reader, len_num)))
+      }
+    }
+  )
+}
+
+/// Encode a string where each character is a single byte in a subset of ASCII.
+fn encode_ascii(s: &str, valid: fn(u8) -> bool) -> Option<Vec<u8>> {
+  if s.bytes().all(valid) {
+    Some(s.as_bytes().to_vec())
+  } else {
+    None
+  }
+}
+
+/// Decode a string where each character is a single byte in a subset of ASCII.
+fn decode_ascii(bytes: Vec<u8>, valid: fn(u8) -> bool) -> Option<String> {
+  if bytes.iter().cloned().all(valid) {
+    String::from_utf8(bytes).ok()
+  } else {
+    None
+  }
+}
+
+fn is_numeric(b: u8) -> bool {
+  b.is_ascii_digit() || b == b' '
+}
+
+fn is_printable(b: u8) -> bool {
+  b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b)
+}
+
+fn is_ia5(b: u8) -> bool {
+  b < 0x80
+}
+
+fn is_visible(b: u8) -> bool {
+  b >= 0x20 && b < 0x7f
+}
+
+fn encode_utf8(s: &str) -> Option<Vec<u8>> {
+  Some(s.as_bytes().to_vec())
+}
+
+fn decode_utf8(bytes: Vec<u8>) -> Option<String> {
+  String::from_utf8(bytes).ok()
+}
+
+fn encode_latin1(s: &str) -> Option<Vec<u8>> {
+  s.chars().map(|c| if (c as u32) < 0x100 { Some(c as u8) } else { None }).collect()
+}
+
+fn decode_latin1(bytes: Vec<u8>) -> Option<String> {
+  Some(bytes.into_iter().map(|b| b as char).collect())
+}
+
+fn encode_bmp(s: &str) -> Option<Vec<u8>> {
+  let mut bytes = Vec::with_capacity(s.len() * 2);
+  for c in s.chars() {
+    if c as u32 > 0xffff {
+      return None;
+    }
+    bytes.push((c as u32 >> 8) as u8);
+    bytes.push(c as u32 as u8);
+  }
+  Some(bytes)
+}
+
+fn decode_bmp(bytes: Vec<u8>) -> Option<String> {
+  if bytes.len() % 2 != 0 {
+    return None;
+  }
+  // Surrogates aren't permitted, since only the Basic Multilingual Plane can be used.
+  bytes.chunks(2)
+    .map(|c| ::std::char::from_u32((c[0] as u32) << 8 | c[1] as u32))
+    .collect()
+}
+
+fn encode_universal(s: &str) -> Option<Vec<u8>> {
+  let mut bytes = Vec::with_capacity(s.len() * 4);
+  for c in s.chars() {
+    bytes.extend_from_slice(&(c as u32).to_be_bytes());
+  }
+  Some(bytes)
+}
+
+fn decode_universal(bytes: Vec<u8>) -> Option<String> {
+  if bytes.len() % 4 != 0 {
+    return None;
+  }
+  bytes.chunks(4)
+    .map(|c| ::std::char::from_u32((c[0] as u32) << 24 | (c[1] as u32) << 16 |
+                                   (c[2] as u32) << 8 | c[3] as u32))
+    .collect()
+}
+
+ber_restricted_string!(
+  /// A Rust wrapper for UTF8String, which can hold any Unicode character.
+  Utf8String, 12, "UTF8String", encode_utf8, decode_utf8
+);
+
+ber_restricted_string!(
+  /// A Rust wrapper for NumericString, which can only hold digits and spaces.
+  NumericString, 18, "NumericString",
+  |s| encode_ascii(s, is_numeric), |b| decode_ascii(b, is_numeric)
+);
+
+ber_restricted_string!(
+  /// A Rust wrapper for PrintableString, which can hold letters, digits,
+  /// spaces and the characters `'()+,-./:=?`.
+  PrintableString, 19, "PrintableString",
+  |s| encode_ascii(s, is_printable), |b| decode_ascii(b, is_printable)
+);
+
+ber_restricted_string!(
+  /// A Rust wrapper for TeletexString (also known as T61String).
+  ///
+  /// The full T.61 character set relies on escape sequences and combining
+  /// diacritics, so like most implementations this treats the content as
+  /// ISO 8859-1, and can only hold characters up to U+00FF.
+  TeletexString, 20, "TeletexString", encode_latin1, decode_latin1
+);
+
+/// Another name for TeletexString.
+pub type T61String = TeletexString;
+
+ber_restricted_string!(
+  /// A Rust wrapper for IA5String, which can hold any ASCII character.
+  Ia5String, 22, "IA5String",
+  |s| encode_ascii(s, is_ia5), |b| decode_ascii(b, is_ia5)
+);
+
+ber_restricted_string!(
+  /// A Rust wrapper for VisibleString, which can hold printing ASCII
+  /// characters and spaces.
+  VisibleString, 26, "VisibleString",
+  |s| encode_ascii(s, is_visible), |b| decode_ascii(b, is_visible)
+);
+
+ber_restricted_string!(
+  /// A Rust wrapper for UniversalString, which can hold any Unicode character,
+  /// and is encoded as UTF-32BE.
+  UniversalString, 28, "UniversalString", encode_universal, decode_universal
+);
+
+ber_restricted_string!(
+  /// A Rust wrapper for BMPString, which can hold any character in the Unicode
+  /// Basic Multilingual Plane, and is encoded as UTF-16BE.
+  BmpString, 30, "BMPString", encode_bmp, decode_bmp
+);
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn restricted_string_simple() {
+  let mut buf: Vec<u8> = Vec::new();
+  PrintableString::new("Test User 1").unwrap().serialize(&mut buf).unwrap();
+  Ia5String::new("test1@rsa.com").unwrap().serialize(&mut buf).unwrap();
+  BmpString::new("Aé").unwrap().serialize(&mut buf).unwrap();
+  UniversalString::new("\u{1f600}").unwrap().serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"\x13\x0bTest User 1\x16\x0dtest1@rsa.com\
+                          \x1e\x04\x00\x41\x00\xe9\x1c\x04\x00\x01\xf6\x00"[..]);
+
+  let mut reader = buf.bytes();
+  assert_eq!(PrintableString::deserialize(reader.by_ref()).unwrap().as_str(), "Test User 1");
+  assert_eq!(Ia5String::deserialize(reader.by_ref()).unwrap().as_str(), "test1@rsa.com");
+  assert_eq!(BmpString::deserialize(reader.by_ref()).unwrap().as_str(), "Aé");
+  assert_eq!(UniversalString::deserialize(reader.by_ref()).unwrap().as_str(), "\u{1f600}");
+}
+
+#[test]
+fn restricted_string_charset() {
+  assert!(NumericString::new("123 456").is_ok());
+  assert!(NumericString::new("12a").is_err());
+  assert!(PrintableString::new("a@b").is_err());
+  assert!(VisibleString::new("a\tb").is_err());
+  assert!(BmpString::new("\u{1f600}").is_err());
+  assert!(TeletexString::new("\u{100}").is_err());
+
+  // The wrapped string can be changed, so it's checked again when encoding.
+  let s = PrintableString("a@b".into());
+  assert!(s.serialize(&mut Vec::new()).is_err());
+
+  match PrintableString::deserialize(b"\x13\x03a*b".bytes().by_ref()) {
+    Err(err::DecodeError::InvalidCharset) => {},
+    res => panic!("Expected InvalidCharset, got {:?}", res),
   }
+  // An unpaired surrogate, and an odd length.
+  assert!(BmpString::deserialize(b"\x1e\x02\xd8\x00".bytes().by_ref()).is_err());
+  assert!(BmpString::deserialize(b"\x1e\x03\x00\x41\x00".bytes().by_ref()).is_err());
+  assert_eq!(TeletexString::deserialize(b"\x14\x02\xe9t".bytes().by_ref()).unwrap().as_str(), "ét");
 }
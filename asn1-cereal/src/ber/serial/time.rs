@@ -0,0 +1,614 @@
+//! Implementation of UTCTime and GeneralizedTime.
+//!
+//! Both types are encoded as a string of ASCII digits, followed by an optional
+//! time zone. When decoding with BER, every variant that BER permits is accepted:
+//! missing seconds (or minutes for GeneralizedTime), fractional seconds, and
+//! local or offset time zones.
+//!
+//! When the encoding rules require the shortest form (DER), times are converted
+//! to UTC and written in the canonical `YYMMDDHHMMSSZ` or `YYYYMMDDHHMMSS[.f]Z` form,
+//! where the fraction has no trailing zeroes. Only these forms are accepted
+//! when decoding.
+//!
+//! A UTCTime only has a two digit year, which is interpreted as being between
+//! 1950 and 2049.
+
+use std::io;
+
+use ::{BerSerialize, BerDeserialize};
+use tag;
+use err;
+
+/// The time zone of a UTCTime or GeneralizedTime.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TimeZone {
+  /// Local time, with no time zone given. This is only valid for GeneralizedTime.
+  Local,
+  /// Coordinated Universal Time, written as `Z`.
+  Utc,
+  /// An offset from UTC in minutes, written as `+HHMM` or `-HHMM`.
+  Offset(i16),
+}
+
+/// A Rust wrapper for UTCTime.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct UtcTime {
+  /// The full year, between 1950 and 2049.
+  pub year: u16,
+  /// The month, between 1 and 12.
+  pub month: u8,
+  /// The day of the month, starting at 1.
+  pub day: u8,
+  /// The hour, between 0 and 23.
+  pub hour: u8,
+  /// The minute, between 0 and 59.
+  pub minute: u8,
+  /// The second, between 0 and 59.
+  pub second: u8,
+  /// The time zone, which can't be `TimeZone::Local`.
+  pub zone: TimeZone,
+}
+
+asn1_info!(UtcTime, [PRIM UNIVERSAL 23], "UTCTime");
+impl ::Asn1Schema for UtcTime {}
+
+/// A Rust wrapper for GeneralizedTime.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GeneralizedTime {
+  /// The year, between 0 and 9999.
+  pub year: u16,
+  /// The month, between 1 and 12.
+  pub month: u8,
+  /// The day of the month, starting at 1.
+  pub day: u8,
+  /// The hour, between 0 and 23.
+  pub hour: u8,
+  /// The minute, between 0 and 59.
+  pub minute: u8,
+  /// The second, between 0 and 59.
+  pub second: u8,
+  /// The fraction of a second, in nanoseconds.
+  pub nanosecond: u32,
+  /// The time zone.
+  pub zone: TimeZone,
+}
+
+asn1_info!(GeneralizedTime, [PRIM UNIVERSAL 24], "GeneralizedTime");
+impl ::Asn1Schema for GeneralizedTime {}
+
+impl UtcTime {
+  /// Create a UTCTime in UTC.
+  pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+    UtcTime {
+      year: year,
+      month: month,
+      day: day,
+      hour: hour,
+      minute: minute,
+      second: second,
+      zone: TimeZone::Utc,
+    }
+  }
+
+  /// Convert this time to UTC, returning None if it's outside the range a
+  /// UTCTime can represent.
+  pub fn to_utc(&self) -> Option<Self> {
+    GeneralizedTime::from(*self).to_utc_time()
+  }
+
+  fn is_valid(&self) -> bool {
+    self.year >= 1950 && self.year <= 2049 && self.zone != TimeZone::Local &&
+      GeneralizedTime::from(*self).is_valid()
+  }
+}
+
+impl GeneralizedTime {
+  /// Create a GeneralizedTime in UTC.
+  pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+    GeneralizedTime {
+      year: year,
+      month: month,
+      day: day,
+      hour: hour,
+      minute: minute,
+      second: second,
+      nanosecond: 0,
+      zone: TimeZone::Utc,
+    }
+  }
+
+  /// Convert this time to UTC, returning None if it's a local time, or
+  /// the year would be outside the range a GeneralizedTime can represent.
+  pub fn to_utc(&self) -> Option<Self> {
+    let offset = match self.zone {
+      TimeZone::Local => return None,
+      TimeZone::Utc => return Some(*self),
+      TimeZone::Offset(offset) => offset as i64,
+    };
+    let minutes = days_from_civil(self.year as i64, self.month, self.day) * 1440 +
+                  self.hour as i64 * 60 + self.minute as i64 - offset;
+    let (year, month, day) = civil_from_days(minutes.div_euclid(1440));
+    if year < 0 || year > 9999 {
+      return None;
+    }
+    let minutes = minutes.rem_euclid(1440);
+    Some(GeneralizedTime {
+      year: year as u16,
+      month: month,
+      day: day,
+      hour: (minutes / 60) as u8,
+      minute: (minutes % 60) as u8,
+      zone: TimeZone::Utc,
+      ..*self
+    })
+  }
+
+  /// Convert this time to a UTCTime in UTC, dropping any fraction of a second.
+  /// Returns None if it's a local time, or outside the range a UTCTime can represent.
+  pub fn to_utc_time(&self) -> Option<UtcTime> {
+    let time = try_opt!(self.to_utc());
+    if time.year < 1950 || time.year > 2049 {
+      return None;
+    }
+    Some(UtcTime {
+      year: time.year,
+      month: time.month,
+      day: time.day,
+      hour: time.hour,
+      minute: time.minute,
+      second: time.second,
+      zone: time.zone,
+    })
+  }
+
+  fn is_valid(&self) -> bool {
+    let zone_valid = match self.zone {
+      TimeZone::Offset(offset) => offset > -24 * 60 && offset < 24 * 60,
+      _ => true,
+    };
+    self.year <= 9999 && self.month >= 1 && self.month <= 12 &&
+      self.day >= 1 && self.day <= days_in_month(self.year, self.month) &&
+      self.hour < 24 && self.minute < 60 && self.second < 60 &&
+      self.nanosecond < 1_000_000_000 && zone_valid
+  }
+}
+
+impl From<UtcTime> for GeneralizedTime {
+  fn from(time: UtcTime) -> Self {
+    GeneralizedTime {
+      year: time.year,
+      month: time.month,
+      day: time.day,
+      hour: time.hour,
+      minute: time.minute,
+      second: time.second,
+      nanosecond: 0,
+      zone: time.zone,
+    }
+  }
+}
+
+/// A Rust time type that can be encoded as a UTCTime or GeneralizedTime.
+///
+/// Types implementing this are encoded as GeneralizedTime by default, but a
+/// SEQUENCE field can choose either encoding with `#[asn1(time="utc")]` or
+/// `#[asn1(time="generalized")]`.
+pub trait Asn1Time: Sized {
+  /// Convert this time to a GeneralizedTime, returning None if it can't be represented.
+  fn to_generalized_time(&self) -> Option<GeneralizedTime>;
+
+  /// Convert a GeneralizedTime to this type, returning None if it can't be represented.
+  fn from_generalized_time(time: GeneralizedTime) -> Option<Self>;
+
+  /// Convert this time to a UTCTime, returning None if it can't be represented.
+  fn to_utc_time(&self) -> Option<UtcTime> {
+    self.to_generalized_time().and_then(|t| t.to_utc_time())
+  }
+
+  /// Convert a UTCTime to this type, returning None if it can't be represented.
+  fn from_utc_time(time: UtcTime) -> Option<Self> {
+    Self::from_generalized_time(time.into())
+  }
+}
+
+impl Asn1Time for GeneralizedTime {
+  fn to_generalized_time(&self) -> Option<GeneralizedTime> {
+    Some(*self)
+  }
+
+  fn from_generalized_time(time: GeneralizedTime) -> Option<Self> {
+    Some(time)
+  }
+}
+
+impl Asn1Time for UtcTime {
+  fn to_generalized_time(&self) -> Option<GeneralizedTime> {
+    Some((*self).into())
+  }
+
+  fn from_generalized_time(time: GeneralizedTime) -> Option<Self> {
+    time.to_utc_time()
+  }
+
+  fn from_utc_time(time: UtcTime) -> Option<Self> {
+    Some(time)
+  }
+}
+
+/// Generate the ASN.1 implementation for a type implementing `Asn1Time`,
+/// which is encoded as a GeneralizedTime.
+macro_rules! ber_cereal_time {
+  ($rs_type:ty) => (
+    asn1_info!($rs_type, [PRIM UNIVERSAL 24], "GeneralizedTime");
+    impl ::Asn1Schema for $rs_type {}
+
+    impl ::BerSerialize for $rs_type {
+      fn serialize_value<E: ::BerEncRules, W: ::std::io::Write>
+          (&self, e: E, writer: &mut W) -> Result<(), ::err::EncodeError> {
+        let time = try!(::ber::serial::time::Asn1Time::to_generalized_time(self)
+          .ok_or(::err::EncodeError::Custom("Time can't be represented as a GeneralizedTime")));
+        ::BerSerialize::serialize_value(&time, e, writer)
+      }
+    }
+
+    impl ::BerDeserialize for $rs_type {
+      fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+          (e: E, reader: &mut I, len: ::tag::Len) -> Result<Self, ::err::DecodeError> {
+        let time: ::ber::serial::time::GeneralizedTime =
+          try!(::BerDeserialize::deserialize_value(e, reader, len));
+        ::ber::serial::time::Asn1Time::from_generalized_time(time)
+          .ok_or(::err::DecodeError::InvalidTime)
+      }
+    }
+  )
+}
+
+fn is_leap_year(year: u16) -> bool {
+  year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+  match month {
+    2 if is_leap_year(year) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// Get the number of days between 1970-01-01 and a date.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = if year >= 0 { year } else { year - 399 } / 400;
+  let yoe = year - era * 400;
+  let month = month as i64;
+  let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+  let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  era * 146097 + doe - 719468
+}
+
+/// Get the date that is a number of days from 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+  let days = days + 719468;
+  let era = if days >= 0 { days } else { days - 146096 } / 146097;
+  let doe = days - era * 146097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+  let year = yoe + era * 400;
+  (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// A simple parser over the characters of a time string.
+struct Parser<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn peek(&self) -> Option<u8> {
+    self.bytes.get(self.pos).cloned()
+  }
+
+  fn peek_digit(&self) -> bool {
+    self.peek().map_or(false, |b| b.is_ascii_digit())
+  }
+
+  fn next(&mut self) -> Option<u8> {
+    let byte = self.peek();
+    self.pos += 1;
+    byte
+  }
+
+  /// Parse exactly `count` digits as a number.
+  fn digits(&mut self, count: usize) -> Option<u16> {
+    let mut value = 0;
+    for _ in 0..count {
+      match self.next() {
+        Some(b) if b.is_ascii_digit() => value = value * 10 + (b - b'0') as u16,
+        _ => return None,
+      }
+    }
+    Some(value)
+  }
+
+  /// Parse a time zone, which must end the string. UTCTime requires the
+  /// minutes of an offset, while GeneralizedTime allows them to be left out.
+  fn zone(&mut self, need_minutes: bool) -> Option<TimeZone> {
+    let zone = match self.next() {
+      None => return Some(TimeZone::Local),
+      Some(b'Z') => TimeZone::Utc,
+      Some(sign @ b'+') | Some(sign @ b'-') => {
+        let hours = try_opt!(self.digits(2));
+        let minutes = if need_minutes || self.peek().is_some() {
+          try_opt!(self.digits(2))
+        } else {
+          0
+        };
+        if hours > 23 || minutes > 59 {
+          return None;
+        }
+        let offset = (hours * 60 + minutes) as i16;
+        TimeZone::Offset(if sign == b'-' { -offset } else { offset })
+      },
+      _ => return None,
+    };
+    if self.peek().is_some() {
+      return None;
+    }
+    Some(zone)
+  }
+}
+
+fn parse_utc_time(bytes: &[u8], canonical: bool) -> Option<UtcTime> {
+  let mut p = Parser { bytes: bytes, pos: 0 };
+  let year = try_opt!(p.digits(2));
+  let month = try_opt!(p.digits(2));
+  let day = try_opt!(p.digits(2));
+  let hour = try_opt!(p.digits(2));
+  let minute = try_opt!(p.digits(2));
+  let second = if p.peek_digit() {
+    Some(try_opt!(p.digits(2)))
+  } else {
+    None
+  };
+  let zone = try_opt!(p.zone(true));
+
+  if canonical && (second.is_none() || zone != TimeZone::Utc) {
+    warn!("Encountered a UTCTime that isn't in the canonical form");
+    return None;
+  }
+
+  let time = UtcTime {
+    year: if year < 50 { 2000 + year } else { 1900 + year },
+    month: month as u8,
+    day: day as u8,
+    hour: hour as u8,
+    minute: minute as u8,
+    second: second.unwrap_or(0) as u8,
+    zone: zone,
+  };
+  if time.is_valid() { Some(time) } else { None }
+}
+
+fn parse_generalized_time(bytes: &[u8], canonical: bool) -> Option<GeneralizedTime> {
+  let mut p = Parser { bytes: bytes, pos: 0 };
+  let year = try_opt!(p.digits(4));
+  let month = try_opt!(p.digits(2));
+  let day = try_opt!(p.digits(2));
+  let hour = try_opt!(p.digits(2)) as u64;
+
+  // Any fraction applies to the last unit that was given, so track its size in seconds.
+  let mut unit = 3600;
+  let mut minute = 0;
+  let mut second = None;
+  if p.peek_digit() {
+    minute = try_opt!(p.digits(2)) as u64;
+    unit = 60;
+    if p.peek_digit() {
+      second = Some(try_opt!(p.digits(2)) as u64);
+      unit = 1;
+    }
+  }
+
+  let mut nanos: u64 = 0;
+  if let Some(sep) = p.peek().filter(|b| *b == b'.' || *b == b',') {
+    p.pos += 1;
+    let start = p.pos;
+    while p.peek_digit() {
+      p.pos += 1;
+    }
+    let fraction = &bytes[start..p.pos];
+    if fraction.is_empty() ||
+       (canonical && (sep != b'.' || fraction.last() == Some(&b'0'))) {
+      return None;
+    }
+    // Digits beyond what can be represented in nanoseconds are dropped.
+    let (mut num, mut den): (u128, u128) = (0, 1);
+    for digit in fraction.iter().take(18) {
+      num = num * 10 + (digit - b'0') as u128;
+      den *= 10;
+    }
+    nanos = (num * unit as u128 * 1_000_000_000 / den) as u64;
+  }
+  let zone = try_opt!(p.zone(false));
+
+  if canonical && (second.is_none() || zone != TimeZone::Utc) {
+    warn!("Encountered a GeneralizedTime that isn't in the canonical form");
+    return None;
+  }
+
+  let seconds = hour * 3600 + minute * 60 + second.unwrap_or(0) + nanos / 1_000_000_000;
+  let time = GeneralizedTime {
+    year: year,
+    month: month as u8,
+    day: day as u8,
+    hour: (seconds / 3600) as u8,
+    minute: (seconds / 60 % 60) as u8,
+    second: (seconds % 60) as u8,
+    nanosecond: (nanos % 1_000_000_000) as u32,
+    zone: zone,
+  };
+  if time.is_valid() { Some(time) } else { None }
+}
+
+fn write_zone<W: io::Write>(zone: TimeZone, writer: &mut W) -> Result<(), err::EncodeError> {
+  match zone {
+    TimeZone::Local => {},
+    TimeZone::Utc => try!(write!(writer, "Z")),
+    TimeZone::Offset(offset) => {
+      let sign = if offset < 0 { '-' } else { '+' };
+      let offset = offset.unsigned_abs();
+      try!(write!(writer, "{}{:02}{:02}", sign, offset / 60, offset % 60));
+    },
+  }
+  Ok(())
+}
+
+/// Read the content octets of a time type.
+fn read_content<I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut I, len: tag::Len) -> Result<Vec<u8>, err::DecodeError> {
+  let len_num = try!(len.as_num().ok_or(err::DecodeError::PrimIndef));
+  let bytes: Result<Vec<u8>, _> = reader.take(len_num as usize).collect();
+  let bytes = try!(bytes);
+  if bytes.len() as u64 != len_num {
+    return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                              "Got unexpected EOF while reading stream").into());
+  }
+  Ok(bytes)
+}
+
+impl BerSerialize for UtcTime {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    if !self.is_valid() {
+      return Err(err::EncodeError::Custom("Invalid UTCTime"));
+    }
+    let time = if e.shortest_form() {
+      try!(self.to_utc().ok_or(err::EncodeError::Custom("UTCTime out of range in UTC")))
+    } else {
+      *self
+    };
+    try!(write!(writer, "{:02}{:02}{:02}{:02}{:02}{:02}", time.year % 100, time.month,
+                time.day, time.hour, time.minute, time.second));
+    write_zone(time.zone, writer)
+  }
+}
+
+impl BerDeserialize for UtcTime {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let bytes = try!(read_content(reader, len));
+    parse_utc_time(&bytes, e.shortest_form()).ok_or(err::DecodeError::InvalidTime)
+  }
+}
+
+impl BerSerialize for GeneralizedTime {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    if !self.is_valid() {
+      return Err(err::EncodeError::Custom("Invalid GeneralizedTime"));
+    }
+    let time = if e.shortest_form() {
+      try!(self.to_utc().ok_or(err::EncodeError::Custom("GeneralizedTime can't be converted to UTC")))
+    } else {
+      *self
+    };
+    try!(write!(writer, "{:04}{:02}{:02}{:02}{:02}{:02}", time.year, time.month,
+                time.day, time.hour, time.minute, time.second));
+    if time.nanosecond != 0 {
+      let fraction = format!("{:09}", time.nanosecond);
+      try!(write!(writer, ".{}", fraction.trim_end_matches('0')));
+    }
+    write_zone(time.zone, writer)
+  }
+}
+
+impl BerDeserialize for GeneralizedTime {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let bytes = try!(read_content(reader, len));
+    parse_generalized_time(&bytes, e.shortest_form()).ok_or(err::DecodeError::InvalidTime)
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn utc_time_simple() {
+  let bytes = b"\x17\x0d491231235959Z";
+  let time = UtcTime::new(2049, 12, 31, 23, 59, 59);
+  let mut buf: Vec<u8> = Vec::new();
+  time.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  assert_eq!(UtcTime::deserialize(bytes.bytes().by_ref()).unwrap(), time);
+
+  let decoded = UtcTime::deserialize(b"\x17\x0d500101000000Z".bytes().by_ref()).unwrap();
+  assert_eq!(decoded.year, 1950);
+}
+
+#[test]
+fn utc_time_ber_variants() {
+  let time = UtcTime::deserialize(b"\x17\x0f9101021205-0130".bytes().by_ref()).unwrap();
+  assert_eq!(time.zone, TimeZone::Offset(-90));
+  assert_eq!(time.second, 0);
+
+  // DER converts to UTC, and requires that form when decoding.
+  let mut buf: Vec<u8> = Vec::new();
+  time.serialize_enc(::DER, &mut buf).unwrap();
+  assert_eq!(&buf, b"\x17\x0d910102133500Z");
+  for bytes in &[&b"\x17\x0f9101021205-0130"[..], &b"\x17\x0b9101021335Z"[..]] {
+    match UtcTime::deserialize_enc(::DER, bytes.bytes().by_ref()) {
+      Err(err::DecodeError::InvalidTime) => {},
+      res => panic!("Expected InvalidTime, got {:?}", res),
+    }
+  }
+  assert!(UtcTime::deserialize(b"\x17\x0c910102133500".bytes().by_ref()).is_err());
+  assert!(UtcTime::deserialize(b"\x17\x0d910230133500Z".bytes().by_ref()).is_err());
+
+  // Offsets of a day or more can't be encoded, and mustn't overflow.
+  for &offset in &[24 * 60, -24 * 60, ::std::i16::MIN, ::std::i16::MAX] {
+    let mut time = UtcTime::new(2049, 12, 31, 23, 59, 59);
+    time.zone = TimeZone::Offset(offset);
+    assert!(time.serialize(&mut Vec::new()).is_err());
+    assert!(GeneralizedTime::from(time).serialize(&mut Vec::new()).is_err());
+  }
+}
+
+#[test]
+fn generalized_time_simple() {
+  let bytes = b"\x18\x1320200229120000.125Z";
+  let mut time = GeneralizedTime::new(2020, 2, 29, 12, 0, 0);
+  time.nanosecond = 125_000_000;
+  let mut buf: Vec<u8> = Vec::new();
+  time.serialize_enc(::DER, &mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  assert_eq!(GeneralizedTime::deserialize_enc(::DER, bytes.bytes().by_ref()).unwrap(), time);
+}
+
+#[test]
+fn generalized_time_ber_variants() {
+  // Fractional hours, with a local time.
+  let time = GeneralizedTime::deserialize(b"\x18\x0c2019010112,5".bytes().by_ref()).unwrap();
+  assert_eq!((time.hour, time.minute, time.second), (12, 30, 0));
+  assert_eq!(time.zone, TimeZone::Local);
+  assert!(time.serialize_enc(::DER, &mut Vec::new()).is_err());
+
+  let time = GeneralizedTime::deserialize(b"\x18\x1319991231230000.5-01".bytes().by_ref()).unwrap();
+  let mut buf: Vec<u8> = Vec::new();
+  time.serialize_enc(::DER, &mut buf).unwrap();
+  assert_eq!(&buf, b"\x18\x1120000101000000.5Z");
+
+  // These are valid BER, but not canonical.
+  for bytes in &[&b"\x18\x10200001010000.50Z"[..], &b"\x18\x1020000101000000,5Z"[..],
+                 &b"\x18\x0d200001010000Z"[..], &b"\x18\x1320000101000000+0000"[..]] {
+    assert!(GeneralizedTime::deserialize(bytes.bytes().by_ref()).is_ok());
+    match GeneralizedTime::deserialize_enc(::DER, bytes.bytes().by_ref()) {
+      Err(err::DecodeError::InvalidTime) => {},
+      res => panic!("Expected InvalidTime, got {:?}", res),
+    }
+  }
+}
@@ -45,7 +45,7 @@ pub trait BerSerialize: Asn1Info {
     trace!("Encoding with tag {}", tag);
 
     // If this is indefinite length and constructed, write the data directly.
-    if E::len_rules() == enc::LenEnc::Indefinite &&
+    if e.len_rules() == enc::LenEnc::Indefinite &&
        tag.constructed {
       debug!("Using indefinite length");
       try!(tag::Len::Indef.write_len(writer));
@@ -93,7 +93,7 @@ pub trait BerDeserialize: Asn1Info + Sized {
   /// Deserialize ASN.1 data into a Rust value, using a specific set of encoding rules.
   fn deserialize_enc<E: enc::BerEncRules, I: Iterator<Item=io::Result<u8>>>
       (e: E, reader: &mut I) -> Result<Self, err::DecodeError> {
-    let (tag, len) = try!(tag::read_taglen(reader));
+    let (tag, len) = try!(tag::read_taglen_enc(e, reader));
     Self::deserialize_with_tag(e, reader, tag, len)
   }
 
@@ -113,7 +113,7 @@ pub trait BerDeserialize: Asn1Info + Sized {
 
     // If we have a tag, ensure the given tag matches.
     if let Some(our_tag) = Self::asn1_tag() {
-      if tag != our_tag {
+      if !enc::tag_matches(e, tag, our_tag) {
         warn!("Expected tag {}, but found tag {}", our_tag, tag);
         return Err(err::DecodeError::TagTypeMismatch);
       }
@@ -121,24 +121,13 @@ pub trait BerDeserialize: Asn1Info + Sized {
       debug!("Decoding type with no tag");
     }
 
-    // Handle any indefinite length error conditions.
-    if len == tag::Len::Indef {
-      // Return an error if the encoding rules only allow definite length
-      // encoding.
-      if E::len_rules() == enc::LenEnc::Definite {
-        warn!("Encountered indefinite length encoding, but encoding rules don't allow this");
-        return Err(err::DecodeError::IndefiniteLen);
-      // If this element is primitve, the length isn't allowed to be indefinite length.
-      } else if !tag.constructed {
-        warn!("Encountered indefinite length encoding, but this is a primitive element");
-        return Err(err::DecodeError::PrimIndef)
-      }
-    }
+    // Handle any length error conditions.
+    try!(enc::check_len(e, tag, len));
     // Read the main data.
     let item: Self = try!(Self::deserialize_value(e, reader, len));
 
-    // If this is encoded with an indefinte length, try to read the end octets.
-    if len == tag::Len::Indef {
+    // If this is encoded with an indefinite length, try to read the end octets.
+    if len == tag::Len::Indef && !Self::_reads_indef_end(e) {
       try!(tag::Len::read_indef_end(reader));
     }
 
@@ -155,11 +144,36 @@ pub trait BerDeserialize: Asn1Info + Sized {
     None
   }
 
+  /// Returns true when `deserialize_value` reads the end-of-contents octets
+  /// of an indefinite length encoding itself, like a SEQUENCE OF, which has to
+  /// read them to find its last element.
+  ///
+  /// By default this is false, and they are read by `deserialize_with_tag`,
+  /// or by the caller of `deserialize_value` when decoding an implicit tag.
+  fn _reads_indef_end<E: enc::BerEncRules>(e: E) -> bool {
+    let _ = e;
+    false
+  }
+
   /// Deserialize an ASN.1 value from a BER stream, after having the tag and length
   /// decoded. Will be called directly when decoding an implici tag.
   ///
   /// The data length must be explicitly passed to this function. For primitive types,
-  /// an error will be returned if this length is Indefinite.
+  /// an error will be returned if this length is Indefinite. For constructed types,
+  /// the end-of-contents octets of an Indefinite length aren't read here, unless
+  /// `_reads_indef_end` returns true.
   fn deserialize_value<E: enc::BerEncRules, I: Iterator<Item=io::Result<u8>>>
     (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError>;
 }
+
+/// Deserialize a value with an implicit tag, after having the tag and length
+/// decoded. Unlike calling `deserialize_value` directly, this also reads the
+/// end-of-contents octets of an indefinite length encoding.
+pub fn deserialize_implicit<T, E, I>(e: E, reader: &mut I, len: tag::Len) -> Result<T, err::DecodeError>
+    where T: BerDeserialize, E: enc::BerEncRules, I: Iterator<Item=io::Result<u8>> {
+  let item = try!(T::deserialize_value(e, reader, len));
+  if len == tag::Len::Indef && !T::_reads_indef_end(e) {
+    try!(tag::Len::read_indef_end(reader));
+  }
+  Ok(item)
+}
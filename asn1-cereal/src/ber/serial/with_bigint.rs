@@ -0,0 +1,96 @@
+//! Implementation of the serialization traits for `num_bigint::BigInt` and
+//! `num_bigint::BigUint`, as INTEGER.
+//!
+//! This is only available with the `num-bigint` feature. Both types are
+//! converted through `Integer`, so under DER, redundant leading octets are
+//! rejected when decoding.
+
+use std::io;
+
+use num_bigint::{BigInt, BigUint};
+
+use ::{BerSerialize, BerDeserialize};
+use ber::serial::int::Integer;
+use tag;
+use err;
+
+asn1_info!(BigInt, [PRIM UNIVERSAL 2], "INTEGER");
+asn1_info!(BigUint, [PRIM UNIVERSAL 2], "INTEGER");
+impl ::Asn1Schema for BigInt {}
+impl ::Asn1Schema for BigUint {}
+
+impl<'a> From<&'a BigInt> for Integer {
+  fn from(int: &'a BigInt) -> Self {
+    Integer::from_bytes(&int.to_signed_bytes_be()).expect("BigInt always has at least one byte")
+  }
+}
+
+impl<'a> From<&'a BigUint> for Integer {
+  fn from(int: &'a BigUint) -> Self {
+    Integer::from_unsigned_bytes(&int.to_bytes_be())
+  }
+}
+
+impl<'a> From<&'a Integer> for BigInt {
+  fn from(int: &'a Integer) -> Self {
+    BigInt::from_signed_bytes_be(int.as_bytes())
+  }
+}
+
+impl BerSerialize for BigInt {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    Integer::from(self).serialize_value(e, writer)
+  }
+}
+
+impl BerDeserialize for BigInt {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let int = try!(Integer::deserialize_value(e, reader, len));
+    Ok(BigInt::from(&int))
+  }
+}
+
+impl BerSerialize for BigUint {
+  fn serialize_value<E: ::BerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    Integer::from(self).serialize_value(e, writer)
+  }
+}
+
+impl BerDeserialize for BigUint {
+  fn deserialize_value<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I, len: tag::Len) -> Result<Self, err::DecodeError> {
+    let int = try!(Integer::deserialize_value(e, reader, len));
+    match int.to_unsigned_bytes() {
+      Some(bytes) => Ok(BigUint::from_bytes_be(bytes)),
+      None => Err(err::DecodeError::IntegerOverflow),
+    }
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn bigint_simple() {
+  let int: BigInt = "-340282366920938463463374607431768211457".parse().unwrap();
+  let mut buf: Vec<u8> = Vec::new();
+  int.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"\x02\x11\xfe\xff\xff\xff\xff\xff\xff\xff\xff\
+                          \xff\xff\xff\xff\xff\xff\xff\xff"[..]);
+  assert_eq!(BigInt::deserialize(buf.bytes().by_ref()).unwrap(), int);
+
+  let uint = BigUint::from(0x80u8);
+  buf.clear();
+  uint.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x02\x02\x00\x80");
+  assert_eq!(BigUint::deserialize(buf.bytes().by_ref()).unwrap(), uint);
+
+  assert!(BigUint::deserialize(b"\x02\x01\xff".bytes().by_ref()).is_err());
+  match BigInt::deserialize_enc(::DER, b"\x02\x02\xff\x80".bytes().by_ref()) {
+    Err(err::DecodeError::NonMinimalInteger) => {},
+    res => panic!("Expected NonMinimalInteger, got {:?}", res),
+  }
+}
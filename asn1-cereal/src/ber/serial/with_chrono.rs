@@ -0,0 +1,56 @@
+//! Implementation of the serialization traits for `chrono::DateTime<Utc>`.
+//!
+//! This is only available with the `chrono` feature. By default this is
+//! encoded as a GeneralizedTime, see `ber::serial::time::Asn1Time` for using
+//! UTCTime instead.
+
+use chrono::{DateTime, Utc, NaiveDate, Datelike, Timelike};
+
+use ber::serial::time::{Asn1Time, GeneralizedTime, TimeZone};
+
+impl Asn1Time for DateTime<Utc> {
+  fn to_generalized_time(&self) -> Option<GeneralizedTime> {
+    if self.year() < 0 || self.year() > 9999 {
+      return None;
+    }
+    Some(GeneralizedTime {
+      year: self.year() as u16,
+      month: self.month() as u8,
+      day: self.day() as u8,
+      hour: self.hour() as u8,
+      minute: self.minute() as u8,
+      second: self.second() as u8,
+      // chrono represents a leap second as an extra second of nanoseconds,
+      // which has no equivalent here.
+      nanosecond: self.nanosecond() % 1_000_000_000,
+      zone: TimeZone::Utc,
+    })
+  }
+
+  fn from_generalized_time(time: GeneralizedTime) -> Option<Self> {
+    let time = try_opt!(time.to_utc());
+    let date = try_opt!(NaiveDate::from_ymd_opt(time.year as i32, time.month as u32, time.day as u32));
+    let date_time = try_opt!(date.and_hms_nano_opt(time.hour as u32, time.minute as u32,
+                                                   time.second as u32, time.nanosecond));
+    Some(::chrono::TimeZone::from_utc_datetime(&Utc, &date_time))
+  }
+}
+
+ber_cereal_time!(DateTime<Utc>);
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use ::{BerSerialize, BerDeserialize};
+
+#[test]
+fn chrono_date_time() {
+  let bytes = b"\x18\x1320200229120000.125Z";
+  let time = NaiveDate::from_ymd_opt(2020, 2, 29).unwrap().and_hms_milli_opt(12, 0, 0, 125).unwrap();
+  let time = ::chrono::TimeZone::from_utc_datetime(&Utc, &time);
+  let mut buf: Vec<u8> = Vec::new();
+  time.serialize_enc(::DER, &mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  assert_eq!(DateTime::<Utc>::deserialize(bytes.bytes().by_ref()).unwrap(), time);
+  assert_eq!(time.to_utc_time(), Some(::UtcTime::new(2020, 2, 29, 12, 0, 0)));
+}
@@ -0,0 +1,74 @@
+//! Implementation of the serialization traits for `time::OffsetDateTime`.
+//!
+//! This is only available with the `time` feature. By default this is
+//! encoded as a GeneralizedTime, see `ber::serial::time::Asn1Time` for using
+//! UTCTime instead.
+//!
+//! The UTC offset is kept when encoding with BER, as long as it's a whole
+//! number of minutes.
+
+use std::convert::TryFrom;
+
+use time::{OffsetDateTime, UtcOffset, Date, Month, Time, PrimitiveDateTime};
+
+use ber::serial::time::{Asn1Time, GeneralizedTime, TimeZone};
+
+impl Asn1Time for OffsetDateTime {
+  fn to_generalized_time(&self) -> Option<GeneralizedTime> {
+    let offset = self.offset();
+    let (time, zone) = if offset.is_utc() {
+      (*self, TimeZone::Utc)
+    } else if offset.seconds_past_minute() == 0 {
+      (*self, TimeZone::Offset(offset.whole_minutes()))
+    } else {
+      (self.to_offset(UtcOffset::UTC), TimeZone::Utc)
+    };
+    if time.year() < 0 || time.year() > 9999 {
+      return None;
+    }
+    Some(GeneralizedTime {
+      year: time.year() as u16,
+      month: u8::from(time.month()),
+      day: time.day(),
+      hour: time.hour(),
+      minute: time.minute(),
+      second: time.second(),
+      nanosecond: time.nanosecond(),
+      zone: zone,
+    })
+  }
+
+  fn from_generalized_time(time: GeneralizedTime) -> Option<Self> {
+    let offset = match time.zone {
+      TimeZone::Local => return None,
+      TimeZone::Utc => UtcOffset::UTC,
+      TimeZone::Offset(offset) => try_opt!(UtcOffset::from_whole_seconds(offset as i32 * 60).ok()),
+    };
+    let month = try_opt!(Month::try_from(time.month).ok());
+    let date = try_opt!(Date::from_calendar_date(time.year as i32, month, time.day).ok());
+    let clock = try_opt!(Time::from_hms_nano(time.hour, time.minute, time.second, time.nanosecond).ok());
+    Some(PrimitiveDateTime::new(date, clock).assume_offset(offset))
+  }
+}
+
+ber_cereal_time!(OffsetDateTime);
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use ::{BerSerialize, BerDeserialize};
+
+#[test]
+fn time_offset_date_time() {
+  let bytes = b"\x18\x1320000101013000+0130";
+  let time = OffsetDateTime::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(time.offset().whole_minutes(), 90);
+  assert_eq!(time.unix_timestamp(), 946684800);
+
+  let mut buf: Vec<u8> = Vec::new();
+  time.serialize_enc(::BER, &mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  buf.clear();
+  time.serialize_enc(::DER, &mut buf).unwrap();
+  assert_eq!(&buf, b"\x18\x0f20000101000000Z");
+}
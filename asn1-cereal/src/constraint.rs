@@ -0,0 +1,89 @@
+//! Subtype constraints and extensibility markers of ASN.1 types.
+//!
+//! BER doesn't depend on constraints, but encodings like PER use them to
+//! decide how many bits a value needs. The `Asn1Info` trait provides the
+//! constraints of a type, and derived types can declare them with the
+//! `range`, `size` and `extensible` attributes.
+//!
+//! ```
+//! use asn1_cereal::constraint::{Constraints, Range};
+//!
+//! // For INTEGER (0..255, ...)
+//! let c = Constraints::value(Range { extensible: true, .. Range::new(Some(0), Some(255)) });
+//! assert!(c.value.unwrap().contains(255));
+//! assert!(!c.value.unwrap().contains(256));
+//! ```
+
+/// An inclusive range of permitted values (for a value constraint) or sizes
+/// (for a size constraint). A bound that is None is MIN or MAX.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Range {
+  /// The lower bound of this range.
+  pub lower: Option<i128>,
+  /// The upper bound of this range.
+  pub upper: Option<i128>,
+  /// Whether this range has an extension marker, so values outside the
+  /// range are permitted.
+  pub extensible: bool,
+}
+
+impl Range {
+  /// Create a range that isn't extensible.
+  pub fn new(lower: Option<i128>, upper: Option<i128>) -> Self {
+    Range {
+      lower: lower,
+      upper: upper,
+      extensible: false,
+    }
+  }
+
+  /// Create a range permitting a single value.
+  pub fn single(value: i128) -> Self {
+    Range::new(Some(value), Some(value))
+  }
+
+  /// Returns true when a value is in the root of this range, ignoring the
+  /// extension marker.
+  pub fn contains(&self, value: i128) -> bool {
+    self.lower.map_or(true, |l| value >= l) && self.upper.map_or(true, |u| value <= u)
+  }
+
+  /// Get the number of values in this range, if it has both bounds and
+  /// the number fits in a u128.
+  pub fn count(&self) -> Option<u128> {
+    match (self.lower, self.upper) {
+      (Some(l), Some(u)) if u >= l => (u.wrapping_sub(l) as u128).checked_add(1),
+      _ => None,
+    }
+  }
+}
+
+/// The constraints of an ASN.1 type.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Constraints {
+  /// The permitted values of an INTEGER.
+  pub value: Option<Range>,
+  /// The permitted sizes of a string, or the number of elements in
+  /// a SEQUENCE OF.
+  pub size: Option<Range>,
+  /// Whether a SEQUENCE, SET, CHOICE or ENUMERATED has an extension marker.
+  pub extensible: bool,
+}
+
+impl Constraints {
+  /// Create constraints with only a value constraint.
+  pub fn value(range: Range) -> Self {
+    Constraints {
+      value: Some(range),
+      .. Constraints::default()
+    }
+  }
+
+  /// Create constraints with only a size constraint.
+  pub fn size(range: Range) -> Self {
+    Constraints {
+      size: Some(range),
+      .. Constraints::default()
+    }
+  }
+}
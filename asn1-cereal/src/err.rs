@@ -44,6 +44,60 @@ pub enum DecodeError {
   /// Indefinite length encoding was started, but no terminator was found
   /// at the end.
   IndefiniteLenEnd,
+  /// A tag number didn't use the minimum number of octets, where the encoding
+  /// rules require the shortest form.
+  NonMinimalTag,
+  /// A definite length didn't use the minimum number of octets, where the
+  /// encoding rules require the shortest form.
+  NonMinimalLength,
+  /// Definite length encoding appeared on a constructed element, when the
+  /// encoding rules require indefinite length encoding.
+  DefiniteLen,
+  /// An INTEGER was encoded with no content octets.
+  InvalidInteger,
+  /// An INTEGER had redundant leading octets, where the encoding rules
+  /// require the minimal encoding.
+  NonMinimalInteger,
+  /// An INTEGER value is too large (or small) to be represented by the Rust type.
+  IntegerOverflow,
+  /// A BOOLEAN didn't have exactly one content octet, or wasn't 0x00 or 0xFF
+  /// where the encoding rules require the shortest form.
+  InvalidBool,
+  /// A NULL was encoded with a non-zero length.
+  InvalidNull,
+  /// The content of a REAL was malformed, or wasn't in the canonical form where
+  /// the encoding rules require it.
+  InvalidReal,
+  /// The content of a BIT STRING was malformed, or had non-zero unused bits
+  /// where the encoding rules don't allow them.
+  InvalidBitString,
+  /// A string type was encoded in the constructed (segmented) form, where the
+  /// encoding rules require the primitive form, or its segments weren't the
+  /// size required by the encoding rules.
+  SegmentedString,
+  /// The content of an OBJECT IDENTIFIER or RELATIVE-OID was malformed.
+  InvalidOid,
+  /// The content of a UTCTime or GeneralizedTime was malformed, or wasn't in
+  /// the canonical form where the encoding rules require it.
+  InvalidTime,
+  /// A character string contained a character outside its permitted alphabet,
+  /// or its content was malformed.
+  InvalidCharset,
+  /// The elements of a SET OF weren't sorted, where the encoding rules
+  /// require them to be.
+  UnsortedSet,
+  /// A SET contained more than one component with the same tag.
+  DuplicateComponent,
+  /// A SET was missing a component that isn't OPTIONAL.
+  MissingComponent,
+  /// A value didn't satisfy the constraints of its type.
+  ConstraintViolation,
+  /// An ENUMERATED value didn't match any known value.
+  UnknownEnumerated,
+  /// The XML of an XER encoding was malformed, or didn't match the type.
+  InvalidXml,
+  /// The JSON of a JER encoding was malformed, or didn't match the type.
+  InvalidJson,
   /// Custom decoding error.
   Custom(&'static str),
 }
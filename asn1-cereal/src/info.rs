@@ -14,6 +14,18 @@ pub trait Asn1Info {
   fn asn1_constructed<E: ::BerEncRules>(_e: E) -> bool {
     Self::asn1_tag().map_or(false, |t| t.constructed)
   }
+
+  /// Get the constraints of this ASN.1 type, which are used by encodings like PER.
+  fn asn1_constraints() -> ::constraint::Constraints {
+    ::constraint::Constraints::default()
+  }
+
+  /// Get the ASN.1 identifiers of the components of a SEQUENCE or SET, the
+  /// alternatives of a CHOICE, or the values of an ENUMERATED, in the order
+  /// they're declared. These are used by encodings like XER.
+  fn asn1_fields() -> &'static [&'static str] {
+    &[]
+  }
 }
 
 #[macro_export]
@@ -0,0 +1,332 @@
+//! Reading and writing the JSON values that JER is built from.
+
+use std::io;
+
+use err;
+use byte::read_byte;
+
+/// Write a JSON string, escaping any characters that need it.
+pub fn write_string<W: io::Write>(writer: &mut W, s: &str) -> io::Result<()> {
+  try!(writer.write_all(b"\""));
+  for c in s.chars() {
+    match c {
+      '"' => try!(writer.write_all(b"\\\"")),
+      '\\' => try!(writer.write_all(b"\\\\")),
+      '\n' => try!(writer.write_all(b"\\n")),
+      '\r' => try!(writer.write_all(b"\\r")),
+      '\t' => try!(writer.write_all(b"\\t")),
+      c if (c as u32) < 0x20 => try!(write!(writer, "\\u{:04x}", c as u32)),
+      c => try!(write!(writer, "{}", c)),
+    }
+  }
+  try!(writer.write_all(b"\""));
+  Ok(())
+}
+
+/// Write the name of an object member, and the separator before its value.
+/// `first` is true before the first member of an object, and is cleared.
+pub fn write_key<W: io::Write>(writer: &mut W, key: &str, first: &mut bool) -> io::Result<()> {
+  if !*first {
+    try!(writer.write_all(b","));
+  }
+  *first = false;
+  try!(write_string(writer, key));
+  writer.write_all(b":")
+}
+
+/// A reader for the values of a JER encoding.
+///
+/// Objects are read with `start_object` and then `next_key` until it returns
+/// None, and arrays with `start_array` and then `next_item` until it returns
+/// false.
+pub struct JsonReader<I: Iterator<Item=io::Result<u8>>> {
+  reader: I,
+  peeked: Option<u8>,
+  /// Whether a member or item has been read, for each open object or array.
+  started: Vec<bool>,
+}
+
+impl<I: Iterator<Item=io::Result<u8>>> JsonReader<I> {
+  /// Create a new JsonReader from an Iterator.
+  pub fn new(reader: I) -> JsonReader<I> {
+    JsonReader {
+      reader: reader,
+      peeked: None,
+      started: Vec::new(),
+    }
+  }
+
+  /// Turn this JsonReader back into the original reader used to create it.
+  pub fn into_reader(self) -> I {
+    self.reader
+  }
+
+  fn next_byte(&mut self) -> Result<u8, err::DecodeError> {
+    match self.peeked.take() {
+      Some(byte) => Ok(byte),
+      None => Ok(try!(read_byte(&mut self.reader))),
+    }
+  }
+
+  /// Get the next byte that isn't whitespace, without consuming it.
+  fn peek(&mut self) -> Result<u8, err::DecodeError> {
+    loop {
+      let byte = try!(self.next_byte());
+      match byte {
+        b' ' | b'\t' | b'\n' | b'\r' => continue,
+        byte => {
+          self.peeked = Some(byte);
+          return Ok(byte);
+        },
+      }
+    }
+  }
+
+  fn expect(&mut self, expected: u8) -> Result<(), err::DecodeError> {
+    try!(self.peek());
+    if try!(self.next_byte()) != expected {
+      return Err(err::DecodeError::InvalidJson);
+    }
+    Ok(())
+  }
+
+  /// Read the separator before a member or item, when it isn't the first.
+  fn separator(&mut self) -> Result<(), err::DecodeError> {
+    match self.started.last_mut() {
+      Some(started) if *started => (),
+      Some(started) => {
+        *started = true;
+        return Ok(());
+      },
+      None => return Err(err::DecodeError::InvalidJson),
+    }
+    self.expect(b',')
+  }
+
+  /// Read the start of an object.
+  pub fn start_object(&mut self) -> Result<(), err::DecodeError> {
+    try!(self.expect(b'{'));
+    self.started.push(false);
+    Ok(())
+  }
+
+  /// Read the name of the next member of an object, or the end of the
+  /// object, which returns None.
+  pub fn next_key(&mut self) -> Result<Option<String>, err::DecodeError> {
+    if try!(self.peek()) == b'}' {
+      try!(self.next_byte());
+      self.started.pop();
+      return Ok(None);
+    }
+    try!(self.separator());
+    let key = try!(self.read_string());
+    try!(self.expect(b':'));
+    Ok(Some(key))
+  }
+
+  /// Read the start of an array.
+  pub fn start_array(&mut self) -> Result<(), err::DecodeError> {
+    try!(self.expect(b'['));
+    self.started.push(false);
+    Ok(())
+  }
+
+  /// Prepare to read the next item of an array. Returns false at the end of
+  /// the array.
+  pub fn next_item(&mut self) -> Result<bool, err::DecodeError> {
+    if try!(self.peek()) == b']' {
+      try!(self.next_byte());
+      self.started.pop();
+      return Ok(false);
+    }
+    try!(self.separator());
+    Ok(true)
+  }
+
+  /// Read a string.
+  pub fn read_string(&mut self) -> Result<String, err::DecodeError> {
+    try!(self.expect(b'"'));
+    let mut bytes = Vec::new();
+    loop {
+      match try!(self.next_byte()) {
+        b'"' => break,
+        b'\\' => {
+          let c = match try!(self.next_byte()) {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => try!(self.read_escape()),
+            _ => return Err(err::DecodeError::InvalidJson),
+          };
+          let mut buf = [0; 4];
+          bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        },
+        byte => bytes.push(byte),
+      }
+    }
+    String::from_utf8(bytes).map_err(|_| err::DecodeError::InvalidJson)
+  }
+
+  /// Read the hex digits of a `\u` escape, and a second escape for a
+  /// surrogate pair.
+  fn read_escape(&mut self) -> Result<char, err::DecodeError> {
+    let first = try!(self.read_hex4());
+    let code = if first >= 0xd800 && first < 0xdc00 {
+      if try!(self.next_byte()) != b'\\' || try!(self.next_byte()) != b'u' {
+        return Err(err::DecodeError::InvalidJson);
+      }
+      let second = try!(self.read_hex4());
+      0x10000 + ((first - 0xd800) << 10) + second.wrapping_sub(0xdc00)
+    } else {
+      first
+    };
+    ::std::char::from_u32(code).ok_or(err::DecodeError::InvalidJson)
+  }
+
+  fn read_hex4(&mut self) -> Result<u32, err::DecodeError> {
+    let mut value = 0;
+    for _ in 0..4 {
+      let digit = try!((try!(self.next_byte()) as char).to_digit(16).ok_or(err::DecodeError::InvalidJson));
+      value = value << 4 | digit;
+    }
+    Ok(value)
+  }
+
+  /// Read the text of a number.
+  pub fn read_number(&mut self) -> Result<String, err::DecodeError> {
+    try!(self.peek());
+    let mut text = String::new();
+    loop {
+      // A number can end the input, since it has no terminator.
+      let byte = match self.peeked.take() {
+        Some(byte) => byte,
+        None => match self.reader.next() {
+          Some(byte) => try!(byte),
+          None => break,
+        },
+      };
+      match byte {
+        b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E' => text.push(byte as char),
+        byte => {
+          self.peeked = Some(byte);
+          break;
+        },
+      }
+    }
+    if text.is_empty() {
+      return Err(err::DecodeError::InvalidJson);
+    }
+    Ok(text)
+  }
+
+  /// Read a literal, like `true` or `null`.
+  fn read_literal(&mut self, literal: &[u8]) -> Result<(), err::DecodeError> {
+    try!(self.peek());
+    for &expected in literal {
+      if try!(self.next_byte()) != expected {
+        return Err(err::DecodeError::InvalidJson);
+      }
+    }
+    Ok(())
+  }
+
+  /// Read `true` or `false`.
+  pub fn read_bool(&mut self) -> Result<bool, err::DecodeError> {
+    let value = try!(self.peek()) == b't';
+    try!(self.read_literal(if value { b"true" } else { b"false" }));
+    Ok(value)
+  }
+
+  /// Read `null`.
+  pub fn read_null(&mut self) -> Result<(), err::DecodeError> {
+    self.read_literal(b"null")
+  }
+
+  /// Skip a value of any type, like an unknown extension.
+  ///
+  /// The objects and arrays that are open inside the value are tracked,
+  /// rather than skipped recursively, so deeply nested values can't overflow
+  /// the stack.
+  pub fn skip_value(&mut self) -> Result<(), err::DecodeError> {
+    // Whether each open object or array is an object.
+    let mut objects: Vec<bool> = Vec::new();
+    loop {
+      match try!(self.peek()) {
+        b'{' => {
+          try!(self.start_object());
+          objects.push(true);
+        },
+        b'[' => {
+          try!(self.start_array());
+          objects.push(false);
+        },
+        b'"' => {
+          try!(self.read_string());
+        },
+        b't' | b'f' => {
+          try!(self.read_bool());
+        },
+        b'n' => try!(self.read_null()),
+        _ => {
+          try!(self.read_number());
+        },
+      }
+      // Move to the next member or item, closing any objects and arrays
+      // that have ended.
+      loop {
+        let more = match objects.last() {
+          None => return Ok(()),
+          Some(&true) => try!(self.next_key()).is_some(),
+          Some(&false) => try!(self.next_item()),
+        };
+        if more {
+          break;
+        }
+        objects.pop();
+      }
+    }
+  }
+}
+
+#[test]
+fn json_reader() {
+  let json = b" { \"a\" : [1, -2.5e3, \"x\\u00e9\\n\"], \"b\": {\"c\": null}, \"d\": true } ";
+  let mut reader = JsonReader::new(json.iter().map(|&b| Ok(b)));
+  reader.start_object().unwrap();
+  assert_eq!(reader.next_key().unwrap().unwrap(), "a");
+  reader.start_array().unwrap();
+  assert!(reader.next_item().unwrap());
+  assert_eq!(reader.read_number().unwrap(), "1");
+  assert!(reader.next_item().unwrap());
+  assert_eq!(reader.read_number().unwrap(), "-2.5e3");
+  assert!(reader.next_item().unwrap());
+  assert_eq!(reader.read_string().unwrap(), "x\u{e9}\n");
+  assert!(!reader.next_item().unwrap());
+  assert_eq!(reader.next_key().unwrap().unwrap(), "b");
+  reader.skip_value().unwrap();
+  assert_eq!(reader.next_key().unwrap().unwrap(), "d");
+  assert_eq!(reader.read_bool().unwrap(), true);
+  assert_eq!(reader.next_key().unwrap(), None);
+}
+
+#[test]
+fn json_skip_nested() {
+  let json = b"{\"a\": [[1, {\"b\": [\"]\"]}], []], \"c\": 2}";
+  let mut reader = JsonReader::new(json.iter().map(|&b| Ok(b)));
+  reader.start_object().unwrap();
+  assert_eq!(reader.next_key().unwrap().unwrap(), "a");
+  reader.skip_value().unwrap();
+  assert_eq!(reader.next_key().unwrap().unwrap(), "c");
+  assert_eq!(reader.read_number().unwrap(), "2");
+  assert_eq!(reader.next_key().unwrap(), None);
+
+  // Deeply nested arrays don't overflow the stack.
+  let json: Vec<u8> = ::std::iter::repeat(b'[').take(1000000).collect();
+  let mut reader = JsonReader::new(json.into_iter().map(Ok));
+  assert!(reader.skip_value().is_err());
+}
@@ -0,0 +1,13 @@
+//! Tools for encoding and decoding the JSON Encoding Rules (JER), from X.697.
+//!
+//! A SEQUENCE or SET is a JSON object with a member for each component that
+//! is present, a CHOICE is an object with a single member for the chosen
+//! alternative, and an ENUMERATED is its identifier as a string. These names
+//! come from `Asn1Info::asn1_fields`. An OCTET STRING is a string of hex
+//! digits, and a BIT STRING is an object with the hex digits as `value` and
+//! the number of bits as `length`, unless its size is fixed.
+//!
+//! The `JerSerialize` and `JerDeserialize` traits are used for JER.
+
+pub mod json;
+pub mod serial;
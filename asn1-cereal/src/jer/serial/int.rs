@@ -0,0 +1,76 @@
+//! Implementation of the JER traits for Rust integers and `Integer`.
+//!
+//! An INTEGER is encoded as a JSON number, without a fraction or exponent.
+
+use std::convert::TryFrom;
+use std::io;
+
+use ::{JerSerialize, JerDeserialize};
+use constraint::Constraints;
+use err;
+use jer::json::JsonReader;
+use ber::serial::int::Integer;
+
+/// Generate the JER implementation for an int type.
+macro_rules! jer_cereal_int {
+  ($rs_type:ty) => (
+    impl JerSerialize for $rs_type {
+      fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        try!(write!(writer, "{}", self));
+        Ok(())
+      }
+    }
+
+    impl JerDeserialize for $rs_type {
+      fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+          (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+        let text = try!(reader.read_number());
+        let value: i128 = try!(text.parse().map_err(|_| err::DecodeError::InvalidInteger));
+        <$rs_type>::try_from(value).map_err(|_| err::DecodeError::IntegerOverflow)
+      }
+    }
+  );
+}
+
+jer_cereal_int!(i8);
+jer_cereal_int!(i16);
+jer_cereal_int!(i32);
+jer_cereal_int!(i64);
+jer_cereal_int!(i128);
+jer_cereal_int!(isize);
+
+jer_cereal_int!(u8);
+jer_cereal_int!(u16);
+jer_cereal_int!(u32);
+jer_cereal_int!(u64);
+jer_cereal_int!(u128);
+jer_cereal_int!(usize);
+
+impl JerSerialize for Integer {
+  fn jer_encode<W: io::Write>(&self, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    let value = try!(i128::try_from(self.clone())
+      .map_err(|_| err::EncodeError::Custom("INTEGER is too large to encode with JER")));
+    value.jer_encode(constraints, writer)
+  }
+}
+
+impl JerDeserialize for Integer {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (constraints: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    let value: i128 = try!(JerDeserialize::jer_decode(constraints, reader));
+    Ok(Integer::from(value))
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn jer_integers() {
+  let mut buf: Vec<u8> = Vec::new();
+  (-1234i32).jer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"-1234"[..]);
+  assert_eq!(i32::jer_deserialize(buf.bytes().by_ref()).unwrap(), -1234);
+  assert!(u8::jer_deserialize(b"256".bytes().by_ref()).is_err());
+  assert!(i32::jer_deserialize(b"1.5".bytes().by_ref()).is_err());
+}
@@ -0,0 +1,7 @@
+//! Implementations of the JER traits for Rust and asn1-cereal types.
+
+pub mod traits;
+pub mod int;
+pub mod prim;
+pub mod str;
+pub mod seq_of;
@@ -0,0 +1,183 @@
+//! Implementation of the JER traits for BOOLEAN, NULL, OCTET STRING,
+//! BIT STRING and OBJECT IDENTIFIER.
+//!
+//! A BOOLEAN is `true` or `false`, and a NULL is `null`. An OCTET STRING is
+//! a string of hex digits, and a BIT STRING is an object with the hex digits
+//! of its bits as `value` and the number of bits as `length`, or just the
+//! hex digits when its size is fixed. An OBJECT IDENTIFIER is a string of
+//! its dotted arcs.
+
+use std::io;
+
+use ::{JerSerialize, JerDeserialize};
+use constraint::Constraints;
+use err;
+use jer::json::{self, JsonReader};
+use oer::serial::num::fixed_size;
+use ber::serial::prim::{OctetString, BitString, ObjectIdentifier, RelativeOid};
+
+/// Write bytes as a string of uppercase hex digits.
+fn write_hex<W: io::Write>(writer: &mut W, bytes: &[u8]) -> Result<(), err::EncodeError> {
+  try!(writer.write_all(b"\""));
+  for byte in bytes {
+    try!(write!(writer, "{:02X}", byte));
+  }
+  try!(writer.write_all(b"\""));
+  Ok(())
+}
+
+/// Read a string of hex digits.
+fn read_hex<I: Iterator<Item=io::Result<u8>>>(reader: &mut JsonReader<I>) -> Result<Vec<u8>, err::DecodeError> {
+  let text = try!(reader.read_string());
+  let digits: Vec<u32> = try!(text.chars().map(|c| c.to_digit(16)).collect::<Option<_>>()
+    .ok_or(err::DecodeError::InvalidJson));
+  if digits.len() % 2 != 0 {
+    return Err(err::DecodeError::InvalidJson);
+  }
+  Ok(digits.chunks(2).map(|d| (d[0] << 4 | d[1]) as u8).collect())
+}
+
+impl JerSerialize for bool {
+  fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(writer.write_all(if *self { b"true" } else { b"false" }));
+    Ok(())
+  }
+}
+
+impl JerDeserialize for bool {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    reader.read_bool().map_err(|_| err::DecodeError::InvalidBool)
+  }
+}
+
+impl JerSerialize for () {
+  fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(writer.write_all(b"null"));
+    Ok(())
+  }
+}
+
+impl JerDeserialize for () {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    reader.read_null().map_err(|_| err::DecodeError::InvalidNull)
+  }
+}
+
+impl JerSerialize for OctetString {
+  fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    write_hex(writer, &self.0)
+  }
+}
+
+impl JerDeserialize for OctetString {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    Ok(OctetString(try!(read_hex(reader))))
+  }
+}
+
+impl JerSerialize for BitString {
+  fn jer_encode<W: io::Write>(&self, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    // A fixed size BIT STRING doesn't need its length.
+    if fixed_size(constraints.size).is_some() {
+      return write_hex(writer, self.as_bytes());
+    }
+    let mut first = true;
+    try!(writer.write_all(b"{"));
+    try!(json::write_key(writer, "value", &mut first));
+    try!(write_hex(writer, self.as_bytes()));
+    try!(json::write_key(writer, "length", &mut first));
+    try!(write!(writer, "{}}}", self.len()));
+    Ok(())
+  }
+}
+
+impl JerDeserialize for BitString {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (constraints: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    let (bytes, len) = match fixed_size(constraints.size) {
+      Some(len) => (try!(read_hex(reader)), len),
+      None => {
+        let mut bytes = None;
+        let mut len = None;
+        try!(reader.start_object());
+        while let Some(key) = try!(reader.next_key()) {
+          match key.as_str() {
+            "value" if bytes.is_none() => bytes = Some(try!(read_hex(reader))),
+            "length" if len.is_none() => len = Some(try!(try!(reader.read_number()).parse()
+              .map_err(|_| err::DecodeError::InvalidBitString))),
+            _ => return Err(err::DecodeError::InvalidJson),
+          }
+        }
+        match (bytes, len) {
+          (Some(bytes), Some(len)) => (bytes, len),
+          _ => return Err(err::DecodeError::InvalidJson),
+        }
+      },
+    };
+    // The length has to use all but the last 0-7 bits of the hex digits.
+    let bits = bytes.len() as u64 * 8;
+    if len > bits || bits - len >= 8 {
+      return Err(err::DecodeError::InvalidBitString);
+    }
+    BitString::from_bytes(bytes, (bits - len) as u8)
+  }
+}
+
+impl JerSerialize for ObjectIdentifier {
+  fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(json::write_string(writer, &self.to_string()));
+    Ok(())
+  }
+}
+
+impl JerDeserialize for ObjectIdentifier {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    try!(reader.read_string()).parse().map_err(|_| err::DecodeError::InvalidOid)
+  }
+}
+
+impl JerSerialize for RelativeOid {
+  fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(json::write_string(writer, &self.to_string()));
+    Ok(())
+  }
+}
+
+impl JerDeserialize for RelativeOid {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    try!(reader.read_string()).parse().map_err(|_| err::DecodeError::InvalidOid)
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn jer_primitives() {
+  let mut buf: Vec<u8> = Vec::new();
+  OctetString(b"\x01\xab".to_vec()).jer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"\"01AB\""[..]);
+  assert_eq!(OctetString::jer_deserialize(buf.bytes().by_ref()).unwrap().0, b"\x01\xab");
+
+  let mut bits = BitString::from(vec![0xa0]);
+  bits.resize(3);
+  buf.clear();
+  bits.jer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"{\"value\":\"A0\",\"length\":3}"[..]);
+  assert_eq!(BitString::jer_deserialize(buf.bytes().by_ref()).unwrap(), bits);
+  assert!(BitString::jer_deserialize(b"{\"value\":\"A0\",\"length\":9}".bytes().by_ref()).is_err());
+
+  let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+  buf.clear();
+  oid.jer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"\"1.2.840.113549\""[..]);
+  assert_eq!(ObjectIdentifier::jer_deserialize(buf.bytes().by_ref()).unwrap(), oid);
+
+  assert_eq!(bool::jer_deserialize(b" false".bytes().by_ref()).unwrap(), false);
+  assert_eq!(<()>::jer_deserialize(b"null".bytes().by_ref()).unwrap(), ());
+}
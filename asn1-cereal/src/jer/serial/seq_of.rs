@@ -0,0 +1,85 @@
+//! Implementation of the JER traits for SEQUENCE OF and SET OF.
+//!
+//! The elements are encoded as a JSON array.
+
+/// Implement JerSerialize and JerDeserialize for a SEQUENCE/SET OF type.
+///
+/// Requires that a reference to the type implement IntoIterator, and that
+/// the type implement FromIterator.
+macro_rules! jer_sequence_of {
+  (impl: $rs_type:ty) => (
+    fn jer_encode<W: ::std::io::Write>(&self, _: $crate::constraint::Constraints, writer: &mut W)
+        -> Result<(), $crate::err::EncodeError> {
+      $crate::jer::serial::seq_of::encode_seq_of(self.into_iter(), writer)
+    }
+  );
+  (impl_de: $rs_type:ty) => (
+    fn jer_decode<I: Iterator<Item=::std::io::Result<u8>>>
+        (_: $crate::constraint::Constraints, reader: &mut $crate::jer::json::JsonReader<I>)
+        -> Result<Self, $crate::err::DecodeError> {
+      let items: Vec<_> = try!($crate::jer::serial::seq_of::decode_seq_of(reader));
+      Ok(items.into_iter().collect())
+    }
+  );
+  ($rs_type:ty => $gen:ident) => (
+    jer_sequence_of!($rs_type => $gen, );
+  );
+  ($rs_type:ty => $gen:ident, $($where_attr:tt)*) => (
+    impl<$gen: $crate::JerSerialize> $crate::JerSerialize for $rs_type where $($where_attr)* {
+      jer_sequence_of!{impl: $rs_type}
+    }
+
+    impl<$gen: $crate::JerDeserialize> $crate::JerDeserialize for $rs_type where $($where_attr)* {
+      jer_sequence_of!{impl_de: $rs_type}
+    }
+  );
+}
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+use std::io;
+
+use ::{JerSerialize, JerDeserialize};
+use err;
+use jer::json::JsonReader;
+
+jer_sequence_of!(Vec<T> => T);
+jer_sequence_of!(HashSet<T> => T, T: Eq + Hash);
+jer_sequence_of!(BTreeSet<T> => T, T: Ord);
+
+/// Encode the elements of a SEQUENCE/SET OF as an array.
+pub fn encode_seq_of<'a, T, It, W>(items: It, writer: &mut W) -> Result<(), err::EncodeError>
+    where T: 'a + JerSerialize, It: Iterator<Item=&'a T>, W: io::Write {
+  try!(writer.write_all(b"["));
+  for (i, item) in items.enumerate() {
+    if i != 0 {
+      try!(writer.write_all(b","));
+    }
+    try!(item.jer_encode(T::asn1_constraints(), writer));
+  }
+  try!(writer.write_all(b"]"));
+  Ok(())
+}
+
+/// Decode the elements of a SEQUENCE/SET OF from an array.
+pub fn decode_seq_of<I, T>(reader: &mut JsonReader<I>) -> Result<Vec<T>, err::DecodeError>
+    where I: Iterator<Item=io::Result<u8>>, T: JerDeserialize {
+  let mut items = Vec::new();
+  try!(reader.start_array());
+  while try!(reader.next_item()) {
+    items.push(try!(T::jer_decode(T::asn1_constraints(), reader)));
+  }
+  Ok(items)
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn jer_seq_of() {
+  let mut buf: Vec<u8> = Vec::new();
+  vec![1u8, 2].jer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"[1,2]"[..]);
+  assert_eq!(Vec::<u8>::jer_deserialize(b"[ 1 , 2 ]".bytes().by_ref()).unwrap(), vec![1, 2]);
+  assert_eq!(Vec::<u8>::jer_deserialize(b"[]".bytes().by_ref()).unwrap(), vec![]);
+}
@@ -0,0 +1,97 @@
+//! Implementation of the JER traits for String, and the restricted character
+//! string types.
+//!
+//! A string is encoded as a JSON string. UTCTime and GeneralizedTime are
+//! encoded as a string of their DER content.
+
+use std::io;
+
+use ::{JerSerialize, JerDeserialize, BerSerialize, BerDeserialize};
+use constraint::Constraints;
+use tag;
+use err;
+use jer::json::{self, JsonReader};
+use ber::serial::str::{Utf8String, NumericString, PrintableString, TeletexString, Ia5String,
+                       VisibleString, UniversalString, BmpString};
+use ber::serial::time::{UtcTime, GeneralizedTime};
+use ber::enc::{BER, DER};
+
+impl JerSerialize for String {
+  fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(json::write_string(writer, self));
+    Ok(())
+  }
+}
+
+impl JerDeserialize for String {
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+    reader.read_string()
+  }
+}
+
+/// Generate the JER implementation for a restricted character string.
+macro_rules! jer_string {
+  ($name:ident) => (
+    impl JerSerialize for $name {
+      fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        try!(json::write_string(writer, self.as_str()));
+        Ok(())
+      }
+    }
+
+    impl JerDeserialize for $name {
+      fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+          (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+        $name::new(try!(reader.read_string()))
+      }
+    }
+  )
+}
+
+/// Generate the JER implementation for a time type, using its content in BER.
+macro_rules! jer_time {
+  ($name:ident) => (
+    impl JerSerialize for $name {
+      fn jer_encode<W: io::Write>(&self, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        let mut content = Vec::new();
+        try!(self.serialize_value(DER, &mut content));
+        let content = try!(String::from_utf8(content)
+          .map_err(|_| err::EncodeError::Custom("Time content wasn't valid UTF-8")));
+        try!(json::write_string(writer, &content));
+        Ok(())
+      }
+    }
+
+    impl JerDeserialize for $name {
+      fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+          (_: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError> {
+        let content = try!(reader.read_string()).into_bytes();
+        let len = tag::Len::Def(content.len() as tag::LenNum);
+        $name::deserialize_value(BER, &mut content.into_iter().map(Ok), len)
+      }
+    }
+  )
+}
+
+jer_string!(Utf8String);
+jer_string!(NumericString);
+jer_string!(PrintableString);
+jer_string!(TeletexString);
+jer_string!(Ia5String);
+jer_string!(VisibleString);
+jer_string!(UniversalString);
+jer_string!(BmpString);
+jer_time!(UtcTime);
+jer_time!(GeneralizedTime);
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn jer_escaped_string() {
+  let mut buf: Vec<u8> = Vec::new();
+  Utf8String::new("a\"b\\ é\n").unwrap().jer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], "\"a\\\"b\\\\ é\\n\"".as_bytes());
+  assert_eq!(Utf8String::jer_deserialize(buf.bytes().by_ref()).unwrap().as_str(), "a\"b\\ é\n");
+}
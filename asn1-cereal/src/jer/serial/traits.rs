@@ -0,0 +1,39 @@
+//! The base traits that are used for serializing and deserializing rust types with JER.
+//!
+//! `JerSerialize` implements serialization, and `JerDeserialize` implements deserialization.
+//! Both traits depend upon the `Asn1Info` trait, which provides the constraints of a type.
+//!
+//! Only `jer_encode` and `jer_decode` need to be implemented. These are given the
+//! constraints that apply to the value, since a BIT STRING with a fixed size is
+//! encoded differently.
+use std::io;
+
+use ::Asn1Info;
+use constraint::Constraints;
+use err;
+use jer::json::JsonReader;
+
+/// Provides the methods required to serialize this Rust type with JER.
+pub trait JerSerialize: Asn1Info {
+  /// Serialize a value with JER.
+  fn jer_serialize<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    debug!("Encoding the type {} with JER", Self::asn1_type());
+    self.jer_encode(Self::asn1_constraints(), writer)
+  }
+
+  /// Encode the JSON value of this value, using a set of constraints.
+  fn jer_encode<W: io::Write>(&self, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError>;
+}
+
+/// Provides the methods required to deserialize this Rust type with JER.
+pub trait JerDeserialize: Asn1Info + Sized {
+  /// Deserialize a value with JER.
+  fn jer_deserialize<I: Iterator<Item=io::Result<u8>>>(reader: &mut I) -> Result<Self, err::DecodeError> {
+    debug!("Decoding the type {} with JER", Self::asn1_type());
+    Self::jer_decode(Self::asn1_constraints(), &mut JsonReader::new(reader))
+  }
+
+  /// Decode the JSON value of this value, using a set of constraints.
+  fn jer_decode<I: Iterator<Item=io::Result<u8>>>
+      (constraints: Constraints, reader: &mut JsonReader<I>) -> Result<Self, err::DecodeError>;
+}
@@ -1,11 +1,19 @@
 //! # asn1-cereal
-//! A collection of encoders and decoders for BER, DER and ASN.1.
+//! A collection of encoders and decoders for BER, DER, CER, PER, OER, XER, JER and ASN.1.
 //!
 //! The grains of this library are a collection of traits and macros, that
 //! allow serialization and deserialization of rust types to and from ASN.1.
 //!
 //! The `Asn1Info`, `BerSerialize` and `BerDeserialize` traits are what
-//! most users will want to use.
+//! most users will want to use. The `PerSerialize` and `PerDeserialize`
+//! traits provide the Packed Encoding Rules (see [`per`](per/index.html)),
+//! using the constraints of each type (see [`constraint`](constraint/index.html)),
+//! and `OerSerialize` and `OerDeserialize` provide the Octet Encoding Rules
+//! (see [`oer`](oer/index.html)). `XerSerialize` and `XerDeserialize` provide
+//! the XML Encoding Rules (see [`xer`](xer/index.html)), and `JerSerialize`
+//! and `JerDeserialize` provide the JSON Encoding Rules (see [`jer`](jer/index.html)).
+//! The `Asn1Schema` trait describes the structure of a type without depending
+//! on an encoding (see [`schema`](schema/index.html)).
 //!
 //! # ASN.1 Elements
 //!
@@ -14,8 +22,16 @@
 //! - SEQUENCE/SET [`ber::serial::seq`](ber/serial/seq/index.html)
 //! - SEQUENCE/SET OF [`ber::serial::seq_of`](ber/serial/seq_of/index.html)
 //! - CHOICE/ANY [`ber::serial::choice`](ber/serial/choice/index.html)
+//! - ANY/open types [`ber::serial::raw`](ber/serial/raw/index.html)
+//! - ANY DEFINED BY [`ber::serial::defined_by`](ber/serial/defined_by/index.html)
 //! - A ::= B [`ber::serial::alias`](ber/serial/alias/index.html)
 //! - OCTET STRING [`ber::serial::prim`](ber/serial/prim/index.html)
+//! - Character strings [`ber::serial::str`](ber/serial/str/index.html)
+//! - NULL [`ber::serial::null`](ber/serial/null/index.html)
+//! - REAL [`ber::serial::real`](ber/serial/real/index.html)
+//! - BIT STRING [`ber::serial::bit_str`](ber/serial/bit_str/index.html)
+//! - OBJECT IDENTIFIER/RELATIVE-OID [`ber::serial::oid`](ber/serial/oid/index.html)
+//! - UTCTime/GeneralizedTime [`ber::serial::time`](ber/serial/time/index.html)
 //!
 //! # Example
 //!
@@ -47,15 +63,36 @@
 
 #[macro_use]
 extern crate log;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "time")]
+extern crate time;
+#[cfg(feature = "num-bigint")]
+extern crate num_bigint;
 
 pub mod tag;
 pub mod err;
 pub mod byte;
 #[macro_use]
 pub mod info;
+pub mod constraint;
+pub mod schema;
 pub mod ber;
+pub mod per;
+pub mod oer;
+pub mod xer;
+pub mod jer;
 
 pub use info::Asn1Info;
+pub use schema::Asn1Schema;
 pub use ber::serial::traits::{BerSerialize, BerDeserialize};
-pub use ber::enc::{BER, DER, BERAlt, BerEncRules};
-pub use ber::serial::prim::OctetString;
+pub use ber::enc::{BER, DER, CER, BERAlt, BerEncRules, EncodingRules, Lenient};
+pub use ber::serial::defined_by::{Registry, DefinedBy};
+pub use per::serial::traits::{PerSerialize, PerDeserialize};
+pub use per::enc::{APER, UPER, PerEncRules};
+pub use oer::serial::traits::{OerSerialize, OerDeserialize};
+pub use oer::enc::{OER, COER, OerEncRules};
+pub use xer::serial::traits::{XerSerialize, XerDeserialize};
+pub use jer::serial::traits::{JerSerialize, JerDeserialize};
+pub use ber::serial::prim::{OctetString, Integer, BitString, ObjectIdentifier, RelativeOid, RawElement,
+                            UtcTime, GeneralizedTime};
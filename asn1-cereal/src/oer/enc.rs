@@ -0,0 +1,38 @@
+//! Encoding rules to use for OER.
+//!
+//! This module provides encoding rules that can be used with
+//! the `OerSerialize` and `OerDeserialize` traits.
+//!
+//! ```
+//! use asn1_cereal::{OerSerialize, COER};
+//!
+//! let mut bytes: Vec<u8> = Vec::new();
+//! let _ = OerSerialize::oer_serialize_enc(&true, COER, &mut bytes);
+//! ```
+
+/// A trait to define encoding rules to use while encoding OER.
+pub trait OerEncRules: Copy {
+  /// Returns true when every value has exactly one encoding, as in Canonical
+  /// OER. Decoding will reject any other encoding.
+  fn canonical(self) -> bool;
+}
+
+#[derive(Copy, Clone)]
+/// Basic OER.
+pub struct OER;
+
+impl OerEncRules for OER {
+  fn canonical(self) -> bool {
+    false
+  }
+}
+
+#[derive(Copy, Clone)]
+/// Canonical OER.
+pub struct COER;
+
+impl OerEncRules for COER {
+  fn canonical(self) -> bool {
+    true
+  }
+}
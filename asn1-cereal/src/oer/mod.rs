@@ -0,0 +1,15 @@
+//! Tools for encoding and decoding the Octet Encoding Rules (OER).
+//!
+//! Like PER, OER uses the constraints of each type (see `Asn1Info::asn1_constraints`)
+//! to avoid encoding tags and lengths, but every field is a whole number of
+//! octets. Both basic OER (`OER`) and Canonical OER (`COER`) are supported,
+//! using the `OerSerialize` and `OerDeserialize` traits.
+//!
+//! The OPTIONAL and DEFAULT components of a SEQUENCE or SET are recorded in
+//! a preamble bitmap. A DEFAULT component is left out when it is equal to
+//! its default value, which is also the value used when it is absent. This is
+//! given by `#[asn1(default="<expr>")]`, or is `Default::default()` for
+//! `#[asn1(default)]`.
+
+pub mod enc;
+pub mod serial;
@@ -0,0 +1,80 @@
+//! Implementation of the OER traits for Rust integers and `Integer`.
+//!
+//! An INTEGER whose value constraint fits in 1, 2, 4 or 8 octets is encoded
+//! in exactly that many octets, which are unsigned when the lower bound isn't
+//! negative. Any other INTEGER is encoded as octets with a length.
+
+use std::convert::TryFrom;
+use std::io;
+
+use ::{OerSerialize, OerDeserialize};
+use constraint::Constraints;
+use err;
+use oer::enc::OerEncRules;
+use oer::serial::num;
+use ber::serial::int::Integer;
+
+/// Generate the OER implementation for an int type.
+macro_rules! oer_cereal_int {
+  ($rs_type:ty) => (
+    impl OerSerialize for $rs_type {
+      fn oer_encode<E: OerEncRules, W: io::Write>
+          (&self, _: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        let value = try!(i128::try_from(*self)
+          .map_err(|_| err::EncodeError::Custom("INTEGER is too large to encode with OER")));
+        num::write_integer(writer, value, constraints.value)
+      }
+    }
+
+    impl OerDeserialize for $rs_type {
+      fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+        let value = try!(num::read_integer(e, reader, constraints.value));
+        <$rs_type>::try_from(value).map_err(|_| err::DecodeError::IntegerOverflow)
+      }
+    }
+  );
+}
+
+oer_cereal_int!(i8);
+oer_cereal_int!(i16);
+oer_cereal_int!(i32);
+oer_cereal_int!(i64);
+oer_cereal_int!(i128);
+oer_cereal_int!(isize);
+
+oer_cereal_int!(u8);
+oer_cereal_int!(u16);
+oer_cereal_int!(u32);
+oer_cereal_int!(u64);
+oer_cereal_int!(u128);
+oer_cereal_int!(usize);
+
+impl OerSerialize for Integer {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, e: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    // Only an unconstrained INTEGER can be larger than an i128.
+    if constraints.value.is_none() {
+      return num::write_octets(writer, self.as_bytes());
+    }
+    let value = try!(i128::try_from(self.clone())
+      .map_err(|_| err::EncodeError::Custom("INTEGER is too large to encode with OER")));
+    value.oer_encode(e, constraints, writer)
+  }
+}
+
+impl OerDeserialize for Integer {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    if constraints.value.is_none() {
+      let bytes = try!(num::read_octets(e, reader));
+      let value = try!(Integer::from_bytes(&bytes));
+      if e.canonical() && value.as_bytes().len() != bytes.len() {
+        return Err(err::DecodeError::NonMinimalInteger);
+      }
+      return Ok(value);
+    }
+    let value: i128 = try!(OerDeserialize::oer_decode(e, constraints, reader));
+    Ok(Integer::from(value))
+  }
+}
@@ -0,0 +1,8 @@
+//! Implementations of the OER traits for Rust and asn1-cereal types.
+
+pub mod traits;
+pub mod num;
+pub mod int;
+pub mod prim;
+pub mod str;
+pub mod seq_of;
@@ -0,0 +1,393 @@
+//! Encoding of the lengths, numbers and preambles that OER is built from.
+//!
+//! These follow X.696, and are used to implement `OerSerialize` and
+//! `OerDeserialize` for each type. With Canonical OER, decoding rejects any
+//! length or number that isn't in its shortest form.
+
+use std::io;
+
+use constraint::Range;
+use err;
+use tag;
+use byte::{read_byte, write_byte};
+use oer::enc::OerEncRules;
+use per::serial::num::{uint_octets, int_octets};
+
+pub use per::serial::num::CONSTRAINT_ERROR;
+
+/// Write a length determinant.
+pub fn write_length<W: io::Write>(writer: &mut W, len: u64) -> Result<(), err::EncodeError> {
+  if len < 128 {
+    try!(write_byte(writer, len as u8));
+  } else {
+    // The long form gives the number of octets holding the length.
+    let bytes = uint_octets(len as u128);
+    try!(write_byte(writer, 0x80 | bytes.len() as u8));
+    try!(writer.write_all(&bytes));
+  }
+  Ok(())
+}
+
+/// Read a length determinant.
+pub fn read_length<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I) -> Result<u64, err::DecodeError> {
+  let first = try!(read_byte(reader));
+  if first & 0x80 == 0 {
+    return Ok(first as u64);
+  }
+  let count = first & 0x7f;
+  if count == 0 || count > 8 {
+    return Err(err::DecodeError::Custom("Invalid OER length determinant"));
+  }
+  let bytes = try!(read_bytes(reader, count as u64));
+  let len = bytes.iter().fold(0u64, |v, &b| v << 8 | b as u64);
+  if e.canonical() && (len < 128 || bytes[0] == 0) {
+    warn!("Encountered a length determinant that isn't minimal");
+    return Err(err::DecodeError::NonMinimalLength);
+  }
+  Ok(len)
+}
+
+/// Read a number of octets.
+pub fn read_bytes<I: Iterator<Item=io::Result<u8>>>(reader: &mut I, len: u64) -> io::Result<Vec<u8>> {
+  // Don't trust the length for the initial allocation.
+  let mut bytes = Vec::with_capacity(::std::cmp::min(len, 4096) as usize);
+  for _ in 0..len {
+    bytes.push(try!(read_byte(reader)));
+  }
+  Ok(bytes)
+}
+
+/// Write octets preceded by their length.
+pub fn write_octets<W: io::Write>(writer: &mut W, bytes: &[u8]) -> Result<(), err::EncodeError> {
+  try!(write_length(writer, bytes.len() as u64));
+  try!(writer.write_all(bytes));
+  Ok(())
+}
+
+/// Read octets preceded by their length.
+pub fn read_octets<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I) -> Result<Vec<u8>, err::DecodeError> {
+  let len = try!(read_length(e, reader));
+  Ok(try!(read_bytes(reader, len)))
+}
+
+/// Get the number of octets, and whether they're signed, for an INTEGER
+/// whose range fits in a fixed number of octets.
+fn fixed_octets(range: Option<Range>) -> Option<(usize, bool)> {
+  let (lower, upper) = match range {
+    // An extensible range is treated as unconstrained.
+    Some(Range { lower: Some(lower), upper: Some(upper), extensible: false }) => (lower, upper),
+    _ => return None,
+  };
+  for &octets in &[1usize, 2, 4, 8] {
+    let bits = octets as u32 * 8;
+    if lower >= 0 && upper < 1i128 << bits {
+      return Some((octets, false));
+    }
+    if lower >= -(1i128 << (bits - 1)) && upper < 1i128 << (bits - 1) {
+      return Some((octets, true));
+    }
+  }
+  None
+}
+
+/// Returns true when a range has a non-negative lower bound, so values are
+/// encoded as unsigned numbers.
+fn unsigned(range: Option<Range>) -> bool {
+  match range {
+    Some(Range { lower: Some(lower), extensible: false, .. }) => lower >= 0,
+    _ => false,
+  }
+}
+
+/// Write an INTEGER, using a value constraint.
+pub fn write_integer<W: io::Write>(writer: &mut W, value: i128, range: Option<Range>)
+    -> Result<(), err::EncodeError> {
+  if let Some(range) = range {
+    if !range.extensible && !range.contains(value) {
+      return Err(CONSTRAINT_ERROR);
+    }
+  }
+  match fixed_octets(range) {
+    Some((octets, _)) => {
+      try!(writer.write_all(&value.to_be_bytes()[16 - octets..]));
+      Ok(())
+    },
+    None if unsigned(range) => write_octets(writer, &uint_octets(value as u128)),
+    None => write_octets(writer, &int_octets(value)),
+  }
+}
+
+/// Read an INTEGER, using a value constraint.
+pub fn read_integer<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I, range: Option<Range>) -> Result<i128, err::DecodeError> {
+  let (bytes, signed) = match fixed_octets(range) {
+    Some((octets, signed)) => (try!(read_bytes(reader, octets as u64)), signed),
+    None => {
+      let bytes = try!(read_octets(e, reader));
+      let signed = !unsigned(range);
+      if bytes.is_empty() || bytes.len() > 16 {
+        return Err(err::DecodeError::InvalidInteger);
+      }
+      let minimal = if signed {
+        bytes.len() == int_octets(sign_extend(&bytes)).len()
+      } else {
+        bytes.len() == 1 || bytes[0] != 0
+      };
+      if e.canonical() && !minimal {
+        warn!("Encountered an INTEGER that isn't minimal");
+        return Err(err::DecodeError::NonMinimalInteger);
+      }
+      (bytes, signed)
+    },
+  };
+  let value = if signed {
+    sign_extend(&bytes)
+  } else if bytes.len() == 16 && bytes[0] & 0x80 != 0 {
+    return Err(err::DecodeError::IntegerOverflow);
+  } else {
+    bytes.iter().fold(0i128, |v, &b| v << 8 | b as i128)
+  };
+  if let Some(range) = range {
+    if !range.extensible && !range.contains(value) {
+      warn!("Encountered an INTEGER outside of its value constraint");
+      return Err(err::DecodeError::ConstraintViolation);
+    }
+  }
+  Ok(value)
+}
+
+/// Decode big-endian two's complement octets.
+fn sign_extend(bytes: &[u8]) -> i128 {
+  let init = if bytes[0] & 0x80 != 0 { -1i128 } else { 0 };
+  bytes.iter().fold(init, |v, &b| v << 8 | b as i128)
+}
+
+/// Get the size of a size constraint when it's fixed, so the size isn't encoded.
+pub fn fixed_size(size: Option<Range>) -> Option<u64> {
+  match size {
+    Some(Range { lower: Some(lower), upper: Some(upper), extensible: false }) if lower == upper =>
+      Some(lower as u64),
+    _ => None,
+  }
+}
+
+/// Returns true when a size satisfies a size constraint.
+fn size_permitted(size: Option<Range>, len: u64) -> bool {
+  size.map_or(true, |s| s.extensible || s.contains(len as i128))
+}
+
+/// Write the content of a string of `len` units, with a length unless the
+/// size constraint is fixed.
+pub fn write_sized<W: io::Write>(writer: &mut W, content: &[u8], len: u64, size: Option<Range>)
+    -> Result<(), err::EncodeError> {
+  if !size_permitted(size, len) {
+    return Err(CONSTRAINT_ERROR);
+  }
+  if fixed_size(size).is_none() {
+    try!(write_length(writer, content.len() as u64));
+  }
+  try!(writer.write_all(content));
+  Ok(())
+}
+
+/// Read the content of a string of units that are `unit` octets each, with
+/// a length unless the size constraint is fixed.
+pub fn read_sized<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I, unit: u64, size: Option<Range>) -> Result<Vec<u8>, err::DecodeError> {
+  let content = match fixed_size(size) {
+    Some(len) => try!(read_bytes(reader, len * unit)),
+    None => try!(read_octets(e, reader)),
+  };
+  if content.len() as u64 % unit != 0 {
+    return Err(err::DecodeError::InvalidCharset);
+  }
+  if !size_permitted(size, content.len() as u64 / unit) {
+    warn!("Encountered a string outside of its size constraint");
+    return Err(err::DecodeError::ConstraintViolation);
+  }
+  Ok(content)
+}
+
+/// Write a quantity, which is the number of elements of a SEQUENCE OF.
+pub fn write_quantity<W: io::Write>(writer: &mut W, count: u64) -> Result<(), err::EncodeError> {
+  write_octets(writer, &uint_octets(count as u128))
+}
+
+/// Read a quantity, which is the number of elements of a SEQUENCE OF.
+pub fn read_quantity<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I) -> Result<u64, err::DecodeError> {
+  let bytes = try!(read_octets(e, reader));
+  if bytes.is_empty() || bytes.len() > 8 {
+    return Err(err::DecodeError::InvalidInteger);
+  }
+  if e.canonical() && bytes.len() > 1 && bytes[0] == 0 {
+    return Err(err::DecodeError::NonMinimalInteger);
+  }
+  Ok(bytes.iter().fold(0u64, |v, &b| v << 8 | b as u64))
+}
+
+/// Write the value of an ENUMERATED.
+pub fn write_enumerated<W: io::Write>(writer: &mut W, value: i64) -> Result<(), err::EncodeError> {
+  if value >= 0 && value < 128 {
+    try!(write_byte(writer, value as u8));
+  } else {
+    // The long form gives the number of octets holding the value.
+    let bytes = int_octets(value as i128);
+    try!(write_byte(writer, 0x80 | bytes.len() as u8));
+    try!(writer.write_all(&bytes));
+  }
+  Ok(())
+}
+
+/// Read the value of an ENUMERATED.
+pub fn read_enumerated<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I) -> Result<i64, err::DecodeError> {
+  let first = try!(read_byte(reader));
+  if first & 0x80 == 0 {
+    return Ok(first as i64);
+  }
+  let count = first & 0x7f;
+  if count == 0 || count > 8 {
+    return Err(err::DecodeError::InvalidInteger);
+  }
+  let bytes = try!(read_bytes(reader, count as u64));
+  let value = sign_extend(&bytes) as i64;
+  if e.canonical() && ((value >= 0 && value < 128) || int_octets(value as i128).len() != bytes.len()) {
+    warn!("Encountered an ENUMERATED that isn't minimal");
+    return Err(err::DecodeError::NonMinimalInteger);
+  }
+  Ok(value)
+}
+
+/// Write the tag of a CHOICE alternative. The constructed flag isn't encoded.
+pub fn write_tag<W: io::Write>(writer: &mut W, tag: tag::Tag) -> Result<(), err::EncodeError> {
+  let class = u8::from(tag.class) << 6;
+  if tag.tagnum < 63 {
+    try!(write_byte(writer, class | tag.tagnum as u8));
+  } else {
+    try!(write_byte(writer, class | 0x3f));
+    let mut bytes = vec![tag.tagnum as u8 & 0x7f];
+    let mut tagnum = tag.tagnum >> 7;
+    while tagnum > 0 {
+      bytes.push(0x80 | (tagnum as u8 & 0x7f));
+      tagnum >>= 7;
+    }
+    bytes.reverse();
+    try!(writer.write_all(&bytes));
+  }
+  Ok(())
+}
+
+/// Read the tag of a CHOICE alternative, as its class and number.
+pub fn read_tag<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I) -> Result<(tag::Class, tag::TagNum), err::DecodeError> {
+  let first = try!(read_byte(reader));
+  let class = tag::Class::from(first >> 6);
+  if first & 0x3f != 0x3f {
+    return Ok((class, (first & 0x3f) as tag::TagNum));
+  }
+  let mut tagnum: tag::TagNum = 0;
+  let mut count = 0;
+  loop {
+    let byte = try!(read_byte(reader));
+    if count == 0 && byte == 0x80 {
+      return Err(err::DecodeError::NonMinimalTag);
+    }
+    count += 1;
+    if count > 9 {
+      return Err(err::DecodeError::IntegerOverflow);
+    }
+    tagnum = tagnum << 7 | (byte & 0x7f) as tag::TagNum;
+    if byte & 0x80 == 0 {
+      break;
+    }
+  }
+  if e.canonical() && tagnum < 63 {
+    return Err(err::DecodeError::NonMinimalTag);
+  }
+  Ok((class, tagnum))
+}
+
+/// Write the preamble of a SEQUENCE or SET, which holds a bit for each flag,
+/// padded to a whole number of octets.
+pub fn write_preamble<W: io::Write>(writer: &mut W, bits: &[bool]) -> Result<(), err::EncodeError> {
+  for chunk in bits.chunks(8) {
+    let byte = chunk.iter().enumerate().fold(0u8, |b, (i, &bit)| b | (bit as u8) << (7 - i));
+    try!(write_byte(writer, byte));
+  }
+  Ok(())
+}
+
+/// Read the preamble of a SEQUENCE or SET, holding `count` bits.
+pub fn read_preamble<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I, count: usize) -> Result<Vec<bool>, err::DecodeError> {
+  let mut bits = Vec::with_capacity(count);
+  while bits.len() < count {
+    let byte = try!(read_byte(reader));
+    for i in 0..8 {
+      let bit = byte & (0x80 >> i) != 0;
+      if bits.len() < count {
+        bits.push(bit);
+      } else if bit && e.canonical() {
+        return Err(err::DecodeError::Custom("Preamble padding bits must be zero"));
+      }
+    }
+  }
+  Ok(bits)
+}
+
+/// Skip the extension additions of a SEQUENCE or SET that aren't known to
+/// this version of the type. Their presence is a BIT STRING, and each
+/// addition is an open type.
+pub fn skip_extensions<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut I) -> Result<(), err::DecodeError> {
+  let presence = try!(read_octets(e, reader));
+  let (unused, bytes) = match presence.split_first() {
+    Some((&unused, bytes)) if unused < 8 => (unused, bytes),
+    _ => return Err(err::DecodeError::InvalidBitString),
+  };
+  let count = (bytes.len() * 8).saturating_sub(unused as usize);
+  for i in 0..count {
+    if bytes[i / 8] & (0x80 >> (i % 8)) != 0 {
+      debug!("Skipping an unknown extension addition");
+      try!(read_octets(e, reader));
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+use oer::enc::{OER, COER};
+
+#[test]
+fn oer_integer_ranges() {
+  let cases: Vec<(i128, Option<Range>, &[u8])> = vec![
+    (200, Some(Range::new(Some(0), Some(255))), b"\xc8"),
+    (-2, Some(Range::new(Some(-100), Some(100))), b"\xfe"),
+    (300, Some(Range::new(Some(0), Some(65535))), b"\x01\x2c"),
+    (70000, Some(Range::new(Some(-1), Some(70000))), b"\x00\x01\x11\x70"),
+    (300, Some(Range::new(Some(0), None)), b"\x02\x01\x2c"),
+    (-300, None, b"\x02\xfe\xd4"),
+    (5, Some(Range { extensible: true, .. Range::new(Some(0), Some(7)) }), b"\x01\x05"),
+  ];
+  for (value, range, bytes) in cases {
+    let mut buf: Vec<u8> = Vec::new();
+    write_integer(&mut buf, value, range).unwrap();
+    assert_eq!(&buf[..], bytes);
+    assert_eq!(read_integer(COER, &mut bytes.iter().map(|&b| Ok(b)), range).unwrap(), value);
+  }
+
+  // Canonical OER rejects padded integers and lengths.
+  let padded = b"\x02\x00\x05";
+  assert_eq!(read_integer(OER, &mut padded.iter().map(|&b| Ok(b)), None).unwrap(), 5);
+  assert!(read_integer(COER, &mut padded.iter().map(|&b| Ok(b)), None).is_err());
+  let long = b"\x81\x05";
+  assert_eq!(read_length(OER, &mut long.iter().map(|&b| Ok(b))).unwrap(), 5);
+  assert!(read_length(COER, &mut long.iter().map(|&b| Ok(b))).is_err());
+
+  let mut buf: Vec<u8> = Vec::new();
+  write_length(&mut buf, 300).unwrap();
+  assert_eq!(&buf[..], b"\x82\x01\x2c");
+}
@@ -0,0 +1,170 @@
+//! Implementation of the OER traits for BOOLEAN, NULL, OCTET STRING,
+//! BIT STRING and OBJECT IDENTIFIER.
+//!
+//! A BOOLEAN is a single octet and a NULL has no octets. The strings have
+//! a length unless their size constraint is fixed, and an OBJECT IDENTIFIER
+//! has the same content octets as BER.
+
+use std::io;
+
+use ::{OerSerialize, OerDeserialize};
+use constraint::Constraints;
+use err;
+use byte::{read_byte, write_byte};
+use oer::enc::OerEncRules;
+use oer::serial::num;
+use ber::serial::prim::{OctetString, BitString, ObjectIdentifier, RelativeOid};
+
+impl OerSerialize for bool {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(write_byte(writer, if *self { 0xff } else { 0x00 }));
+    Ok(())
+  }
+}
+
+impl OerDeserialize for bool {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, _: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    match try!(read_byte(reader)) {
+      0x00 => Ok(false),
+      0xff => Ok(true),
+      _ if e.canonical() => Err(err::DecodeError::InvalidBool),
+      _ => Ok(true),
+    }
+  }
+}
+
+impl OerSerialize for () {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, _: Constraints, _: &mut W) -> Result<(), err::EncodeError> {
+    Ok(())
+  }
+}
+
+impl OerDeserialize for () {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, _: Constraints, _: &mut I) -> Result<Self, err::DecodeError> {
+    Ok(())
+  }
+}
+
+impl OerSerialize for OctetString {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    num::write_sized(writer, &self.0, self.0.len() as u64, constraints.size)
+  }
+}
+
+impl OerDeserialize for OctetString {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    Ok(OctetString(try!(num::read_sized(e, reader, 1, constraints.size))))
+  }
+}
+
+impl OerSerialize for BitString {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    // A fixed size BIT STRING doesn't need the count of unused bits.
+    if num::fixed_size(constraints.size).is_some() {
+      return num::write_sized(writer, self.as_bytes(), self.len(), constraints.size);
+    }
+    let mut content = Vec::with_capacity(self.as_bytes().len() + 1);
+    content.push(self.unused_bits());
+    content.extend_from_slice(self.as_bytes());
+    num::write_sized(writer, &content, self.len(), constraints.size)
+  }
+}
+
+impl OerDeserialize for BitString {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    let (bytes, unused) = match num::fixed_size(constraints.size) {
+      Some(len) => {
+        let bytes = try!(num::read_bytes(reader, (len + 7) / 8));
+        (bytes, ((8 - len % 8) % 8) as u8)
+      },
+      None => {
+        let mut content = try!(num::read_octets(e, reader));
+        if content.is_empty() {
+          return Err(err::DecodeError::InvalidBitString);
+        }
+        let unused = content.remove(0);
+        (content, unused)
+      },
+    };
+    // Canonical OER requires the unused bits to be zero.
+    if e.canonical() && unused < 8 && bytes.last().map_or(false, |b| b & !(0xff << unused) != 0) {
+      return Err(err::DecodeError::InvalidBitString);
+    }
+    let bits = try!(BitString::from_bytes(bytes, unused));
+    if constraints.size.map_or(false, |s| !s.extensible && !s.contains(bits.len() as i128)) {
+      warn!("Encountered a BIT STRING outside of its size constraint");
+      return Err(err::DecodeError::ConstraintViolation);
+    }
+    Ok(bits)
+  }
+}
+
+impl OerSerialize for ObjectIdentifier {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    num::write_octets(writer, self.as_bytes())
+  }
+}
+
+impl OerDeserialize for ObjectIdentifier {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, _: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    ObjectIdentifier::from_bytes(try!(num::read_octets(e, reader)))
+  }
+}
+
+impl OerSerialize for RelativeOid {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    num::write_octets(writer, self.as_bytes())
+  }
+}
+
+impl OerDeserialize for RelativeOid {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, _: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    RelativeOid::from_bytes(try!(num::read_octets(e, reader)))
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use constraint::Range;
+#[cfg(test)]
+use oer::enc::{OER, COER};
+
+#[test]
+fn oer_strings() {
+  let mut buf: Vec<u8> = Vec::new();
+  let octets = OctetString(b"abc".to_vec());
+  octets.oer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x03abc");
+
+  // A fixed size doesn't need a length.
+  let c = Constraints::size(Range::single(3));
+  buf.clear();
+  octets.oer_encode(OER, c, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"abc");
+  assert_eq!(OctetString::oer_decode(OER, c, &mut buf.bytes()).unwrap().0, b"abc");
+
+  let mut bits = BitString::from(vec![0xa0]);
+  bits.resize(3);
+  buf.clear();
+  bits.oer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x02\x05\xa0");
+  assert_eq!(BitString::oer_deserialize_enc(COER, buf.bytes().by_ref()).unwrap(), bits);
+  assert!(BitString::oer_deserialize_enc(COER, b"\x02\x05\xa1".bytes().by_ref()).is_err());
+
+  // Canonical OER only accepts 0xff as TRUE.
+  assert_eq!(bool::oer_deserialize(b"\x01".bytes().by_ref()).unwrap(), true);
+  assert!(bool::oer_deserialize_enc(COER, b"\x01".bytes().by_ref()).is_err());
+}
@@ -0,0 +1,114 @@
+//! Implementation of the OER traits for SEQUENCE OF and SET OF.
+//!
+//! The number of elements is encoded first as a quantity, and then each
+//! element is encoded using the constraints of its type. With Canonical OER,
+//! the elements of a SET OF are sorted by their encodings.
+
+/// Implement OerSerialize and OerDeserialize for a SEQUENCE/SET OF type.
+///
+/// Requires that a reference to the type implement IntoIterator, and that
+/// the type implement FromIterator. `$sorted` is true for a SET OF.
+macro_rules! oer_sequence_of {
+  (impl: $rs_type:ty, $sorted:expr) => (
+    fn oer_encode<E: $crate::oer::enc::OerEncRules, W: ::std::io::Write>
+        (&self, e: E, constraints: $crate::constraint::Constraints, writer: &mut W)
+        -> Result<(), $crate::err::EncodeError> {
+      $crate::oer::serial::seq_of::encode_seq_of(e, self.into_iter(), constraints, writer, $sorted)
+    }
+  );
+  (impl_de: $rs_type:ty) => (
+    fn oer_decode<E: $crate::oer::enc::OerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+        (e: E, constraints: $crate::constraint::Constraints, reader: &mut I)
+        -> Result<Self, $crate::err::DecodeError> {
+      let items: Vec<_> = try!($crate::oer::serial::seq_of::decode_seq_of(e, constraints, reader));
+      Ok(items.into_iter().collect())
+    }
+  );
+  ($rs_type:ty => $gen:ident, $sorted:expr) => (
+    oer_sequence_of!($rs_type => $gen, $sorted, );
+  );
+  ($rs_type:ty => $gen:ident, $sorted:expr, $($where_attr:tt)*) => (
+    impl<$gen: $crate::OerSerialize> $crate::OerSerialize for $rs_type where $($where_attr)* {
+      oer_sequence_of!{impl: $rs_type, $sorted}
+    }
+
+    impl<$gen: $crate::OerDeserialize> $crate::OerDeserialize for $rs_type where $($where_attr)* {
+      oer_sequence_of!{impl_de: $rs_type}
+    }
+  );
+}
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+use std::io;
+
+use ::{OerSerialize, OerDeserialize};
+use constraint::Constraints;
+use err;
+use oer::enc::OerEncRules;
+use oer::serial::num;
+
+oer_sequence_of!(Vec<T> => T, false);
+oer_sequence_of!(HashSet<T> => T, true, T: Eq + Hash);
+oer_sequence_of!(BTreeSet<T> => T, true, T: Ord);
+
+/// Encode the elements of a SEQUENCE/SET OF. When `sorted` is true and the
+/// encoding rules are canonical, the elements are sorted by their encodings.
+pub fn encode_seq_of<'a, E, T, It, W>(e: E, items: It, constraints: Constraints, writer: &mut W, sorted: bool)
+    -> Result<(), err::EncodeError>
+    where E: OerEncRules, T: 'a + OerSerialize, It: Iterator<Item=&'a T>, W: io::Write {
+  let mut encoded = Vec::new();
+  for item in items {
+    let mut bytes = Vec::new();
+    try!(item.oer_encode(e, T::asn1_constraints(), &mut bytes));
+    encoded.push(bytes);
+  }
+  let len = encoded.len() as u64;
+  if constraints.size.map_or(false, |s| !s.extensible && !s.contains(len as i128)) {
+    return Err(num::CONSTRAINT_ERROR);
+  }
+  if sorted && e.canonical() {
+    encoded.sort();
+  }
+  try!(num::write_quantity(writer, len));
+  for bytes in encoded {
+    try!(writer.write_all(&bytes));
+  }
+  Ok(())
+}
+
+/// Decode the elements of a SEQUENCE/SET OF.
+pub fn decode_seq_of<E, I, T>(e: E, constraints: Constraints, reader: &mut I)
+    -> Result<Vec<T>, err::DecodeError>
+    where E: OerEncRules, I: Iterator<Item=io::Result<u8>>, T: OerDeserialize {
+  let len = try!(num::read_quantity(e, reader));
+  if constraints.size.map_or(false, |s| !s.extensible && !s.contains(len as i128)) {
+    warn!("Encountered a SEQUENCE OF outside of its size constraint");
+    return Err(err::DecodeError::ConstraintViolation);
+  }
+  // Don't trust the quantity for the initial allocation.
+  let mut items = Vec::with_capacity(::std::cmp::min(len, 1024) as usize);
+  for _ in 0..len {
+    items.push(try!(T::oer_decode(e, T::asn1_constraints(), reader)));
+  }
+  Ok(items)
+}
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use oer::enc::COER;
+
+#[test]
+fn oer_seq_of() {
+  let mut buf: Vec<u8> = Vec::new();
+  vec![true, false].oer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x01\x02\xff\x00");
+  assert_eq!(Vec::<bool>::oer_deserialize(buf.bytes().by_ref()).unwrap(), vec![true, false]);
+
+  // Canonical OER sorts a SET OF.
+  let set: HashSet<u8> = [3u8, 1, 2].iter().cloned().collect();
+  buf.clear();
+  set.oer_serialize_enc(COER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x01\x03\x01\x01\x01\x02\x01\x03");
+}
@@ -0,0 +1,111 @@
+//! Implementation of the OER traits for String, and the restricted character
+//! string types.
+//!
+//! A known-multiplier character string (like IA5String or BMPString) has the
+//! same content octets as BER, and uses its size constraint like an OCTET
+//! STRING. Other strings (UTF8String and TeletexString) always have a length.
+//!
+//! Like BER, a Rust `String` is treated as a PrintableString. UTCTime and
+//! GeneralizedTime are encoded as a VisibleString holding their DER content.
+
+use std::io;
+
+use ::{OerSerialize, OerDeserialize, BerSerialize, BerDeserialize};
+use constraint::Constraints;
+use tag;
+use err;
+use oer::enc::OerEncRules;
+use oer::serial::num;
+use ber::serial::str::{Utf8String, NumericString, PrintableString, TeletexString, Ia5String,
+                       VisibleString, UniversalString, BmpString, decode_string};
+use ber::serial::time::{UtcTime, GeneralizedTime};
+use ber::enc::{BER, DER};
+
+impl OerSerialize for String {
+  fn oer_encode<E: OerEncRules, W: io::Write>
+      (&self, _: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+    num::write_sized(writer, self.as_bytes(), self.len() as u64, constraints.size)
+  }
+}
+
+impl OerDeserialize for String {
+  fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+    decode_string(try!(num::read_sized(e, reader, 1, constraints.size)))
+  }
+}
+
+/// Generate the OER implementation for a known-multiplier character string,
+/// with characters of `$octets` octets.
+macro_rules! oer_known_multiplier_string {
+  ($name:ident, $octets:expr) => (
+    impl OerSerialize for $name {
+      fn oer_encode<E: OerEncRules, W: io::Write>
+          (&self, _: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        let content = try!(self.content());
+        num::write_sized(writer, &content, content.len() as u64 / $octets, constraints.size)
+      }
+    }
+
+    impl OerDeserialize for $name {
+      fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+        $name::from_content(try!(num::read_sized(e, reader, $octets, constraints.size)))
+      }
+    }
+  )
+}
+
+/// Generate the OER implementation for a character string that is encoded
+/// as octets.
+macro_rules! oer_octet_string {
+  ($name:ident) => (
+    impl OerSerialize for $name {
+      fn oer_encode<E: OerEncRules, W: io::Write>
+          (&self, _: E, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        num::write_octets(writer, &try!(self.content()))
+      }
+    }
+
+    impl OerDeserialize for $name {
+      fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, _: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+        $name::from_content(try!(num::read_octets(e, reader)))
+      }
+    }
+  )
+}
+
+/// Generate the OER implementation for a time type, using its content in BER.
+macro_rules! oer_time {
+  ($name:ident) => (
+    impl OerSerialize for $name {
+      fn oer_encode<E: OerEncRules, W: io::Write>
+          (&self, _: E, _: Constraints, writer: &mut W) -> Result<(), err::EncodeError> {
+        let mut content = Vec::new();
+        try!(self.serialize_value(DER, &mut content));
+        num::write_octets(writer, &content)
+      }
+    }
+
+    impl OerDeserialize for $name {
+      fn oer_decode<E: OerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, _: Constraints, reader: &mut I) -> Result<Self, err::DecodeError> {
+        let content = try!(num::read_octets(e, reader));
+        let len = tag::Len::Def(content.len() as tag::LenNum);
+        $name::deserialize_value(BER, &mut content.into_iter().map(Ok), len)
+      }
+    }
+  )
+}
+
+oer_known_multiplier_string!(NumericString, 1);
+oer_known_multiplier_string!(PrintableString, 1);
+oer_known_multiplier_string!(Ia5String, 1);
+oer_known_multiplier_string!(VisibleString, 1);
+oer_known_multiplier_string!(BmpString, 2);
+oer_known_multiplier_string!(UniversalString, 4);
+oer_octet_string!(Utf8String);
+oer_octet_string!(TeletexString);
+oer_time!(UtcTime);
+oer_time!(GeneralizedTime);
@@ -0,0 +1,52 @@
+//! The base traits that are used for serializing and deserializing rust types with OER.
+//!
+//! `OerSerialize` implements serialization, and `OerDeserialize` implements deserialization.
+//! Both traits depend upon the `Asn1Info` trait, which provides the constraints of a type.
+//!
+//! Only `oer_encode` and `oer_decode` need to be implemented. These are given the
+//! constraints that apply to the value, which are the constraints of its type, or
+//! the constraints of the field that contains it.
+use std::io;
+
+use ::Asn1Info;
+use constraint::Constraints;
+use err;
+use oer::enc;
+
+/// Provides the methods required to serialize this Rust type with OER.
+pub trait OerSerialize: Asn1Info {
+  /// Serialize a value with basic OER.
+  fn oer_serialize<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    self.oer_serialize_enc(enc::OER, writer)
+  }
+
+  /// Serialize a value with a specific variant of OER.
+  fn oer_serialize_enc<E: enc::OerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    debug!("Encoding the type {} with OER", Self::asn1_type());
+    self.oer_encode(e, Self::asn1_constraints(), writer)
+  }
+
+  /// Encode the octets of this value, using a set of constraints.
+  fn oer_encode<E: enc::OerEncRules, W: io::Write>
+      (&self, e: E, constraints: Constraints, writer: &mut W) -> Result<(), err::EncodeError>;
+}
+
+/// Provides the methods required to deserialize this Rust type with OER.
+pub trait OerDeserialize: Asn1Info + Sized {
+  /// Deserialize a value with basic OER.
+  fn oer_deserialize<I: Iterator<Item=io::Result<u8>>>(reader: &mut I) -> Result<Self, err::DecodeError> {
+    Self::oer_deserialize_enc(enc::OER, reader)
+  }
+
+  /// Deserialize a value with a specific variant of OER.
+  fn oer_deserialize_enc<E: enc::OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I) -> Result<Self, err::DecodeError> {
+    debug!("Decoding the type {} with OER", Self::asn1_type());
+    Self::oer_decode(e, Self::asn1_constraints(), reader)
+  }
+
+  /// Decode the octets of this value, using a set of constraints.
+  fn oer_decode<E: enc::OerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut I) -> Result<Self, err::DecodeError>;
+}
@@ -0,0 +1,168 @@
+//! Bit-level readers and writers used by PER.
+
+use std::io;
+
+use err;
+use byte::read_byte;
+
+/// A writer that packs bits into octets, with the most significant bit first.
+#[derive(Clone, Debug, Default)]
+pub struct BitWriter {
+  bytes: Vec<u8>,
+  /// The number of bits written.
+  pub count: u64,
+}
+
+impl BitWriter {
+  /// Create an empty BitWriter.
+  pub fn new() -> Self {
+    BitWriter::default()
+  }
+
+  /// Write a single bit.
+  pub fn write_bit(&mut self, bit: bool) {
+    if self.count % 8 == 0 {
+      self.bytes.push(0);
+    }
+    if bit {
+      let last = self.bytes.len() - 1;
+      self.bytes[last] |= 0x80 >> (self.count % 8);
+    }
+    self.count += 1;
+  }
+
+  /// Write the lowest `width` bits of a value.
+  pub fn write_bits(&mut self, value: u128, width: u32) {
+    for i in (0..width).rev() {
+      self.write_bit(value >> i & 1 == 1);
+    }
+  }
+
+  /// Write a series of octets, which may not start on an octet boundary.
+  pub fn write_bytes(&mut self, bytes: &[u8]) {
+    if self.is_aligned() {
+      self.bytes.extend_from_slice(bytes);
+      self.count += bytes.len() as u64 * 8;
+    } else {
+      for &b in bytes {
+        self.write_bits(b as u128, 8);
+      }
+    }
+  }
+
+  /// Returns true when the next bit starts an octet.
+  pub fn is_aligned(&self) -> bool {
+    self.count % 8 == 0
+  }
+
+  /// Write zero bits until the next octet boundary.
+  pub fn align(&mut self) {
+    self.count = (self.count + 7) / 8 * 8;
+  }
+
+  /// Get the bytes written, where the final octet is padded with zero bits.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.bytes
+  }
+
+  /// Convert this into a complete encoding, which is padded to an octet
+  /// boundary, and is at least one octet.
+  pub fn into_bytes(self) -> Vec<u8> {
+    let mut bytes = self.bytes;
+    if bytes.is_empty() {
+      bytes.push(0);
+    }
+    bytes
+  }
+}
+
+/// A reader that reads bits from an iterator of octets, with the most
+/// significant bit first.
+pub struct BitReader<I: Iterator<Item=io::Result<u8>>> {
+  reader: I,
+  current: u8,
+  /// The number of bits read.
+  pub count: u64,
+}
+
+impl<I: Iterator<Item=io::Result<u8>>> BitReader<I> {
+  /// Create a new BitReader from an Iterator.
+  pub fn new(reader: I) -> Self {
+    BitReader {
+      reader: reader,
+      current: 0,
+      count: 0,
+    }
+  }
+
+  /// Turn this BitReader back into the original reader used to create it.
+  /// Any unread bits of the current octet are discarded.
+  pub fn into_reader(self) -> I {
+    self.reader
+  }
+
+  /// Read a single bit.
+  pub fn read_bit(&mut self) -> Result<bool, err::DecodeError> {
+    if self.count % 8 == 0 {
+      self.current = try!(read_byte(&mut self.reader));
+    }
+    let bit = self.current & (0x80 >> (self.count % 8)) != 0;
+    self.count += 1;
+    Ok(bit)
+  }
+
+  /// Read a value with `width` bits.
+  pub fn read_bits(&mut self, width: u32) -> Result<u128, err::DecodeError> {
+    let mut value = 0u128;
+    for _ in 0..width {
+      value = value << 1 | try!(self.read_bit()) as u128;
+    }
+    Ok(value)
+  }
+
+  /// Read a series of octets, which may not start on an octet boundary.
+  pub fn read_bytes(&mut self, len: u64) -> Result<Vec<u8>, err::DecodeError> {
+    // Don't trust the length for the initial allocation.
+    let mut bytes = Vec::with_capacity(::std::cmp::min(len, 4096) as usize);
+    for _ in 0..len {
+      if self.is_aligned() {
+        bytes.push(try!(read_byte(&mut self.reader)));
+        self.count += 8;
+      } else {
+        bytes.push(try!(self.read_bits(8)) as u8);
+      }
+    }
+    Ok(bytes)
+  }
+
+  /// Returns true when the next bit starts an octet.
+  pub fn is_aligned(&self) -> bool {
+    self.count % 8 == 0
+  }
+
+  /// Skip the padding bits until the next octet boundary.
+  pub fn align(&mut self) {
+    self.count = (self.count + 7) / 8 * 8;
+  }
+}
+
+#[test]
+fn bits_unaligned() {
+  let mut writer = BitWriter::new();
+  writer.write_bit(true);
+  writer.write_bits(0x5, 3);
+  writer.write_bytes(b"\xff");
+  writer.align();
+  writer.write_bytes(b"\x01");
+  assert_eq!(writer.count, 24);
+  let bytes = writer.into_bytes();
+  assert_eq!(&bytes[..], b"\xdf\xf0\x01");
+
+  let mut reader = BitReader::new(bytes.iter().map(|&b| Ok(b)));
+  assert_eq!(reader.read_bit().unwrap(), true);
+  assert_eq!(reader.read_bits(3).unwrap(), 0x5);
+  assert_eq!(reader.read_bytes(1).unwrap(), b"\xff");
+  reader.align();
+  assert_eq!(reader.read_bytes(1).unwrap(), b"\x01");
+  assert!(reader.read_bit().is_err());
+}
@@ -0,0 +1,38 @@
+//! Encoding rules to use for PER.
+//!
+//! This module provides encoding rules that can be used with
+//! the `PerSerialize` and `PerDeserialize` traits.
+//!
+//! ```
+//! use asn1_cereal::{PerSerialize, UPER};
+//!
+//! let mut bytes: Vec<u8> = Vec::new();
+//! let _ = PerSerialize::per_serialize_enc(&true, UPER, &mut bytes);
+//! ```
+
+/// A trait to define encoding rules to use while encoding PER.
+pub trait PerEncRules: Copy {
+  /// Returns true when fields are aligned to octet boundaries, as in the
+  /// aligned variant of PER.
+  fn aligned(self) -> bool;
+}
+
+#[derive(Copy, Clone)]
+/// Basic aligned PER.
+pub struct APER;
+
+impl PerEncRules for APER {
+  fn aligned(self) -> bool {
+    true
+  }
+}
+
+#[derive(Copy, Clone)]
+/// Basic unaligned PER.
+pub struct UPER;
+
+impl PerEncRules for UPER {
+  fn aligned(self) -> bool {
+    false
+  }
+}
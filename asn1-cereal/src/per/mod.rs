@@ -0,0 +1,13 @@
+//! Tools for encoding and decoding the Packed Encoding Rules (PER).
+//!
+//! PER doesn't encode tags or lengths where they can be known from the type,
+//! so values are encoded as a series of bits, whose widths are determined by
+//! the constraints of each type (see `Asn1Info::asn1_constraints`). Both the
+//! aligned (`APER`) and unaligned (`UPER`) variants are supported, using the
+//! `PerSerialize` and `PerDeserialize` traits.
+//!
+//! Lengths that need fragmentation (16K or more) aren't supported.
+
+pub mod bits;
+pub mod enc;
+pub mod serial;
@@ -0,0 +1,77 @@
+//! Implementation of the PER traits for Rust integers and `Integer`.
+//!
+//! The value constraint of an INTEGER determines its encoding. A range with
+//! both bounds is encoded as a bit-field just wide enough for the range, a
+//! range with only a lower bound is encoded as octets offset from that bound,
+//! and an unconstrained INTEGER is encoded as two's complement octets.
+
+use std::convert::TryFrom;
+use std::io;
+
+use ::{PerSerialize, PerDeserialize};
+use constraint::Constraints;
+use err;
+use per::enc::PerEncRules;
+use per::bits::{BitWriter, BitReader};
+use per::serial::num;
+use ber::serial::int::Integer;
+
+/// Generate the PER implementation for an int type.
+macro_rules! per_cereal_int {
+  ($rs_type:ty) => (
+    impl PerSerialize for $rs_type {
+      fn per_encode<E: PerEncRules>
+          (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+        let value = try!(i128::try_from(*self)
+          .map_err(|_| err::EncodeError::Custom("INTEGER is too large to encode with PER")));
+        num::write_integer(e, writer, value, constraints.value)
+      }
+    }
+
+    impl PerDeserialize for $rs_type {
+      fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+        let value = try!(num::read_integer(e, reader, constraints.value));
+        <$rs_type>::try_from(value).map_err(|_| err::DecodeError::IntegerOverflow)
+      }
+    }
+  );
+}
+
+per_cereal_int!(i8);
+per_cereal_int!(i16);
+per_cereal_int!(i32);
+per_cereal_int!(i64);
+per_cereal_int!(i128);
+per_cereal_int!(isize);
+
+per_cereal_int!(u8);
+per_cereal_int!(u16);
+per_cereal_int!(u32);
+per_cereal_int!(u64);
+per_cereal_int!(u128);
+per_cereal_int!(usize);
+
+impl PerSerialize for Integer {
+  fn per_encode<E: PerEncRules>
+      (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    // Only an unconstrained INTEGER can be larger than an i128.
+    if constraints.value.is_none() {
+      return num::write_octets(e, writer, self.as_bytes());
+    }
+    let value = try!(i128::try_from(self.clone())
+      .map_err(|_| err::EncodeError::Custom("INTEGER is too large to encode with PER")));
+    value.per_encode(e, constraints, writer)
+  }
+}
+
+impl PerDeserialize for Integer {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    if constraints.value.is_none() {
+      return Integer::from_bytes(&try!(num::read_octets(e, reader)));
+    }
+    let value: i128 = try!(PerDeserialize::per_decode(e, constraints, reader));
+    Ok(Integer::from(value))
+  }
+}
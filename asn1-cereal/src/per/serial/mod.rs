@@ -0,0 +1,12 @@
+//! Traits for serializing and deserializing rust types from/to PER.
+//!
+//! Like BER, this module provides an implementation for built-in types,
+//! and the derive crate can generate an implementation for structs and enums.
+
+pub mod traits;
+pub mod num;
+
+pub mod int;
+pub mod prim;
+pub mod str;
+pub mod seq_of;
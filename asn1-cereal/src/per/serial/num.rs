@@ -0,0 +1,443 @@
+//! Encoding of the whole numbers and lengths that PER is built from.
+//!
+//! These follow the procedures of X.691 clauses 10 to 13, and are used to
+//! implement `PerSerialize` and `PerDeserialize` for each type.
+
+use std::io;
+
+use constraint::Range;
+use err;
+use per::enc::PerEncRules;
+use per::bits::{BitWriter, BitReader};
+
+/// The number of values where a length determinant can no longer be encoded
+/// as a constrained whole number.
+const LEN_64K: u64 = 65536;
+
+/// An error for values that don't satisfy the constraints of their type.
+pub const CONSTRAINT_ERROR: err::EncodeError =
+  err::EncodeError::Custom("Value doesn't satisfy the constraints of its type");
+
+/// Get the number of bits needed to represent a non-negative value.
+pub fn bit_width(value: u128) -> u32 {
+  128 - value.leading_zeros()
+}
+
+/// Get the number of octets needed to represent a non-negative value,
+/// which is at least one.
+fn octet_width(value: u128) -> u32 {
+  ::std::cmp::max(1, (bit_width(value) + 7) / 8)
+}
+
+/// Write a constrained whole number, which is `value` offset from the lower
+/// bound of a range with `count` values.
+pub fn write_constrained<E: PerEncRules>(e: E, writer: &mut BitWriter, value: u128, count: u128) {
+  if count <= 1 {
+    return;
+  }
+  let max = count - 1;
+  if !e.aligned() || count <= 255 {
+    writer.write_bits(value, bit_width(max));
+  } else if count <= 256 {
+    writer.align();
+    writer.write_bits(value, 8);
+  } else if count <= LEN_64K as u128 {
+    writer.align();
+    writer.write_bits(value, 16);
+  } else {
+    // The number of octets is encoded first, then the minimum octets.
+    let octets = octet_width(value);
+    write_constrained(e, writer, octets as u128 - 1, octet_width(max) as u128);
+    writer.align();
+    writer.write_bits(value, octets * 8);
+  }
+}
+
+/// Read a constrained whole number from a range with `count` values, and
+/// return its offset from the lower bound.
+pub fn read_constrained<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>, count: u128) -> Result<u128, err::DecodeError> {
+  if count <= 1 {
+    return Ok(0);
+  }
+  let max = count - 1;
+  let value = if !e.aligned() || count <= 255 {
+    try!(reader.read_bits(bit_width(max)))
+  } else if count <= 256 {
+    reader.align();
+    try!(reader.read_bits(8))
+  } else if count <= LEN_64K as u128 {
+    reader.align();
+    try!(reader.read_bits(16))
+  } else {
+    let octets = try!(read_constrained(e, reader, octet_width(max) as u128)) as u32 + 1;
+    reader.align();
+    try!(reader.read_bits(octets * 8))
+  };
+  if value > max {
+    warn!("Encountered a constrained whole number larger than its range");
+    return Err(err::DecodeError::ConstraintViolation);
+  }
+  Ok(value)
+}
+
+/// Write a normally small non-negative whole number, like a CHOICE index
+/// or the number of extension additions.
+pub fn write_normally_small<E: PerEncRules>(e: E, writer: &mut BitWriter, value: u64)
+    -> Result<(), err::EncodeError> {
+  if value < 64 {
+    writer.write_bit(false);
+    writer.write_bits(value as u128, 6);
+    Ok(())
+  } else {
+    writer.write_bit(true);
+    write_octets(e, writer, &uint_octets(value as u128))
+  }
+}
+
+/// Read a normally small non-negative whole number.
+pub fn read_normally_small<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>) -> Result<u64, err::DecodeError> {
+  if !try!(reader.read_bit()) {
+    return Ok(try!(reader.read_bits(6)) as u64);
+  }
+  let bytes = try!(read_octets(e, reader));
+  if bytes.len() > 8 {
+    return Err(err::DecodeError::IntegerOverflow);
+  }
+  Ok(bytes.iter().fold(0, |v, &b| v << 8 | b as u64))
+}
+
+/// Write a normally small length, like the number of bits in the bitmap of
+/// extension additions, which is at least one.
+pub fn write_normally_small_length<E: PerEncRules>(e: E, writer: &mut BitWriter, len: u64)
+    -> Result<(), err::EncodeError> {
+  if len == 0 {
+    return Err(err::EncodeError::Custom("A normally small length can't be zero"));
+  } else if len <= 64 {
+    writer.write_bit(false);
+    writer.write_bits((len - 1) as u128, 6);
+    Ok(())
+  } else {
+    writer.write_bit(true);
+    write_length(e, writer, len, 0, None)
+  }
+}
+
+/// Read a normally small length.
+pub fn read_normally_small_length<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>) -> Result<u64, err::DecodeError> {
+  if !try!(reader.read_bit()) {
+    return Ok(try!(reader.read_bits(6)) as u64 + 1);
+  }
+  match try!(read_length(e, reader, 0, None)) {
+    0 => {
+      warn!("Encountered a normally small length of zero");
+      Err(err::DecodeError::ConstraintViolation)
+    },
+    len => Ok(len),
+  }
+}
+
+/// Write a length determinant, which is constrained when the upper bound
+/// is known and less than 64K.
+pub fn write_length<E: PerEncRules>(e: E, writer: &mut BitWriter, len: u64, lower: u64, upper: Option<u64>)
+    -> Result<(), err::EncodeError> {
+  match upper {
+    Some(upper) if upper < LEN_64K => {
+      write_constrained(e, writer, (len - lower) as u128, (upper - lower + 1) as u128);
+    },
+    _ => {
+      if e.aligned() {
+        writer.align();
+      }
+      if len < 128 {
+        writer.write_bits(len as u128, 8);
+      } else if len < 16384 {
+        writer.write_bits(0x8000 | len as u128, 16);
+      } else {
+        return Err(err::EncodeError::Custom("PER fragmentation isn't supported"));
+      }
+    },
+  }
+  Ok(())
+}
+
+/// Read a length determinant.
+pub fn read_length<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>, lower: u64, upper: Option<u64>) -> Result<u64, err::DecodeError> {
+  match upper {
+    Some(upper) if upper < LEN_64K => {
+      let value = try!(read_constrained(e, reader, (upper - lower + 1) as u128));
+      Ok(value as u64 + lower)
+    },
+    _ => {
+      if e.aligned() {
+        reader.align();
+      }
+      if !try!(reader.read_bit()) {
+        Ok(try!(reader.read_bits(7)) as u64)
+      } else if !try!(reader.read_bit()) {
+        Ok(try!(reader.read_bits(14)) as u64)
+      } else {
+        warn!("Encountered a fragmented PER length");
+        Err(err::DecodeError::Custom("PER fragmentation isn't supported"))
+      }
+    },
+  }
+}
+
+/// Write octets preceded by an unconstrained length determinant.
+pub fn write_octets<E: PerEncRules>(e: E, writer: &mut BitWriter, bytes: &[u8]) -> Result<(), err::EncodeError> {
+  try!(write_length(e, writer, bytes.len() as u64, 0, None));
+  writer.write_bytes(bytes);
+  Ok(())
+}
+
+/// Read octets preceded by an unconstrained length determinant.
+pub fn read_octets<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>) -> Result<Vec<u8>, err::DecodeError> {
+  let len = try!(read_length(e, reader, 0, None));
+  reader.read_bytes(len)
+}
+
+/// Write an open type, which is a complete encoding wrapped in octets.
+pub fn write_open_type<E: PerEncRules>(e: E, writer: &mut BitWriter, value: BitWriter)
+    -> Result<(), err::EncodeError> {
+  write_octets(e, writer, &value.into_bytes())
+}
+
+/// Skip the extension additions of a SEQUENCE or SET that aren't known to
+/// this version of the type. Each addition is an open type.
+pub fn skip_extensions<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>) -> Result<(), err::DecodeError> {
+  let count = try!(read_normally_small_length(e, reader));
+  let mut present = Vec::new();
+  for _ in 0..count {
+    present.push(try!(reader.read_bit()));
+  }
+  for _ in present.into_iter().filter(|&p| p) {
+    debug!("Skipping an unknown extension addition");
+    try!(read_octets(e, reader));
+  }
+  Ok(())
+}
+
+/// Get the minimal big-endian octets of a non-negative number.
+pub fn uint_octets(value: u128) -> Vec<u8> {
+  let bytes = value.to_be_bytes();
+  bytes[bytes.len() - octet_width(value) as usize..].to_vec()
+}
+
+/// Get the minimal big-endian two's complement octets of a number.
+pub fn int_octets(value: i128) -> Vec<u8> {
+  let bytes = value.to_be_bytes();
+  let mut start = 0;
+  while start < bytes.len() - 1 &&
+        ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0) ||
+         (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0)) {
+    start += 1;
+  }
+  bytes[start..].to_vec()
+}
+
+/// Check a value against an optional range, writing the extension bit if the
+/// range is extensible. Returns the range to use for the value, which is None
+/// if it's unconstrained.
+pub fn write_extensible(writer: &mut BitWriter, range: Option<Range>, value: i128)
+    -> Result<Option<Range>, err::EncodeError> {
+  let range = match range {
+    Some(range) => range,
+    None => return Ok(None),
+  };
+  let in_root = range.contains(value);
+  if range.extensible {
+    writer.write_bit(!in_root);
+  } else if !in_root {
+    return Err(CONSTRAINT_ERROR);
+  }
+  Ok(if in_root { Some(range) } else { None })
+}
+
+/// Read the extension bit of an optional range, if the range is extensible.
+/// Returns the range to use for the value, which is None if it's unconstrained.
+pub fn read_extensible<I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut BitReader<I>, range: Option<Range>) -> Result<Option<Range>, err::DecodeError> {
+  match range {
+    Some(range) if range.extensible && try!(reader.read_bit()) => Ok(None),
+    range => Ok(range),
+  }
+}
+
+/// Write an INTEGER, using a value constraint.
+pub fn write_integer<E: PerEncRules>(e: E, writer: &mut BitWriter, value: i128, range: Option<Range>)
+    -> Result<(), err::EncodeError> {
+  let range = try!(write_extensible(writer, range, value));
+  match range.map_or((None, None), |r| (r.lower, r.count())) {
+    (Some(lower), Some(count)) => {
+      write_constrained(e, writer, value.wrapping_sub(lower) as u128, count);
+      Ok(())
+    },
+    // A semi-constrained number is the offset from the lower bound.
+    (Some(lower), None) => write_octets(e, writer, &uint_octets(value.wrapping_sub(lower) as u128)),
+    (None, _) => write_octets(e, writer, &int_octets(value)),
+  }
+}
+
+/// Read an INTEGER, using a value constraint.
+pub fn read_integer<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>, range: Option<Range>) -> Result<i128, err::DecodeError> {
+  let range = try!(read_extensible(reader, range));
+  match range.map_or((None, None), |r| (r.lower, r.count())) {
+    (Some(lower), Some(count)) => {
+      let value = try!(read_constrained(e, reader, count));
+      Ok(lower.wrapping_add(value as i128))
+    },
+    (Some(lower), None) => {
+      let bytes = try!(read_octets(e, reader));
+      if bytes.is_empty() || bytes.len() > 16 {
+        return Err(err::DecodeError::InvalidInteger);
+      }
+      let value = bytes.iter().fold(0u128, |v, &b| v << 8 | b as u128);
+      Ok(lower.wrapping_add(value as i128))
+    },
+    (None, _) => {
+      let bytes = try!(read_octets(e, reader));
+      if bytes.is_empty() || bytes.len() > 16 {
+        return Err(err::DecodeError::InvalidInteger);
+      }
+      // Sign extend the two's complement value.
+      let init = if bytes[0] & 0x80 != 0 { -1i128 } else { 0 };
+      Ok(bytes.iter().fold(init, |v, &b| v << 8 | b as i128))
+    },
+  }
+}
+
+/// The bounds of a size constraint, after its extension bit is handled.
+pub type SizeBounds = (u64, Option<u64>);
+
+/// Check a size against a size constraint, writing the extension bit if the
+/// constraint is extensible.
+pub fn write_size_ext(writer: &mut BitWriter, size: Option<Range>, len: u64)
+    -> Result<SizeBounds, err::EncodeError> {
+  let range = try!(write_extensible(writer, size, len as i128));
+  Ok(size_bounds(range))
+}
+
+/// Read the extension bit of a size constraint, if it's extensible.
+pub fn read_size_ext<I: Iterator<Item=io::Result<u8>>>
+    (reader: &mut BitReader<I>, size: Option<Range>) -> Result<SizeBounds, err::DecodeError> {
+  let range = try!(read_extensible(reader, size));
+  Ok(size_bounds(range))
+}
+
+fn size_bounds(range: Option<Range>) -> SizeBounds {
+  match range {
+    Some(r) => (r.lower.map_or(0, |l| ::std::cmp::max(l, 0) as u64), r.upper.map(|u| u as u64)),
+    None => (0, None),
+  }
+}
+
+/// Write the length of a string with `len` units of `unit` bits each. The
+/// length is omitted when the size is fixed, and the content is aligned
+/// when it may be longer than 16 bits.
+pub fn write_string_len<E: PerEncRules>(e: E, writer: &mut BitWriter, len: u64, bounds: SizeBounds, unit: u64)
+    -> Result<(), err::EncodeError> {
+  let (lower, upper) = bounds;
+  if upper != Some(lower) || lower >= LEN_64K {
+    try!(write_length(e, writer, len, lower, upper));
+  }
+  if e.aligned() && upper.map_or(true, |u| u * unit > 16) {
+    writer.align();
+  }
+  Ok(())
+}
+
+/// Read the length of a string with units of `unit` bits each.
+pub fn read_string_len<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>, bounds: SizeBounds, unit: u64) -> Result<u64, err::DecodeError> {
+  let (lower, upper) = bounds;
+  let len = if upper != Some(lower) || lower >= LEN_64K {
+    try!(read_length(e, reader, lower, upper))
+  } else {
+    lower
+  };
+  if upper.map_or(false, |u| len > u) || len < lower {
+    warn!("Encountered a length outside of its size constraint");
+    return Err(err::DecodeError::ConstraintViolation);
+  }
+  if e.aligned() && upper.map_or(true, |u| u * unit > 16) {
+    reader.align();
+  }
+  Ok(len)
+}
+
+#[cfg(test)]
+use per::enc::{APER, UPER};
+
+#[cfg(test)]
+fn encode_integer<E: PerEncRules>(e: E, value: i128, range: Option<Range>) -> Vec<u8> {
+  let mut writer = BitWriter::new();
+  write_integer(e, &mut writer, value, range).unwrap();
+  let bytes = writer.into_bytes();
+  let mut reader = BitReader::new(bytes.iter().map(|&b| Ok(b)));
+  assert_eq!(read_integer(e, &mut reader, range).unwrap(), value);
+  bytes
+}
+
+#[test]
+fn per_integer_ranges() {
+  let range = |l, u| Some(Range::new(Some(l), Some(u)));
+  // Examples from X.691 and common usage.
+  assert_eq!(encode_integer(UPER, 3, range(0, 7)), b"\x60");
+  assert_eq!(encode_integer(APER, 3, range(0, 7)), b"\x60");
+  assert_eq!(encode_integer(APER, 255, range(0, 255)), b"\xff");
+  assert_eq!(encode_integer(APER, 256, range(0, 65535)), b"\x01\x00");
+  assert_eq!(encode_integer(UPER, 256, range(0, 65535)), b"\x01\x00");
+  assert_eq!(encode_integer(UPER, 256, range(0, 999)), b"\x40\x00");
+  // Ranges larger than 64K encode the number of octets first.
+  assert_eq!(encode_integer(APER, 256, range(0, 0xffff_ffff)), b"\x40\x01\x00");
+  assert_eq!(encode_integer(UPER, 1, range(0, 0xffff_ffff)), b"\x00\x00\x00\x01");
+  // Semi-constrained and unconstrained.
+  assert_eq!(encode_integer(APER, 4, Some(Range::new(Some(1), None))), b"\x01\x03");
+  assert_eq!(encode_integer(APER, -128, None), b"\x01\x80");
+  assert_eq!(encode_integer(UPER, 128, None), b"\x02\x00\x80");
+
+  // An extensible range has a bit that's set for values outside the root.
+  let ext = Some(Range { extensible: true, .. Range::new(Some(0), Some(7)) });
+  assert_eq!(encode_integer(UPER, 3, ext), b"\x30");
+  assert_eq!(encode_integer(UPER, 8, ext), b"\x80\x84\x00");
+  let mut writer = BitWriter::new();
+  assert!(write_integer(UPER, &mut writer, 8, range(0, 7)).is_err());
+}
+
+#[test]
+fn per_extension_bitmap() {
+  // Up to 64 additions, the length is 6 bits, otherwise it's a length determinant.
+  for &(count, first) in &[(2, 0x01), (64, 0x3f), (70, 0x51)] {
+    // The extension bit comes first.
+    let mut writer = BitWriter::new();
+    writer.write_bit(false);
+    write_normally_small_length(UPER, &mut writer, count).unwrap();
+    assert_eq!(writer.as_bytes()[0], first);
+
+    // Only the first addition is present.
+    writer.write_bit(true);
+    for _ in 1..count {
+      writer.write_bit(false);
+    }
+    write_octets(UPER, &mut writer, b"\x05").unwrap();
+    writer.write_bits(0x2a, 8);
+    let bytes = writer.into_bytes();
+    let mut reader = BitReader::new(bytes.iter().map(|&b| Ok(b)));
+    assert!(!reader.read_bit().unwrap());
+    skip_extensions(UPER, &mut reader).unwrap();
+    assert_eq!(reader.read_bits(8).unwrap(), 0x2a);
+  }
+  assert!(write_normally_small_length(UPER, &mut BitWriter::new(), 0).is_err());
+
+  // A huge count doesn't overflow.
+  let bytes = b"\xc0\x7f\xff\xff\xff\xff\xff\xff\xff\xff";
+  let mut reader = BitReader::new(bytes.iter().map(|&b| Ok(b)));
+  assert!(skip_extensions(UPER, &mut reader).is_err());
+}
@@ -0,0 +1,156 @@
+//! Implementation of the PER traits for BOOLEAN, NULL, OCTET STRING,
+//! BIT STRING and OBJECT IDENTIFIER.
+//!
+//! A BOOLEAN is a single bit and a NULL has no bits. The strings use their
+//! size constraint, and an OBJECT IDENTIFIER has the same content octets
+//! as BER.
+
+use std::io;
+
+use ::{PerSerialize, PerDeserialize};
+use constraint::Constraints;
+use err;
+use per::enc::PerEncRules;
+use per::bits::{BitWriter, BitReader};
+use per::serial::num;
+use ber::serial::prim::{OctetString, BitString, ObjectIdentifier, RelativeOid};
+
+impl PerSerialize for bool {
+  fn per_encode<E: PerEncRules>
+      (&self, _: E, _: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    writer.write_bit(*self);
+    Ok(())
+  }
+}
+
+impl PerDeserialize for bool {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, _: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    reader.read_bit()
+  }
+}
+
+impl PerSerialize for () {
+  fn per_encode<E: PerEncRules>
+      (&self, _: E, _: Constraints, _: &mut BitWriter) -> Result<(), err::EncodeError> {
+    Ok(())
+  }
+}
+
+impl PerDeserialize for () {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (_: E, _: Constraints, _: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    Ok(())
+  }
+}
+
+impl PerSerialize for OctetString {
+  fn per_encode<E: PerEncRules>
+      (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    let len = self.0.len() as u64;
+    let bounds = try!(num::write_size_ext(writer, constraints.size, len));
+    try!(num::write_string_len(e, writer, len, bounds, 8));
+    writer.write_bytes(&self.0);
+    Ok(())
+  }
+}
+
+impl PerDeserialize for OctetString {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    let bounds = try!(num::read_size_ext(reader, constraints.size));
+    let len = try!(num::read_string_len(e, reader, bounds, 8));
+    Ok(OctetString(try!(reader.read_bytes(len))))
+  }
+}
+
+impl PerSerialize for BitString {
+  fn per_encode<E: PerEncRules>
+      (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    let len = self.len();
+    let bounds = try!(num::write_size_ext(writer, constraints.size, len));
+    try!(num::write_string_len(e, writer, len, bounds, 1));
+    for bit in self.iter() {
+      writer.write_bit(bit);
+    }
+    Ok(())
+  }
+}
+
+impl PerDeserialize for BitString {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    let bounds = try!(num::read_size_ext(reader, constraints.size));
+    let len = try!(num::read_string_len(e, reader, bounds, 1));
+    let mut bits = BitString::with_len(len);
+    for i in 0..len {
+      bits.set(i, try!(reader.read_bit()));
+    }
+    Ok(bits)
+  }
+}
+
+impl PerSerialize for ObjectIdentifier {
+  fn per_encode<E: PerEncRules>
+      (&self, e: E, _: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    num::write_octets(e, writer, self.as_bytes())
+  }
+}
+
+impl PerDeserialize for ObjectIdentifier {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, _: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    ObjectIdentifier::from_bytes(try!(num::read_octets(e, reader)))
+  }
+}
+
+impl PerSerialize for RelativeOid {
+  fn per_encode<E: PerEncRules>
+      (&self, e: E, _: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    num::write_octets(e, writer, self.as_bytes())
+  }
+}
+
+impl PerDeserialize for RelativeOid {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, _: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    RelativeOid::from_bytes(try!(num::read_octets(e, reader)))
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use constraint::Range;
+#[cfg(test)]
+use per::enc::{APER, UPER};
+
+#[test]
+fn per_strings() {
+  let mut buf: Vec<u8> = Vec::new();
+  let octets = OctetString(b"abc".to_vec());
+  octets.per_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x03abc");
+
+  // A fixed size doesn't need a length.
+  let c = Constraints::size(Range::single(3));
+  let mut writer = BitWriter::new();
+  true.per_encode(UPER, c, &mut writer).unwrap();
+  octets.per_encode(UPER, c, &mut writer).unwrap();
+  assert_eq!(&writer.as_bytes()[..], b"\xb0\xb1\x31\x80");
+  let mut writer = BitWriter::new();
+  true.per_encode(APER, c, &mut writer).unwrap();
+  octets.per_encode(APER, c, &mut writer).unwrap();
+  assert_eq!(&writer.as_bytes()[..], b"\x80abc");
+
+  let mut bits = BitString::from(vec![0xa0]);
+  bits.resize(3);
+  buf.clear();
+  bits.per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x03\xa0");
+  assert_eq!(BitString::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), bits);
+
+  // Values outside a size constraint are an error.
+  let mut writer = BitWriter::new();
+  assert!(OctetString(b"ab".to_vec()).per_encode(UPER, c, &mut writer).is_err());
+}
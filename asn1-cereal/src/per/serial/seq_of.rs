@@ -0,0 +1,108 @@
+//! Implementation of the PER traits for SEQUENCE OF and SET OF.
+//!
+//! The number of elements is encoded first, using the size constraint, and
+//! then each element is encoded using the constraints of its type.
+
+/// Implement PerSerialize and PerDeserialize for a SEQUENCE/SET OF type.
+///
+/// Requires that a reference to the type implement IntoIterator, and that
+/// the type implement FromIterator.
+macro_rules! per_sequence_of {
+  (impl: $rs_type:ty) => (
+    fn per_encode<E: $crate::per::enc::PerEncRules>
+        (&self, e: E, constraints: $crate::constraint::Constraints, writer: &mut $crate::per::bits::BitWriter)
+        -> Result<(), $crate::err::EncodeError> {
+      $crate::per::serial::seq_of::encode_seq_of(e, self.into_iter(), constraints, writer)
+    }
+  );
+  (impl_de: $rs_type:ty) => (
+    fn per_decode<E: $crate::per::enc::PerEncRules, I: Iterator<Item=::std::io::Result<u8>>>
+        (e: E, constraints: $crate::constraint::Constraints, reader: &mut $crate::per::bits::BitReader<I>)
+        -> Result<Self, $crate::err::DecodeError> {
+      let items: Vec<_> = try!($crate::per::serial::seq_of::decode_seq_of(e, constraints, reader));
+      Ok(items.into_iter().collect())
+    }
+  );
+  ($rs_type:ty => $gen:ident) => (
+    per_sequence_of!($rs_type => $gen, );
+  );
+  ($rs_type:ty => $gen:ident, $($where_attr:tt)*) => (
+    impl<$gen: $crate::PerSerialize> $crate::PerSerialize for $rs_type where $($where_attr)* {
+      per_sequence_of!{impl: $rs_type}
+    }
+
+    impl<$gen: $crate::PerDeserialize> $crate::PerDeserialize for $rs_type where $($where_attr)* {
+      per_sequence_of!{impl_de: $rs_type}
+    }
+  );
+}
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+use std::io;
+
+use ::{PerSerialize, PerDeserialize};
+use constraint::Constraints;
+use err;
+use per::enc::PerEncRules;
+use per::bits::{BitWriter, BitReader};
+use per::serial::num;
+
+per_sequence_of!(Vec<T> => T);
+per_sequence_of!(HashSet<T> => T, T: Eq + Hash);
+per_sequence_of!(BTreeSet<T> => T, T: Ord);
+
+/// Returns true when a size constraint has a fixed size, so the number of
+/// elements isn't encoded.
+fn fixed_size(bounds: num::SizeBounds) -> bool {
+  bounds.1 == Some(bounds.0) && bounds.0 < 65536
+}
+
+/// Encode the elements of a SEQUENCE/SET OF.
+pub fn encode_seq_of<'a, E, T, It>(e: E, items: It, constraints: Constraints, writer: &mut BitWriter)
+    -> Result<(), err::EncodeError>
+    where E: PerEncRules, T: 'a + PerSerialize, It: Iterator<Item=&'a T> {
+  let items: Vec<&T> = items.collect();
+  let len = items.len() as u64;
+  let bounds = try!(num::write_size_ext(writer, constraints.size, len));
+  if !fixed_size(bounds) {
+    try!(num::write_length(e, writer, len, bounds.0, bounds.1));
+  }
+  for item in items {
+    try!(item.per_encode(e, T::asn1_constraints(), writer));
+  }
+  Ok(())
+}
+
+/// Decode the elements of a SEQUENCE/SET OF.
+pub fn decode_seq_of<E, I, T>(e: E, constraints: Constraints, reader: &mut BitReader<I>)
+    -> Result<Vec<T>, err::DecodeError>
+    where E: PerEncRules, I: Iterator<Item=io::Result<u8>>, T: PerDeserialize {
+  let bounds = try!(num::read_size_ext(reader, constraints.size));
+  let len = if fixed_size(bounds) {
+    bounds.0
+  } else {
+    try!(num::read_length(e, reader, bounds.0, bounds.1))
+  };
+  if len < bounds.0 || bounds.1.map_or(false, |u| len > u) {
+    warn!("Encountered a SEQUENCE OF outside of its size constraint");
+    return Err(err::DecodeError::ConstraintViolation);
+  }
+  // Don't trust the length for the initial allocation.
+  let mut items = Vec::with_capacity(::std::cmp::min(len, 1024) as usize);
+  for _ in 0..len {
+    items.push(try!(T::per_decode(e, T::asn1_constraints(), reader)));
+  }
+  Ok(items)
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn per_seq_of() {
+  let mut buf: Vec<u8> = Vec::new();
+  vec![true, false, true].per_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x03\xa0");
+  assert_eq!(Vec::<bool>::per_deserialize(buf.bytes().by_ref()).unwrap(), vec![true, false, true]);
+}
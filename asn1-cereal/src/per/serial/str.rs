@@ -0,0 +1,205 @@
+//! Implementation of the PER traits for String, and the restricted character
+//! string types.
+//!
+//! A known-multiplier character string (like IA5String or BMPString) encodes
+//! each character as a bit-field, whose width depends on the size of its
+//! alphabet, and uses its size constraint like an OCTET STRING. Other strings
+//! (UTF8String and TeletexString) are encoded as octets with a length.
+//!
+//! Like BER, a Rust `String` is treated as a PrintableString, so it can only
+//! be encoded when every character is ASCII. UTCTime and GeneralizedTime are
+//! encoded as a VisibleString holding their DER content.
+
+use std::io;
+
+use ::{PerSerialize, PerDeserialize, BerSerialize, BerDeserialize};
+use constraint::Constraints;
+use tag;
+use err;
+use per::enc::PerEncRules;
+use per::bits::{BitWriter, BitReader};
+use per::serial::num;
+use ber::serial::str::{Utf8String, NumericString, PrintableString, TeletexString, Ia5String,
+                       VisibleString, UniversalString, BmpString, decode_string};
+use ber::serial::time::{UtcTime, GeneralizedTime};
+use ber::enc::{BER, DER};
+
+/// How the characters of a known-multiplier character string are encoded.
+struct Chars {
+  /// The number of content octets for each character in BER.
+  octets: usize,
+  /// The permitted characters, when each character is encoded as its index.
+  alphabet: Option<&'static [u8]>,
+  /// The width of each character with unaligned PER.
+  unaligned: u32,
+  /// The width of each character with aligned PER.
+  aligned: u32,
+}
+
+impl Chars {
+  fn width<E: PerEncRules>(&self, e: E) -> u32 {
+    if e.aligned() { self.aligned } else { self.unaligned }
+  }
+}
+
+const NUMERIC: Chars = Chars { octets: 1, alphabet: Some(b" 0123456789"), unaligned: 4, aligned: 4 };
+const ASCII: Chars = Chars { octets: 1, alphabet: None, unaligned: 7, aligned: 8 };
+const BMP: Chars = Chars { octets: 2, alphabet: None, unaligned: 16, aligned: 16 };
+const UNIVERSAL: Chars = Chars { octets: 4, alphabet: None, unaligned: 32, aligned: 32 };
+
+/// Write the content octets of a known-multiplier character string.
+fn write_chars<E: PerEncRules>(e: E, writer: &mut BitWriter, chars: &Chars, content: &[u8],
+                               constraints: Constraints) -> Result<(), err::EncodeError> {
+  let len = (content.len() / chars.octets) as u64;
+  let width = chars.width(e);
+  let bounds = try!(num::write_size_ext(writer, constraints.size, len));
+  try!(num::write_string_len(e, writer, len, bounds, width as u64));
+  for c in content.chunks(chars.octets) {
+    let value = c.iter().fold(0u32, |v, &b| v << 8 | b as u32);
+    let value = match chars.alphabet {
+      Some(alphabet) => alphabet.iter().position(|&a| a as u32 == value).map(|i| i as u32),
+      None if value >> chars.unaligned == 0 => Some(value),
+      None => None,
+    };
+    let value = try!(value.ok_or(err::EncodeError::Custom("Invalid character for a PER string")));
+    writer.write_bits(value as u128, width);
+  }
+  Ok(())
+}
+
+/// Read a known-multiplier character string, and return its content octets.
+fn read_chars<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, reader: &mut BitReader<I>, chars: &Chars, constraints: Constraints) -> Result<Vec<u8>, err::DecodeError> {
+  let width = chars.width(e);
+  let bounds = try!(num::read_size_ext(reader, constraints.size));
+  let len = try!(num::read_string_len(e, reader, bounds, width as u64));
+  // Don't trust the length for the initial allocation.
+  let mut content = Vec::with_capacity(::std::cmp::min(len, 4096) as usize * chars.octets);
+  for _ in 0..len {
+    let value = try!(reader.read_bits(width)) as u32;
+    let value = match chars.alphabet {
+      Some(alphabet) => *try!(alphabet.get(value as usize).ok_or(err::DecodeError::InvalidCharset)) as u32,
+      None => value,
+    };
+    content.extend_from_slice(&value.to_be_bytes()[4 - chars.octets..]);
+  }
+  Ok(content)
+}
+
+impl PerSerialize for String {
+  fn per_encode<E: PerEncRules>
+      (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+    write_chars(e, writer, &ASCII, self.as_bytes(), constraints)
+  }
+}
+
+impl PerDeserialize for String {
+  fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+    decode_string(try!(read_chars(e, reader, &ASCII, constraints)))
+  }
+}
+
+/// Generate the PER implementation for a known-multiplier character string.
+macro_rules! per_known_multiplier_string {
+  ($name:ident, $chars:expr) => (
+    impl PerSerialize for $name {
+      fn per_encode<E: PerEncRules>
+          (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+        write_chars(e, writer, &$chars, &try!(self.content()), constraints)
+      }
+    }
+
+    impl PerDeserialize for $name {
+      fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+        $name::from_content(try!(read_chars(e, reader, &$chars, constraints)))
+      }
+    }
+  )
+}
+
+/// Generate the PER implementation for a character string that is encoded
+/// as octets.
+macro_rules! per_octet_string {
+  ($name:ident) => (
+    impl PerSerialize for $name {
+      fn per_encode<E: PerEncRules>
+          (&self, e: E, _: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+        num::write_octets(e, writer, &try!(self.content()))
+      }
+    }
+
+    impl PerDeserialize for $name {
+      fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, _: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+        $name::from_content(try!(num::read_octets(e, reader)))
+      }
+    }
+  )
+}
+
+/// Generate the PER implementation for a time type, using its content in BER.
+macro_rules! per_time {
+  ($name:ident) => (
+    impl PerSerialize for $name {
+      fn per_encode<E: PerEncRules>
+          (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError> {
+        let mut content = Vec::new();
+        try!(self.serialize_value(DER, &mut content));
+        write_chars(e, writer, &ASCII, &content, constraints)
+      }
+    }
+
+    impl PerDeserialize for $name {
+      fn per_decode<E: PerEncRules, I: Iterator<Item=io::Result<u8>>>
+          (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError> {
+        let content = try!(read_chars(e, reader, &ASCII, constraints));
+        let len = tag::Len::Def(content.len() as tag::LenNum);
+        $name::deserialize_value(BER, &mut content.into_iter().map(Ok), len)
+      }
+    }
+  )
+}
+
+per_known_multiplier_string!(NumericString, NUMERIC);
+per_known_multiplier_string!(PrintableString, ASCII);
+per_known_multiplier_string!(Ia5String, ASCII);
+per_known_multiplier_string!(VisibleString, ASCII);
+per_known_multiplier_string!(BmpString, BMP);
+per_known_multiplier_string!(UniversalString, UNIVERSAL);
+per_octet_string!(Utf8String);
+per_octet_string!(TeletexString);
+per_time!(UtcTime);
+per_time!(GeneralizedTime);
+
+#[cfg(test)]
+use std::io::Read;
+#[cfg(test)]
+use constraint::Range;
+#[cfg(test)]
+use per::enc::UPER;
+
+#[test]
+fn per_known_multiplier() {
+  let mut buf: Vec<u8> = Vec::new();
+  Ia5String::new("ab").unwrap().per_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x02ab");
+  buf.clear();
+  Ia5String::new("ab").unwrap().per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x02\xc3\x88");
+  assert_eq!(Ia5String::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap().as_str(), "ab");
+
+  // Each digit of a NumericString is 4 bits.
+  let mut writer = BitWriter::new();
+  let c = Constraints::size(Range::new(Some(1), Some(4)));
+  NumericString::new("19 ").unwrap().per_encode(UPER, c, &mut writer).unwrap();
+  assert_eq!(writer.as_bytes(), b"\x8a\x80");
+  let bytes = writer.into_bytes();
+  let mut reader = BitReader::new(bytes.iter().map(|&b| Ok(b)));
+  assert_eq!(NumericString::per_decode(UPER, c, &mut reader).unwrap().as_str(), "19 ");
+
+  buf.clear();
+  Utf8String::new("é").unwrap().per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x02\xc3\xa9");
+}
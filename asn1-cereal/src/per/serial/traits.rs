@@ -0,0 +1,58 @@
+//! The base traits that are used for serializing and deserializing rust types with PER.
+//!
+//! `PerSerialize` implements serialization, and `PerDeserialize` implements deserialization.
+//! Both traits depend upon the `Asn1Info` trait, which provides the constraints of a type.
+//!
+//! Only `per_encode` and `per_decode` need to be implemented. These are given the
+//! constraints that apply to the value, which are the constraints of its type, or
+//! the constraints of the field that contains it.
+use std::io;
+
+use ::Asn1Info;
+use constraint::Constraints;
+use err;
+use per::enc;
+use per::bits::{BitWriter, BitReader};
+
+/// Provides the methods required to serialize this Rust type with PER.
+pub trait PerSerialize: Asn1Info {
+  /// Serialize a value with aligned PER.
+  fn per_serialize<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    self.per_serialize_enc(enc::APER, writer)
+  }
+
+  /// Serialize a value with a specific variant of PER. This writes a complete
+  /// encoding, which is padded to an octet boundary.
+  fn per_serialize_enc<E: enc::PerEncRules, W: io::Write>
+      (&self, e: E, writer: &mut W) -> Result<(), err::EncodeError> {
+    debug!("Encoding the type {} with PER", Self::asn1_type());
+    let mut bits = BitWriter::new();
+    try!(self.per_encode(e, Self::asn1_constraints(), &mut bits));
+    try!(writer.write_all(&bits.into_bytes()));
+    Ok(())
+  }
+
+  /// Encode the bits of this value, using a set of constraints.
+  fn per_encode<E: enc::PerEncRules>
+      (&self, e: E, constraints: Constraints, writer: &mut BitWriter) -> Result<(), err::EncodeError>;
+}
+
+/// Provides the methods required to deserialize this Rust type with PER.
+pub trait PerDeserialize: Asn1Info + Sized {
+  /// Deserialize a value with aligned PER.
+  fn per_deserialize<I: Iterator<Item=io::Result<u8>>>(reader: &mut I) -> Result<Self, err::DecodeError> {
+    Self::per_deserialize_enc(enc::APER, reader)
+  }
+
+  /// Deserialize a value with a specific variant of PER.
+  fn per_deserialize_enc<E: enc::PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, reader: &mut I) -> Result<Self, err::DecodeError> {
+    debug!("Decoding the type {} with PER", Self::asn1_type());
+    let mut bits = BitReader::new(reader);
+    Self::per_decode(e, Self::asn1_constraints(), &mut bits)
+  }
+
+  /// Decode the bits of this value, using a set of constraints.
+  fn per_decode<E: enc::PerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, constraints: Constraints, reader: &mut BitReader<I>) -> Result<Self, err::DecodeError>;
+}
@@ -0,0 +1,138 @@
+//! Descriptions of the structure of ASN.1 types, which don't depend on an
+//! encoding.
+//!
+//! The `Asn1Schema` trait provides a `Schema` for a type, with its kind, tag
+//! and constraints, and a `Component` for each component of a SEQUENCE or
+//! SET, alternative of a CHOICE, value of an ENUMERATED or named bit. The
+//! schema of the type of each component can also be found, so a codec or
+//! tool can walk a whole type without knowing about Rust types.
+//!
+//! Derived types implement `Asn1Schema` with `#[derive(Asn1Schema)]`, which
+//! requires that the types of their components also implement it.
+//!
+//! ```
+//! use asn1_cereal::Asn1Schema;
+//! use asn1_cereal::schema::Kind;
+//!
+//! let schema = Vec::<u32>::asn1_schema();
+//! assert_eq!(schema.kind, Kind::SequenceOf);
+//! let element = (schema.components[0].schema.unwrap())();
+//! assert_eq!(element.asn1_type, "INTEGER");
+//! ```
+
+use ::Asn1Info;
+use tag;
+use constraint::Constraints;
+
+/// The kind of an ASN.1 type, which decides what its components are.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Kind {
+  /// A type without components, like INTEGER or OCTET STRING, which is
+  /// identified by its tag.
+  Primitive,
+  /// A NULL, or a unit struct.
+  Null,
+  /// A SEQUENCE, with a component for each field.
+  Sequence,
+  /// A SET, with a component for each field.
+  Set,
+  /// A CHOICE, with a component for each alternative.
+  Choice,
+  /// An ENUMERATED, with a component for each value.
+  Enumerated,
+  /// A BIT STRING with named bits, with a component for each bit.
+  NamedBits,
+  /// A SEQUENCE OF, with a component for the type of the elements.
+  SequenceOf,
+  /// A SET OF, with a component for the type of the elements.
+  SetOf,
+  /// A type defined as another type, with a component for that type.
+  Alias,
+  /// An open type, like ANY, which can hold a value of any type.
+  Open,
+}
+
+/// A component of an ASN.1 type.
+#[derive(Clone, Debug)]
+pub struct Component {
+  /// The ASN.1 identifier of this component, which is empty for the
+  /// elements of a SEQUENCE OF or the inner type of an alias.
+  pub name: &'static str,
+  /// The tag of this component when it's given by the containing type, like
+  /// the context-specific tag of a SEQUENCE component. When this is None,
+  /// the tag of the component's type is used.
+  pub tag: Option<tag::Tag>,
+  /// Whether this component is OPTIONAL.
+  pub optional: bool,
+  /// Whether this component has a DEFAULT, which is given by the `default`
+  /// attribute, or is the `Default` value of its Rust type.
+  pub default: bool,
+  /// The value of an ENUMERATED, or the number of a named bit.
+  pub value: Option<i64>,
+  /// The constraints of this component, which include any given by the
+  /// containing type.
+  pub constraints: Constraints,
+  /// Get the schema of the type of this component. This is None for the
+  /// values of an ENUMERATED and named bits.
+  pub schema: Option<fn() -> Schema>,
+}
+
+impl Component {
+  /// Create a component for the type `T`, with no tag or constraints other
+  /// than those of the type.
+  pub fn of<T: Asn1Schema + ?Sized>(name: &'static str) -> Self {
+    Component {
+      name: name,
+      tag: None,
+      optional: false,
+      default: false,
+      value: None,
+      constraints: T::asn1_constraints(),
+      schema: Some(T::asn1_schema),
+    }
+  }
+}
+
+/// The description of an ASN.1 type.
+#[derive(Clone, Debug)]
+pub struct Schema {
+  /// The kind of this type.
+  pub kind: Kind,
+  /// The ASN.1 type, like `Asn1Info::asn1_type`.
+  pub asn1_type: tag::Type,
+  /// The tag of this type, like `Asn1Info::asn1_tag`.
+  pub tag: Option<tag::Tag>,
+  /// The constraints of this type, like `Asn1Info::asn1_constraints`.
+  pub constraints: Constraints,
+  /// The components of this type, in the order they're declared.
+  pub components: Vec<Component>,
+}
+
+impl Schema {
+  /// Create a schema for the type `T` using its `Asn1Info`, with no
+  /// components.
+  pub fn of<T: Asn1Info + ?Sized>(kind: Kind) -> Self {
+    Schema {
+      kind: kind,
+      asn1_type: T::asn1_type(),
+      tag: T::asn1_tag(),
+      constraints: T::asn1_constraints(),
+      components: Vec::new(),
+    }
+  }
+
+  /// Find a component by its ASN.1 identifier.
+  pub fn component(&self, name: &str) -> Option<&Component> {
+    self.components.iter().find(|c| c.name == name)
+  }
+}
+
+/// Provides the structure of a Rust type as an ASN.1 type, for codecs and
+/// tools that work with any type.
+pub trait Asn1Schema: Asn1Info {
+  /// Get the schema of this type. By default, this is a primitive type
+  /// without components.
+  fn asn1_schema() -> Schema {
+    Schema::of::<Self>(Kind::Primitive)
+  }
+}
@@ -10,7 +10,7 @@ use std::io;
 /// A type for ASN.1 type names.
 pub type Type = String;
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 /// An ASN.1 Class.
 pub enum Class {
   /// Universal class.
@@ -60,7 +60,7 @@ impl fmt::Display for Class {
 /// An ASN.1 tag number.
 pub type TagNum = u64;
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 /// A struct containing the information from an ASN.1 tag, which represents an ASN.1 element.
 pub struct Tag {
   /// The class flag in the ASN.1 tag.
@@ -84,6 +84,19 @@ impl Tag {
 
   /// Given an iterator over a byte stream, read and return a Tag struct.
   pub fn read_tag<I: Iterator<Item=io::Result<u8>>>(bytes: &mut I) -> Result<Self, err::DecodeError> {
+    Tag::read_tag_strict(bytes, false)
+  }
+
+  /// Given an iterator over a byte stream, read and return a Tag struct. If
+  /// the encoding rules require the shortest form, it's an error for the tag
+  /// number to not use the minimum number of octets.
+  pub fn read_tag_enc<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, bytes: &mut I) -> Result<Self, err::DecodeError> {
+    Tag::read_tag_strict(bytes, e.shortest_form())
+  }
+
+  fn read_tag_strict<I: Iterator<Item=io::Result<u8>>>
+      (bytes: &mut I, strict: bool) -> Result<Self, err::DecodeError> {
     // Decode tag byte, which includes class, constructed flag, and tag number.
     let tag_byte = try!(read_byte(bytes));
     let class_num = (tag_byte & 0xc0) >> 6;
@@ -91,15 +104,27 @@ impl Tag {
     // If tag is 0x1F, use extended decode format.
     let tagnum = if (tag_byte & 0x1f) == 0x1f {
       let mut tag: TagNum = 0;
+      let mut first = true;
       loop {
         // Incrementatlly read bytes, adding base-128 to tag.
         let tag_more = try!(read_byte(bytes));
+        // The first byte can't have a leading zero slice.
+        if strict && first && tag_more == 0x80 {
+          warn!("Encountered a tag number with a leading zero octet");
+          return Err(err::DecodeError::NonMinimalTag);
+        }
+        first = false;
         tag = (tag << 7) + (tag_more & 0x7f) as TagNum;
         // Stop looping when 0x80 bit is set.
         if tag_more & 0x80 == 0x00 {
           break;
         }
       }
+      // Tag numbers below 31 must use the single byte form.
+      if strict && tag < 31 {
+        warn!("Encountered tag number {} using the high tag number form", tag);
+        return Err(err::DecodeError::NonMinimalTag);
+      }
       tag
     // Otherwise it's just bits 5-1.
     } else {
@@ -201,6 +226,19 @@ impl Len {
 
   /// Given an iterator over a byte stream, read and return the ASN.1 element length.
   pub fn read_len<I: Iterator<Item=io::Result<u8>>>(bytes: &mut I) -> Result<Self, err::DecodeError> {
+    Len::read_len_strict(bytes, false)
+  }
+
+  /// Given an iterator over a byte stream, read and return the ASN.1 element length.
+  /// If the encoding rules require the shortest form, it's an error for a definite
+  /// length to not use the minimum number of octets.
+  pub fn read_len_enc<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+      (e: E, bytes: &mut I) -> Result<Self, err::DecodeError> {
+    Len::read_len_strict(bytes, e.shortest_form())
+  }
+
+  fn read_len_strict<I: Iterator<Item=io::Result<u8>>>
+      (bytes: &mut I, strict: bool) -> Result<Self, err::DecodeError> {
     // Decode len byte.
     let len_byte = try!(read_byte(bytes));
     Ok(match len_byte {
@@ -211,11 +249,20 @@ impl Len {
           let mut len: LenNum = 0;
           let byte_count = l & 0x7f;
           // Loop through number of len bytes.
-          for _ in 0..byte_count {
+          for i in 0..byte_count {
             let len_more = try!(read_byte(bytes));
+            if strict && i == 0 && len_more == 0 {
+              warn!("Encountered a length with a leading zero octet");
+              return Err(err::DecodeError::NonMinimalLength);
+            }
             // Add up each byte base-256.
             len = (len << 8) + len_more as TagNum;
           }
+          // Lengths below 128 must use the short form.
+          if strict && len < 128 {
+            warn!("Encountered length {} using the long form", len);
+            return Err(err::DecodeError::NonMinimalLength);
+          }
           Len::Def(len)
         // If 0x80 bit is not set, just decode the value.
         } else {
@@ -329,6 +376,16 @@ pub fn read_taglen<I: Iterator<Item=io::Result<u8>>>(bytes: &mut I) -> Result<(T
   Ok((tag, len))
 }
 
+/// Given an iterator over a byte stream, read and return a `TagLen` struct. If the
+/// encoding rules require the shortest form, it's an error for the tag or length
+/// to not use the minimum number of octets.
+pub fn read_taglen_enc<E: ::BerEncRules, I: Iterator<Item=io::Result<u8>>>
+    (e: E, bytes: &mut I) -> Result<(Tag, Len), err::DecodeError> {
+  let tag = try!(Tag::read_tag_enc(e, bytes));
+  let len = try!(Len::read_len_enc(e, bytes));
+  Ok((tag, len))
+}
+
 /// Write the ASN.1 representation of this `TagNum` struct to the given writer.
 pub fn write_taglen<W: io::Write>(tag: Tag, len: Len, writer: &mut W) -> Result<(), err::EncodeError> {
   try!(tag.write_tag(writer));
@@ -547,3 +604,22 @@ fn tag_missing_len_bytes() {
     _ => panic!("Expected UnexpectedEOf, got {:?}", res.unwrap_err()),
   }
 }
+
+#[test]
+fn tag_non_minimal() {
+  for bytes in &[&b"\x5f\x01\x10"[..], &b"\x5f\x80\x21\x10"[..]] {
+    assert!(read_taglen(bytes.bytes().by_ref()).is_ok());
+    match read_taglen_enc(::DER, bytes.bytes().by_ref()) {
+      Err(err::DecodeError::NonMinimalTag) => {},
+      res => panic!("Expected NonMinimalTag, got {:?}", res),
+    }
+  }
+  for bytes in &[&b"\x30\x81\x11"[..], &b"\x30\x82\x00\x81"[..]] {
+    assert!(read_taglen(bytes.bytes().by_ref()).is_ok());
+    match read_taglen_enc(::DER, bytes.bytes().by_ref()) {
+      Err(err::DecodeError::NonMinimalLength) => {},
+      res => panic!("Expected NonMinimalLength, got {:?}", res),
+    }
+  }
+  assert!(read_taglen_enc(::DER, b"\x7f\x81\x00\x81\x81".bytes().by_ref()).is_ok());
+}
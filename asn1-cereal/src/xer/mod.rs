@@ -0,0 +1,14 @@
+//! Tools for encoding and decoding the basic XML Encoding Rules (XER).
+//!
+//! A value is encoded as an XML element named after its ASN.1 type (see
+//! `Asn1Info::asn1_type`), and the components of a SEQUENCE, SET or CHOICE
+//! are elements named by their identifiers (see `Asn1Info::asn1_fields`).
+//! Derived types use the Rust identifier of each field, unless the field has
+//! a `name` attribute.
+//!
+//! The `XerSerialize` and `XerDeserialize` traits are used for XER. Only the
+//! XML needed for XER is understood, so attributes, namespaces and CDATA
+//! are ignored or rejected.
+
+pub mod xml;
+pub mod serial;
@@ -0,0 +1,61 @@
+//! Implementation of the XER traits for Rust integers and `Integer`.
+//!
+//! An INTEGER is encoded as decimal digits.
+
+use std::convert::TryFrom;
+use std::io;
+
+use ::{XerSerialize, XerDeserialize};
+use err;
+use xer::xml::XmlReader;
+use ber::serial::int::Integer;
+
+/// Generate the XER implementation for an int type.
+macro_rules! xer_cereal_int {
+  ($rs_type:ty) => (
+    impl XerSerialize for $rs_type {
+      fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+        try!(write!(writer, "{}", self));
+        Ok(())
+      }
+    }
+
+    impl XerDeserialize for $rs_type {
+      fn xer_decode<I: Iterator<Item=io::Result<u8>>>
+          (reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+        let text = try!(reader.text());
+        let value: i128 = try!(text.trim().parse().map_err(|_| err::DecodeError::InvalidInteger));
+        <$rs_type>::try_from(value).map_err(|_| err::DecodeError::IntegerOverflow)
+      }
+    }
+  );
+}
+
+xer_cereal_int!(i8);
+xer_cereal_int!(i16);
+xer_cereal_int!(i32);
+xer_cereal_int!(i64);
+xer_cereal_int!(i128);
+xer_cereal_int!(isize);
+
+xer_cereal_int!(u8);
+xer_cereal_int!(u16);
+xer_cereal_int!(u32);
+xer_cereal_int!(u64);
+xer_cereal_int!(u128);
+xer_cereal_int!(usize);
+
+impl XerSerialize for Integer {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    let value = try!(i128::try_from(self.clone())
+      .map_err(|_| err::EncodeError::Custom("INTEGER is too large to encode with XER")));
+    value.xer_encode(writer)
+  }
+}
+
+impl XerDeserialize for Integer {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    let value: i128 = try!(XerDeserialize::xer_decode(reader));
+    Ok(Integer::from(value))
+  }
+}
@@ -0,0 +1,145 @@
+//! Implementation of the XER traits for BOOLEAN, NULL, OCTET STRING,
+//! BIT STRING and OBJECT IDENTIFIER.
+//!
+//! A BOOLEAN is an empty `<true/>` or `<false/>` element, and a NULL has no
+//! content. An OCTET STRING is hexadecimal digits, a BIT STRING is the digits
+//! `0` and `1`, and an OBJECT IDENTIFIER is its dotted arcs.
+
+use std::io;
+
+use ::{XerSerialize, XerDeserialize};
+use err;
+use xer::xml::{self, XmlReader};
+use ber::serial::prim::{OctetString, BitString, ObjectIdentifier, RelativeOid};
+
+impl XerSerialize for bool {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(xml::write_empty(writer, if *self { "true" } else { "false" }));
+    Ok(())
+  }
+}
+
+impl XerDeserialize for bool {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    let name = try!(try!(reader.child()).ok_or(err::DecodeError::InvalidBool));
+    let value = match name.as_str() {
+      "true" => true,
+      "false" => false,
+      _ => return Err(err::DecodeError::InvalidBool),
+    };
+    try!(reader.end(&name));
+    Ok(value)
+  }
+}
+
+impl XerSerialize for () {
+  fn xer_encode<W: io::Write>(&self, _: &mut W) -> Result<(), err::EncodeError> {
+    Ok(())
+  }
+}
+
+impl XerDeserialize for () {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    if !try!(reader.text()).trim().is_empty() {
+      return Err(err::DecodeError::InvalidNull);
+    }
+    Ok(())
+  }
+}
+
+impl XerSerialize for OctetString {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    for byte in &self.0 {
+      try!(write!(writer, "{:02X}", byte));
+    }
+    Ok(())
+  }
+}
+
+impl XerDeserialize for OctetString {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    let text = try!(reader.text());
+    let digits: Vec<u32> = try!(text.chars().filter(|c| !c.is_whitespace())
+      .map(|c| c.to_digit(16)).collect::<Option<_>>().ok_or(err::DecodeError::InvalidXml));
+    if digits.len() % 2 != 0 {
+      return Err(err::DecodeError::InvalidXml);
+    }
+    Ok(OctetString(digits.chunks(2).map(|d| (d[0] << 4 | d[1]) as u8).collect()))
+  }
+}
+
+impl XerSerialize for BitString {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    for bit in self.iter() {
+      try!(writer.write_all(if bit { b"1" } else { b"0" }));
+    }
+    Ok(())
+  }
+}
+
+impl XerDeserialize for BitString {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    let text = try!(reader.text());
+    let mut bits = BitString::new();
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+      let len = bits.len();
+      bits.resize(len + 1);
+      match c {
+        '0' => {},
+        '1' => bits.set(len, true),
+        _ => return Err(err::DecodeError::InvalidBitString),
+      }
+    }
+    Ok(bits)
+  }
+}
+
+impl XerSerialize for ObjectIdentifier {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(write!(writer, "{}", self));
+    Ok(())
+  }
+}
+
+impl XerDeserialize for ObjectIdentifier {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    try!(reader.text()).trim().parse().map_err(|_| err::DecodeError::InvalidOid)
+  }
+}
+
+impl XerSerialize for RelativeOid {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(write!(writer, "{}", self));
+    Ok(())
+  }
+}
+
+impl XerDeserialize for RelativeOid {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    try!(reader.text()).trim().parse().map_err(|_| err::DecodeError::InvalidOid)
+  }
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn xer_primitives() {
+  let mut buf: Vec<u8> = Vec::new();
+  OctetString(b"\x01\xab".to_vec()).xer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"<OCTET_STRING>01AB</OCTET_STRING>"[..]);
+  assert_eq!(OctetString::xer_deserialize(buf.bytes().by_ref()).unwrap().0, b"\x01\xab");
+
+  buf.clear();
+  true.xer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"<BOOLEAN><true/></BOOLEAN>"[..]);
+  assert_eq!(bool::xer_deserialize(buf.bytes().by_ref()).unwrap(), true);
+
+  let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+  buf.clear();
+  oid.xer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"<OBJECT_IDENTIFIER>1.2.840.113549</OBJECT_IDENTIFIER>"[..]);
+  assert_eq!(ObjectIdentifier::xer_deserialize(buf.bytes().by_ref()).unwrap(), oid);
+
+  assert_eq!(<()>::xer_deserialize(b"<NULL/>".bytes().by_ref()).unwrap(), ());
+}
@@ -0,0 +1,86 @@
+//! Implementation of the XER traits for SEQUENCE OF and SET OF.
+//!
+//! Each element is encoded as an element named after the ASN.1 type of the
+//! elements, like `<INTEGER>5</INTEGER>`.
+
+/// Implement XerSerialize and XerDeserialize for a SEQUENCE/SET OF type.
+///
+/// Requires that a reference to the type implement IntoIterator, and that
+/// the type implement FromIterator.
+macro_rules! xer_sequence_of {
+  (impl: $rs_type:ty) => (
+    fn xer_encode<W: ::std::io::Write>(&self, writer: &mut W) -> Result<(), $crate::err::EncodeError> {
+      $crate::xer::serial::seq_of::encode_seq_of(self.into_iter(), writer)
+    }
+  );
+  (impl_de: $rs_type:ty) => (
+    fn xer_decode<I: Iterator<Item=::std::io::Result<u8>>>
+        (reader: &mut $crate::xer::xml::XmlReader<I>) -> Result<Self, $crate::err::DecodeError> {
+      let items: Vec<_> = try!($crate::xer::serial::seq_of::decode_seq_of(reader));
+      Ok(items.into_iter().collect())
+    }
+  );
+  ($rs_type:ty => $gen:ident) => (
+    xer_sequence_of!($rs_type => $gen, );
+  );
+  ($rs_type:ty => $gen:ident, $($where_attr:tt)*) => (
+    impl<$gen: $crate::XerSerialize> $crate::XerSerialize for $rs_type where $($where_attr)* {
+      xer_sequence_of!{impl: $rs_type}
+    }
+
+    impl<$gen: $crate::XerDeserialize> $crate::XerDeserialize for $rs_type where $($where_attr)* {
+      xer_sequence_of!{impl_de: $rs_type}
+    }
+  );
+}
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+use std::io;
+
+use ::{XerSerialize, XerDeserialize};
+use err;
+use xer::xml::{self, XmlReader};
+
+xer_sequence_of!(Vec<T> => T);
+xer_sequence_of!(HashSet<T> => T, T: Eq + Hash);
+xer_sequence_of!(BTreeSet<T> => T, T: Ord);
+
+/// Encode the elements of a SEQUENCE/SET OF.
+pub fn encode_seq_of<'a, T, It, W>(items: It, writer: &mut W) -> Result<(), err::EncodeError>
+    where T: 'a + XerSerialize, It: Iterator<Item=&'a T>, W: io::Write {
+  let name = xml::element_name(&T::asn1_type());
+  for item in items {
+    try!(xml::write_start(writer, &name));
+    try!(item.xer_encode(writer));
+    try!(xml::write_end(writer, &name));
+  }
+  Ok(())
+}
+
+/// Decode the elements of a SEQUENCE/SET OF.
+pub fn decode_seq_of<I, T>(reader: &mut XmlReader<I>) -> Result<Vec<T>, err::DecodeError>
+    where I: Iterator<Item=io::Result<u8>>, T: XerDeserialize {
+  let name = xml::element_name(&T::asn1_type());
+  let mut items = Vec::new();
+  while let Some(child) = try!(reader.child()) {
+    if child != name {
+      warn!("Expected the element {}, but got {}", name, child);
+      return Err(err::DecodeError::InvalidXml);
+    }
+    items.push(try!(T::xer_decode(reader)));
+    try!(reader.end(&child));
+  }
+  Ok(items)
+}
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn xer_seq_of() {
+  let mut buf: Vec<u8> = Vec::new();
+  vec![1u8, 2].xer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &b"<SEQUENCE_OF><INTEGER>1</INTEGER><INTEGER>2</INTEGER></SEQUENCE_OF>"[..]);
+  assert_eq!(Vec::<u8>::xer_deserialize(buf.bytes().by_ref()).unwrap(), vec![1, 2]);
+}
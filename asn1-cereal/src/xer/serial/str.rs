@@ -0,0 +1,90 @@
+//! Implementation of the XER traits for String, and the restricted character
+//! string types.
+//!
+//! The characters of a string are its character data, with markup characters
+//! escaped. UTCTime and GeneralizedTime are encoded as their DER content.
+
+use std::io;
+
+use ::{XerSerialize, XerDeserialize, BerSerialize, BerDeserialize};
+use tag;
+use err;
+use xer::xml::{self, XmlReader};
+use ber::serial::str::{Utf8String, NumericString, PrintableString, TeletexString, Ia5String,
+                       VisibleString, UniversalString, BmpString};
+use ber::serial::time::{UtcTime, GeneralizedTime};
+use ber::enc::{BER, DER};
+
+impl XerSerialize for String {
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    try!(xml::write_text(writer, self));
+    Ok(())
+  }
+}
+
+impl XerDeserialize for String {
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+    reader.text()
+  }
+}
+
+/// Generate the XER implementation for a restricted character string.
+macro_rules! xer_string {
+  ($name:ident) => (
+    impl XerSerialize for $name {
+      fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+        try!(xml::write_text(writer, self.as_str()));
+        Ok(())
+      }
+    }
+
+    impl XerDeserialize for $name {
+      fn xer_decode<I: Iterator<Item=io::Result<u8>>>
+          (reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+        $name::new(try!(reader.text()))
+      }
+    }
+  )
+}
+
+/// Generate the XER implementation for a time type, using its content in BER.
+macro_rules! xer_time {
+  ($name:ident) => (
+    impl XerSerialize for $name {
+      fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+        self.serialize_value(DER, writer)
+      }
+    }
+
+    impl XerDeserialize for $name {
+      fn xer_decode<I: Iterator<Item=io::Result<u8>>>
+          (reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError> {
+        let content = try!(reader.text()).trim().to_owned().into_bytes();
+        let len = tag::Len::Def(content.len() as tag::LenNum);
+        $name::deserialize_value(BER, &mut content.into_iter().map(Ok), len)
+      }
+    }
+  )
+}
+
+xer_string!(Utf8String);
+xer_string!(NumericString);
+xer_string!(PrintableString);
+xer_string!(TeletexString);
+xer_string!(Ia5String);
+xer_string!(VisibleString);
+xer_string!(UniversalString);
+xer_string!(BmpString);
+xer_time!(UtcTime);
+xer_time!(GeneralizedTime);
+
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn xer_escaped_string() {
+  let mut buf: Vec<u8> = Vec::new();
+  Utf8String::new("a<b & é").unwrap().xer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], "<UTF8String>a&lt;b &amp; é</UTF8String>".as_bytes());
+  assert_eq!(Utf8String::xer_deserialize(buf.bytes().by_ref()).unwrap().as_str(), "a<b & é");
+}
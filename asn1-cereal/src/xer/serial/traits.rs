@@ -0,0 +1,49 @@
+//! The base traits that are used for serializing and deserializing rust types with XER.
+//!
+//! `XerSerialize` implements serialization, and `XerDeserialize` implements deserialization.
+//! Both traits depend upon the `Asn1Info` trait, which provides the name of the element
+//! that holds a complete encoding.
+//!
+//! Only `xer_encode` and `xer_decode` need to be implemented. These handle the content
+//! of an element, since the name of the element depends on where the value is used.
+use std::io;
+
+use ::Asn1Info;
+use err;
+use xer::xml::{self, XmlReader};
+
+/// Provides the methods required to serialize this Rust type with XER.
+pub trait XerSerialize: Asn1Info {
+  /// Serialize a value with XER, as an element named after its ASN.1 type.
+  fn xer_serialize<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError> {
+    debug!("Encoding the type {} with XER", Self::asn1_type());
+    let name = xml::element_name(&Self::asn1_type());
+    try!(xml::write_start(writer, &name));
+    try!(self.xer_encode(writer));
+    try!(xml::write_end(writer, &name));
+    Ok(())
+  }
+
+  /// Encode the content of the element holding this value.
+  fn xer_encode<W: io::Write>(&self, writer: &mut W) -> Result<(), err::EncodeError>;
+}
+
+/// Provides the methods required to deserialize this Rust type with XER.
+pub trait XerDeserialize: Asn1Info + Sized {
+  /// Deserialize a value with XER, from an element named after its ASN.1 type.
+  fn xer_deserialize<I: Iterator<Item=io::Result<u8>>>(reader: &mut I) -> Result<Self, err::DecodeError> {
+    debug!("Decoding the type {} with XER", Self::asn1_type());
+    let mut xml = XmlReader::new(reader);
+    let name = try!(xml.start());
+    if name != xml::element_name(&Self::asn1_type()) {
+      warn!("Expected the element {}, but got {}", Self::asn1_type(), name);
+      return Err(err::DecodeError::InvalidXml);
+    }
+    let value = try!(Self::xer_decode(&mut xml));
+    try!(xml.end(&name));
+    Ok(value)
+  }
+
+  /// Decode the content of the element holding this value.
+  fn xer_decode<I: Iterator<Item=io::Result<u8>>>(reader: &mut XmlReader<I>) -> Result<Self, err::DecodeError>;
+}
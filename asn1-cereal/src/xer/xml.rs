@@ -0,0 +1,245 @@
+//! Reading and writing the XML elements that XER is built from.
+
+use std::io;
+
+use err;
+use byte::read_byte;
+
+/// Get the XML element name for an ASN.1 type, such as `OCTET_STRING` for
+/// OCTET STRING.
+pub fn element_name(asn1_type: &str) -> String {
+  asn1_type.replace(' ', "_")
+}
+
+/// Write the start tag of an element.
+pub fn write_start<W: io::Write>(writer: &mut W, name: &str) -> io::Result<()> {
+  write!(writer, "<{}>", name)
+}
+
+/// Write the end tag of an element.
+pub fn write_end<W: io::Write>(writer: &mut W, name: &str) -> io::Result<()> {
+  write!(writer, "</{}>", name)
+}
+
+/// Write an element with no content.
+pub fn write_empty<W: io::Write>(writer: &mut W, name: &str) -> io::Result<()> {
+  write!(writer, "<{}/>", name)
+}
+
+/// Write character data, escaping any markup characters.
+pub fn write_text<W: io::Write>(writer: &mut W, text: &str) -> io::Result<()> {
+  for c in text.chars() {
+    match c {
+      '&' => try!(writer.write_all(b"&amp;")),
+      '<' => try!(writer.write_all(b"&lt;")),
+      '>' => try!(writer.write_all(b"&gt;")),
+      c => try!(write!(writer, "{}", c)),
+    }
+  }
+  Ok(())
+}
+
+/// A reader for the elements of an XER encoding.
+///
+/// After `start` or `child` reads a start tag, the content of that element
+/// is read with `text` or `child`, and then `end` reads its end tag.
+pub struct XmlReader<I: Iterator<Item=io::Result<u8>>> {
+  reader: I,
+  pushed: Vec<u8>,
+  empty: bool,
+}
+
+impl<I: Iterator<Item=io::Result<u8>>> XmlReader<I> {
+  /// Create a new XmlReader from an Iterator.
+  pub fn new(reader: I) -> XmlReader<I> {
+    XmlReader {
+      reader: reader,
+      pushed: Vec::new(),
+      empty: false,
+    }
+  }
+
+  /// Turn this XmlReader back into the original reader used to create it.
+  pub fn into_reader(self) -> I {
+    self.reader
+  }
+
+  fn next_byte(&mut self) -> Result<u8, err::DecodeError> {
+    match self.pushed.pop() {
+      Some(byte) => Ok(byte),
+      None => Ok(try!(read_byte(&mut self.reader))),
+    }
+  }
+
+  fn skip_space(&mut self) -> Result<(), err::DecodeError> {
+    loop {
+      let byte = try!(self.next_byte());
+      if !(byte as char).is_whitespace() {
+        self.pushed.push(byte);
+        return Ok(());
+      }
+    }
+  }
+
+  /// Skip bytes until after a terminator, like `-->`.
+  fn skip_until(&mut self, end: &[u8]) -> Result<(), err::DecodeError> {
+    let mut recent = Vec::with_capacity(end.len());
+    while recent != end {
+      if recent.len() == end.len() {
+        recent.remove(0);
+      }
+      recent.push(try!(self.next_byte()));
+    }
+    Ok(())
+  }
+
+  /// Read the `<` of the next tag, skipping any whitespace, comments and
+  /// processing instructions. Returns false when the next tag is an end tag,
+  /// which isn't read.
+  fn next_tag(&mut self) -> Result<bool, err::DecodeError> {
+    loop {
+      try!(self.skip_space());
+      if try!(self.next_byte()) != b'<' {
+        return Err(err::DecodeError::InvalidXml);
+      }
+      match try!(self.next_byte()) {
+        b'/' => {
+          self.pushed.push(b'/');
+          self.pushed.push(b'<');
+          return Ok(false);
+        },
+        b'?' => try!(self.skip_until(b"?>")),
+        b'!' => try!(self.skip_until(b"-->")),
+        byte => {
+          self.pushed.push(byte);
+          return Ok(true);
+        },
+      }
+    }
+  }
+
+  /// Read the name of a tag, and any attributes, up to the closing `>`.
+  /// Returns the name, and records whether the element is empty.
+  fn read_tag(&mut self) -> Result<String, err::DecodeError> {
+    let mut name = Vec::new();
+    let mut in_name = true;
+    let mut last = 0;
+    loop {
+      let byte = try!(self.next_byte());
+      if byte == b'>' {
+        break;
+      }
+      // Everything after the name is ignored.
+      if byte == b'/' || (byte as char).is_whitespace() {
+        in_name = false;
+      } else if in_name {
+        name.push(byte);
+      }
+      last = byte;
+    }
+    self.empty = last == b'/';
+    if name.is_empty() {
+      return Err(err::DecodeError::InvalidXml);
+    }
+    String::from_utf8(name).map_err(|_| err::DecodeError::InvalidXml)
+  }
+
+  /// Read the start tag of the next element, and return its name.
+  pub fn start(&mut self) -> Result<String, err::DecodeError> {
+    if !try!(self.next_tag()) {
+      return Err(err::DecodeError::InvalidXml);
+    }
+    self.read_tag()
+  }
+
+  /// Read the start tag of the next child element of the current element,
+  /// and return its name. Returns None when there are no more children.
+  pub fn child(&mut self) -> Result<Option<String>, err::DecodeError> {
+    if self.empty || !try!(self.next_tag()) {
+      return Ok(None);
+    }
+    Ok(Some(try!(self.read_tag())))
+  }
+
+  /// Read the character data of the current element.
+  pub fn text(&mut self) -> Result<String, err::DecodeError> {
+    if self.empty {
+      return Ok(String::new());
+    }
+    let mut bytes = Vec::new();
+    loop {
+      let byte = try!(self.next_byte());
+      if byte == b'<' {
+        self.pushed.push(byte);
+        break;
+      }
+      bytes.push(byte);
+    }
+    let text = try!(String::from_utf8(bytes).map_err(|_| err::DecodeError::InvalidXml));
+    unescape(&text)
+  }
+
+  /// Read the end tag of the current element, which must be `name`.
+  pub fn end(&mut self, name: &str) -> Result<(), err::DecodeError> {
+    if self.empty {
+      self.empty = false;
+      return Ok(());
+    }
+    if try!(self.next_tag()) {
+      warn!("Encountered an unexpected element while reading XER");
+      return Err(err::DecodeError::InvalidXml);
+    }
+    // Skip the `</`.
+    try!(self.next_byte());
+    try!(self.next_byte());
+    let end = try!(self.read_tag());
+    self.empty = false;
+    if end != name {
+      warn!("Expected the end tag of {}, but got {}", name, end);
+      return Err(err::DecodeError::InvalidXml);
+    }
+    Ok(())
+  }
+}
+
+/// Replace the entity and character references in character data.
+fn unescape(text: &str) -> Result<String, err::DecodeError> {
+  let mut out = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    out.push_str(&rest[..start]);
+    let end = try!(rest[start..].find(';').ok_or(err::DecodeError::InvalidXml)) + start;
+    let entity = &rest[start + 1..end];
+    let c = match entity {
+      "amp" => '&',
+      "lt" => '<',
+      "gt" => '>',
+      "quot" => '"',
+      "apos" => '\'',
+      _ if entity.starts_with("#x") => try!(u32::from_str_radix(&entity[2..], 16).ok()
+        .and_then(::std::char::from_u32).ok_or(err::DecodeError::InvalidXml)),
+      _ if entity.starts_with('#') => try!(entity[1..].parse().ok()
+        .and_then(::std::char::from_u32).ok_or(err::DecodeError::InvalidXml)),
+      _ => return Err(err::DecodeError::InvalidXml),
+    };
+    out.push(c);
+    rest = &rest[end + 1..];
+  }
+  out.push_str(rest);
+  Ok(out)
+}
+
+#[test]
+fn xml_reader() {
+  let xml = b"<?xml version=\"1.0\"?>\n<A>\n  <b x=\"1\">a &amp; &#x62;</b><c/>\n</A>";
+  let mut reader = XmlReader::new(xml.iter().map(|&b| Ok(b)));
+  assert_eq!(reader.start().unwrap(), "A");
+  assert_eq!(reader.child().unwrap().unwrap(), "b");
+  assert_eq!(reader.text().unwrap(), "a & b");
+  reader.end("b").unwrap();
+  assert_eq!(reader.child().unwrap().unwrap(), "c");
+  assert_eq!(reader.child().unwrap(), None);
+  reader.end("c").unwrap();
+  assert_eq!(reader.child().unwrap(), None);
+  reader.end("A").unwrap();
+}
@@ -0,0 +1,996 @@
+#[macro_use]
+extern crate asn1_cereal_derive;
+extern crate asn1_cereal;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "time")]
+extern crate time;
+
+use std::io::Read;
+use std::cell::Cell;
+
+use asn1_cereal::{BerSerialize, BerDeserialize, DER, Lenient};
+use asn1_cereal::ber::enc::Deviation;
+
+#[derive(Asn1Info, Asn1Schema, BerSerialize, BerDeserialize, Debug, PartialEq, Default)]
+#[asn1(asn1_type="KeyUsage", form="bits")]
+struct KeyUsage {
+  #[asn1(bit=0)]
+  digital_signature: bool,
+  #[asn1(bit=1)]
+  non_repudiation: bool,
+  #[asn1(bit=2)]
+  key_encipherment: bool,
+  #[asn1(bit=5)]
+  key_cert_sign: bool,
+  #[asn1(bit=6)]
+  crl_sign: bool,
+}
+
+#[test]
+fn bits_named() {
+  let usage = KeyUsage {
+    digital_signature: true,
+    key_cert_sign: true,
+    crl_sign: true,
+    ..KeyUsage::default()
+  };
+  let bytes = b"\x03\x02\x01\x86";
+  let mut buf: Vec<u8> = Vec::new();
+  usage.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, bytes);
+  assert_eq!(KeyUsage::deserialize_enc(DER, bytes.bytes().by_ref()).unwrap(), usage);
+}
+
+#[test]
+fn bits_trailing_zeroes() {
+  // Only the digitalSignature bit, with seven trailing bits trimmed.
+  let usage = KeyUsage { digital_signature: true, ..KeyUsage::default() };
+  let mut buf: Vec<u8> = Vec::new();
+  usage.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x03\x02\x07\x80");
+
+  // No bits set is an empty BIT STRING.
+  buf.clear();
+  KeyUsage::default().serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x03\x01\x00");
+  assert_eq!(KeyUsage::deserialize(buf.bytes().by_ref()).unwrap(), KeyUsage::default());
+}
+
+#[test]
+fn bits_unknown() {
+  // Unknown bits 3 and 9 are ignored.
+  let bytes = b"\x03\x03\x06\x90\x40";
+  let usage = KeyUsage::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(usage, KeyUsage { digital_signature: true, ..KeyUsage::default() });
+
+  // The constructed form is accepted with BER.
+  let bytes = b"\x23\x80\x03\x02\x00\x84\x03\x02\x07\x80\x00\x00";
+  let usage = KeyUsage::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(usage, KeyUsage { digital_signature: true, key_cert_sign: true, ..KeyUsage::default() });
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="UnSpecified")]
+struct UnSpecified;
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="NoSuchObject", tag="[CONTEXT 0]")]
+struct NoSuchObject;
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Value", form="choice")]
+enum Value {
+  Int(i32),
+  UnSpecified(UnSpecified),
+  NoSuchObject(NoSuchObject),
+  #[asn1(tag="[CONTEXT 1]")]
+  NoSuchInstance,
+  Nothing,
+}
+
+#[test]
+fn null_unit_struct() {
+  let mut buf: Vec<u8> = Vec::new();
+  UnSpecified.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x05\x00");
+  assert_eq!(UnSpecified::deserialize(buf.bytes().by_ref()).unwrap(), UnSpecified);
+
+  buf.clear();
+  NoSuchObject.serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x80\x00");
+  assert_eq!(NoSuchObject::deserialize(buf.bytes().by_ref()).unwrap(), NoSuchObject);
+
+  // A NULL must have no content.
+  assert!(NoSuchObject::deserialize(b"\x80\x01\x00".bytes().by_ref()).is_err());
+}
+
+#[test]
+fn null_choice() {
+  let cases: Vec<(Value, &[u8])> = vec![
+    (Value::Int(5), b"\x02\x01\x05"),
+    (Value::UnSpecified(UnSpecified), b"\x05\x00"),
+    (Value::NoSuchObject(NoSuchObject), b"\x80\x00"),
+    (Value::NoSuchInstance, b"\x81\x00"),
+    (Value::Nothing, b"\x05\x00"),
+  ];
+  for (value, bytes) in cases {
+    let mut buf: Vec<u8> = Vec::new();
+    value.serialize(&mut buf).unwrap();
+    assert_eq!(&buf[..], bytes);
+  }
+  let decoded = Value::deserialize(b"\x81\x00".bytes().by_ref()).unwrap();
+  assert_eq!(decoded, Value::NoSuchInstance);
+  assert!(Value::deserialize(b"\x81\x01\x00".bytes().by_ref()).is_err());
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="ErrorStatus", form="enumerated")]
+enum ErrorStatus {
+  NoError,
+  TooBig,
+  #[asn1(value=5)]
+  GenErr,
+  NoAccess,
+  Negative = -1,
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="ResultCode", tag="[APPLICATION 10 PRIMITIVE]", form="enumerated", extensible)]
+enum ResultCode {
+  Success,
+  OperationsError,
+  Unknown(i64),
+}
+
+#[test]
+fn enumerated_values() {
+  let cases = vec![
+    (ErrorStatus::NoError, b"\x0a\x01\x00"),
+    (ErrorStatus::TooBig, b"\x0a\x01\x01"),
+    (ErrorStatus::GenErr, b"\x0a\x01\x05"),
+    (ErrorStatus::NoAccess, b"\x0a\x01\x06"),
+    (ErrorStatus::Negative, b"\x0a\x01\xff"),
+  ];
+  for (value, bytes) in cases {
+    let mut buf: Vec<u8> = Vec::new();
+    value.serialize(&mut buf).unwrap();
+    assert_eq!(&buf, bytes);
+    assert_eq!(ErrorStatus::deserialize(buf.bytes().by_ref()).unwrap(), value);
+  }
+  match ErrorStatus::deserialize(b"\x0a\x01\x02".bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::UnknownEnumerated) => {},
+    res => panic!("Expected UnknownEnumerated, got {:?}", res),
+  }
+}
+
+#[test]
+fn enumerated_extensible() {
+  let mut buf: Vec<u8> = Vec::new();
+  ResultCode::OperationsError.serialize(&mut buf).unwrap();
+  ResultCode::Unknown(32).serialize(&mut buf).unwrap();
+  assert_eq!(&buf, b"\x4a\x01\x01\x4a\x01\x20");
+
+  let mut reader = buf.bytes();
+  assert_eq!(ResultCode::deserialize(reader.by_ref()).unwrap(), ResultCode::OperationsError);
+  assert_eq!(ResultCode::deserialize(reader.by_ref()).unwrap(), ResultCode::Unknown(32));
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Validity", form="sequence")]
+struct Validity {
+  #[asn1(time="utc")]
+  not_before: asn1_cereal::GeneralizedTime,
+  #[asn1(time="generalized")]
+  not_after: asn1_cereal::GeneralizedTime,
+}
+
+#[test]
+fn time_field_types() {
+  let validity = Validity {
+    not_before: asn1_cereal::GeneralizedTime::new(2017, 1, 1, 0, 0, 0),
+    not_after: asn1_cereal::GeneralizedTime::new(2050, 1, 1, 0, 0, 0),
+  };
+  let bytes = b"\x30\x20\x80\x0d170101000000Z\x81\x0f20500101000000Z";
+  let mut buf: Vec<u8> = Vec::new();
+  validity.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Validity::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), validity);
+
+  // A UTCTime field can't hold dates past 2049.
+  let validity = Validity {
+    not_before: asn1_cereal::GeneralizedTime::new(2050, 1, 1, 0, 0, 0),
+    ..validity
+  };
+  assert!(validity.serialize(&mut Vec::new()).is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="ChronoValidity", form="sequence")]
+struct ChronoValidity {
+  #[asn1(time="utc")]
+  not_before: chrono::DateTime<chrono::Utc>,
+  #[asn1(time="generalized")]
+  not_after: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_time_fields() {
+  use chrono::TimeZone;
+  let validity = ChronoValidity {
+    not_before: chrono::Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap(),
+    not_after: chrono::Utc.with_ymd_and_hms(2050, 1, 1, 0, 0, 0).unwrap(),
+  };
+  let bytes = b"\x30\x20\x80\x0d170101000000Z\x81\x0f20500101000000Z";
+  let mut buf: Vec<u8> = Vec::new();
+  validity.serialize_enc(DER, &mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(ChronoValidity::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), validity);
+
+  let validity = ChronoValidity {
+    not_before: chrono::Utc.with_ymd_and_hms(2050, 1, 1, 0, 0, 0).unwrap(),
+    ..validity
+  };
+  assert!(validity.serialize(&mut Vec::new()).is_err());
+}
+
+#[cfg(feature = "time")]
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="TimeValidity", form="sequence")]
+struct TimeValidity {
+  #[asn1(time="utc")]
+  not_before: time::OffsetDateTime,
+  #[asn1(time="generalized")]
+  not_after: time::OffsetDateTime,
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_time_fields() {
+  // 2017-01-01 00:00:00 UTC and 2050-01-01 00:00:00 UTC.
+  let validity = TimeValidity {
+    not_before: time::OffsetDateTime::from_unix_timestamp(1483228800).unwrap(),
+    not_after: time::OffsetDateTime::from_unix_timestamp(2524608000).unwrap(),
+  };
+  let bytes = b"\x30\x20\x80\x0d170101000000Z\x81\x0f20500101000000Z";
+  let mut buf: Vec<u8> = Vec::new();
+  validity.serialize_enc(DER, &mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(TimeValidity::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), validity);
+
+  // BER keeps the offset, which is the same instant.
+  let bytes = b"\x30\x28\xa0\x13\x17\x11170101013000+0130\xa1\x11\x18\x0f20500101000000Z";
+  let decoded = TimeValidity::deserialize(bytes.bytes().by_ref()).unwrap();
+  assert_eq!(decoded.not_before.offset().whole_minutes(), 90);
+  assert_eq!(decoded, validity);
+
+  let validity = TimeValidity {
+    not_before: validity.not_after,
+    ..validity
+  };
+  assert!(validity.serialize(&mut Vec::new()).is_err());
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="AlgorithmIdentifier", form="sequence")]
+struct AlgorithmIdentifier {
+  algorithm: u32,
+  parameters: asn1_cereal::RawElement,
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Known")]
+enum Known {
+  Int(u32),
+  Other(asn1_cereal::RawElement),
+}
+
+#[test]
+fn raw_element_fields() {
+  let alg = AlgorithmIdentifier {
+    algorithm: 1,
+    parameters: asn1_cereal::RawElement::encode(&()).unwrap(),
+  };
+  let bytes = b"\x30\x07\x80\x01\x01\xa1\x02\x05\x00";
+  let mut buf: Vec<u8> = Vec::new();
+  alg.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  let decoded = AlgorithmIdentifier::deserialize_enc(DER, buf.bytes().by_ref()).unwrap();
+  assert_eq!(decoded, alg);
+  decoded.parameters.decode::<()>().unwrap();
+
+  // An unknown CHOICE alternative is captured by the RawElement variant.
+  assert_eq!(Known::deserialize(b"\x02\x01\x05".bytes().by_ref()).unwrap(), Known::Int(5));
+  let other = Known::deserialize(b"\x01\x01\xff".bytes().by_ref()).unwrap();
+  match other {
+    Known::Other(ref raw) => assert_eq!(raw.decode::<bool>().unwrap(), true),
+    _ => panic!("Expected the Other variant, got {:?}", other),
+  }
+  buf.clear();
+  other.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x01\x01\xff");
+}
+
+#[test]
+fn choice_unknown_tag() {
+  match Value::deserialize(b"\x04\x01\x05".bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::TagTypeMismatch) => {},
+    res => panic!("Expected TagTypeMismatch, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Parameters")]
+enum Parameters {
+  Null(()),
+  Int(u32),
+  Unknown(asn1_cereal::RawElement),
+}
+
+impl asn1_cereal::DefinedBy<asn1_cereal::ObjectIdentifier> for Parameters {
+  fn decode_defined_by<E: asn1_cereal::BerEncRules>(e: E, key: &asn1_cereal::ObjectIdentifier,
+                                                   raw: asn1_cereal::RawElement)
+      -> Result<Self, asn1_cereal::err::DecodeError> {
+    static REGISTRY: std::sync::OnceLock<asn1_cereal::Registry<asn1_cereal::ObjectIdentifier, Parameters>> =
+      std::sync::OnceLock::new();
+    let registry = REGISTRY.get_or_init(|| {
+      let mut registry = asn1_cereal::Registry::new();
+      registry.register("1.2.840.113549.1.1.1".parse().unwrap(), Parameters::Null);
+      registry.register("1.2.3.4".parse().unwrap(), Parameters::Int);
+      registry
+    });
+    registry.decode(e, key, raw, Parameters::Unknown)
+  }
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="AlgorithmIdentifier", form="sequence")]
+struct TypedAlgorithmIdentifier {
+  algorithm: asn1_cereal::ObjectIdentifier,
+  #[asn1(defined_by="algorithm")]
+  parameters: Parameters,
+}
+
+#[test]
+fn defined_by_registry() {
+  let cases = vec![
+    ("1.2.840.113549.1.1.1", Parameters::Null(())),
+    ("1.2.3.4", Parameters::Int(7)),
+    ("1.2.3.5", Parameters::Unknown(asn1_cereal::RawElement::encode(&7u32).unwrap())),
+  ];
+  for (oid, parameters) in cases {
+    let alg = TypedAlgorithmIdentifier {
+      algorithm: oid.parse().unwrap(),
+      parameters: parameters,
+    };
+    let mut buf: Vec<u8> = Vec::new();
+    alg.serialize(&mut buf).unwrap();
+    assert_eq!(TypedAlgorithmIdentifier::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), alg);
+  }
+
+  // A registered key with the wrong type is an error.
+  let alg = TypedAlgorithmIdentifier {
+    algorithm: "1.2.3.4".parse().unwrap(),
+    parameters: Parameters::Null(()),
+  };
+  let mut buf: Vec<u8> = Vec::new();
+  alg.serialize(&mut buf).unwrap();
+  assert!(TypedAlgorithmIdentifier::deserialize_enc(DER, buf.bytes().by_ref()).is_err());
+  // The open type is decoded with the same rules, so DER rejects a non-minimal INTEGER.
+  let bytes = b"\x30\x0b\x80\x03\x2a\x03\x04\xa1\x04\x02\x02\x00\x07";
+  assert!(TypedAlgorithmIdentifier::deserialize_enc(DER, bytes.bytes().by_ref()).is_err());
+  let rules = asn1_cereal::EncodingRules {
+    shortest_form: false,
+    canonical: false,
+    ..asn1_cereal::EncodingRules::new(DER)
+  };
+  let alg = TypedAlgorithmIdentifier::deserialize_enc(rules, bytes.bytes().by_ref()).unwrap();
+  assert_eq!(alg.parameters, Parameters::Int(7));
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Message", form="sequence")]
+struct Message {
+  id: u32,
+  values: Vec<u32>,
+  #[asn1(optional)]
+  note: Option<String>,
+}
+
+#[test]
+fn sequence_cer() {
+  let message = Message { id: 1, values: vec![2, 3], note: None };
+  let bytes = b"\x30\x80\x80\x01\x01\xa1\x80\x02\x01\x02\x02\x01\x03\x00\x00\x00\x00";
+  let mut buf: Vec<u8> = Vec::new();
+  message.serialize_enc(asn1_cereal::CER, &mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Message::deserialize_enc(asn1_cereal::CER, buf.bytes().by_ref()).unwrap(), message);
+
+  let message = Message { note: Some("hi".to_string()), ..message };
+  buf.clear();
+  message.serialize_enc(asn1_cereal::CER, &mut buf).unwrap();
+  assert_eq!(Message::deserialize_enc(asn1_cereal::CER, buf.bytes().by_ref()).unwrap(), message);
+
+  // CER doesn't allow definite length encoding of constructed elements.
+  buf.clear();
+  message.serialize(&mut buf).unwrap();
+  match Message::deserialize_enc(asn1_cereal::CER, buf.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::DefiniteLen) => {},
+    res => panic!("Expected DefiniteLen, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Record", form="sequence")]
+struct Record {
+  #[asn1(tag="[APPLICATION 5]")]
+  id: u32,
+  count: u32,
+}
+
+#[test]
+fn sequence_field_tags() {
+  let record = Record { id: 7, count: 2 };
+  let bytes = b"\x30\x06\x45\x01\x07\x81\x01\x02";
+  let mut buf: Vec<u8> = Vec::new();
+  record.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Record::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), record);
+
+  let bytes = b"\x30\x0a\x65\x03\x02\x01\x07\xa1\x03\x02\x01\x02";
+  buf.clear();
+  record.serialize_enc(asn1_cereal::BER, &mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Record::deserialize(buf.bytes().by_ref()).unwrap(), record);
+
+  // The component isn't numbered by its position when it has a tag.
+  let bytes = b"\x30\x06\x80\x01\x07\x81\x01\x02";
+  match Record::deserialize_enc(DER, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::TagTypeMismatch) => {},
+    res => panic!("Expected TagTypeMismatch, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Wrapped", tag="[APPLICATION 3]")]
+struct Wrapped(u32);
+
+#[test]
+fn alias_explicit_indefinite() {
+  let rules = asn1_cereal::EncodingRules {
+    tags: asn1_cereal::ber::enc::TagEnc::Explicit,
+    lengths: asn1_cereal::ber::enc::LenEnc::Indefinite,
+    .. asn1_cereal::EncodingRules::new(asn1_cereal::BER)
+  };
+  let values = vec![Wrapped(1), Wrapped(2)];
+  let bytes = b"\x30\x80\x63\x80\x02\x01\x01\x00\x00\x63\x80\x02\x01\x02\x00\x00\x00\x00";
+  let mut buf: Vec<u8> = Vec::new();
+  values.serialize_enc(rules, &mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  // The end-of-contents octets of each explicit tag are read.
+  assert_eq!(Vec::<Wrapped>::deserialize_enc(rules, buf.bytes().by_ref()).unwrap(), values);
+
+  let mut reader = b"\x63\x80\x02\x01\x01\x00\x00\x02\x01\x05".bytes();
+  assert_eq!(Wrapped::deserialize_enc(rules, reader.by_ref()).unwrap(), Wrapped(1));
+  assert_eq!(u32::deserialize_enc(rules, reader.by_ref()).unwrap(), 5);
+}
+
+#[test]
+fn sequence_lenient() {
+  let message = Message { id: 1, values: vec![2], note: None };
+  let deviations = Cell::new(0);
+  let count = |_: Deviation| deviations.set(deviations.get() + 1);
+  let rules = Lenient::new(DER, &count);
+
+  // The id component has the constructed bit set.
+  let bytes = b"\x30\x08\xa0\x01\x01\xa1\x03\x02\x01\x02";
+  match Message::deserialize_enc(DER, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::TagTypeMismatch) => {},
+    res => panic!("Expected TagTypeMismatch, got {:?}", res),
+  }
+  assert_eq!(Message::deserialize_enc(rules, bytes.bytes().by_ref()).unwrap(), message);
+  assert_eq!(deviations.get(), 1);
+
+  // A NULL follows the last component, and the following element is still
+  // read after skipping it.
+  let bytes = b"\x30\x0a\x80\x01\x01\xa1\x03\x02\x01\x02\x05\x00\x02\x01\x07";
+  match Message::deserialize_enc(DER, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::SmallerLen) => {},
+    res => panic!("Expected SmallerLen, got {:?}", res),
+  }
+  let mut reader = bytes.bytes();
+  assert_eq!(Message::deserialize_enc(rules, reader.by_ref()).unwrap(), message);
+  assert_eq!(u32::deserialize_enc(rules, reader.by_ref()).unwrap(), 7);
+  assert_eq!(deviations.get(), 2);
+
+  let rules = rules.only(&[Deviation::ConstructedBit]);
+  assert!(Message::deserialize_enc(rules, bytes.bytes().by_ref()).is_err());
+}
+
+#[test]
+fn choice_set_lenient() {
+  let deviations = Cell::new(0);
+  let count = |_: Deviation| deviations.set(deviations.get() + 1);
+  let rules = Lenient::new(DER, &count);
+
+  // CHOICE alternatives with the constructed bit set.
+  for &(bytes, ref value) in &[(&b"\x22\x01\x05"[..], Value::Int(5)), (&b"\xa1\x00"[..], Value::NoSuchInstance)] {
+    match Value::deserialize_enc(DER, bytes.bytes().by_ref()) {
+      Err(asn1_cereal::err::DecodeError::TagTypeMismatch) => {},
+      res => panic!("Expected TagTypeMismatch, got {:?}", res),
+    }
+    assert_eq!(&Value::deserialize_enc(rules, bytes.bytes().by_ref()).unwrap(), value);
+  }
+  assert_eq!(deviations.get(), 2);
+
+  // Named bits with the constructed bit set, which DER doesn't allow for the
+  // constructed form.
+  let bytes = b"\x23\x02\x07\x80";
+  assert!(KeyUsage::deserialize_enc(DER, bytes.bytes().by_ref()).is_err());
+  assert_eq!(KeyUsage::deserialize_enc(rules, bytes.bytes().by_ref()).unwrap(),
+             KeyUsage { digital_signature: true, ..KeyUsage::default() });
+  assert_eq!(deviations.get(), 3);
+
+  // A SET component with the constructed bit set, followed by an unknown
+  // element.
+  let person = Person { id: 5, name: "ab".to_string(), age: Some(30) };
+  let bytes = b"\x31\x0c\xa0\x02ab\x81\x01\x05\x82\x01\x1e\x85\x00";
+  match Person::deserialize_enc(DER, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::TagTypeMismatch) => {},
+    res => panic!("Expected TagTypeMismatch, got {:?}", res),
+  }
+  let mut reader = bytes.bytes().chain(b"\x02\x01\x07".bytes());
+  assert_eq!(Person::deserialize_enc(rules, reader.by_ref()).unwrap(), person);
+  assert_eq!(u32::deserialize_enc(rules, reader.by_ref()).unwrap(), 7);
+  assert_eq!(deviations.get(), 5);
+}
+
+#[derive(Asn1Info, Asn1Schema, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Ports", form="set of")]
+struct Ports(Vec<u16>);
+
+impl<'a> IntoIterator for &'a Ports {
+  type Item = &'a u16;
+  type IntoIter = std::slice::Iter<'a, u16>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.0.iter()
+  }
+}
+
+impl std::iter::FromIterator<u16> for Ports {
+  fn from_iter<I: IntoIterator<Item=u16>>(iter: I) -> Self {
+    Ports(iter.into_iter().collect())
+  }
+}
+
+#[test]
+fn set_of_sorted() {
+  let ports = Ports(vec![443, 80, 22]);
+  let bytes = b"\x31\x0a\x02\x01\x16\x02\x01\x50\x02\x02\x01\xbb";
+  let mut buf: Vec<u8> = Vec::new();
+  ports.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Ports::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), Ports(vec![22, 80, 443]));
+
+  // BER keeps the original order, but DER requires sorted elements.
+  buf.clear();
+  ports.serialize_enc(asn1_cereal::BER, &mut buf).unwrap();
+  assert_eq!(Ports::deserialize(buf.bytes().by_ref()).unwrap(), ports);
+  match Ports::deserialize_enc(DER, buf.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::UnsortedSet) => {},
+    res => panic!("Expected UnsortedSet, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, XerSerialize, XerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Person", form="set")]
+struct Person {
+  #[asn1(tag="[CONTEXT 1]")]
+  id: u32,
+  #[asn1(tag="[CONTEXT 0]")]
+  name: String,
+  #[asn1(optional)]
+  age: Option<u32>,
+}
+
+#[test]
+fn set_any_order() {
+  let person = Person { id: 5, name: "ab".to_string(), age: Some(30) };
+  // DER sorts the components by tag.
+  let bytes = b"\x31\x0a\x80\x02ab\x81\x01\x05\x82\x01\x1e";
+  let mut buf: Vec<u8> = Vec::new();
+  person.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], &bytes[..]);
+  assert_eq!(Person::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), person);
+
+  buf.clear();
+  person.serialize_enc(asn1_cereal::BER, &mut buf).unwrap();
+  assert_eq!(Person::deserialize_enc(asn1_cereal::BER, buf.bytes().by_ref()).unwrap(), person);
+
+  // Without canonical encoding rules, components can be in any order.
+  let bytes = b"\x31\x07\x81\x01\x05\x80\x02ab";
+  let person = Person { id: 5, name: "ab".to_string(), age: None };
+  assert_eq!(Person::deserialize_enc(asn1_cereal::BERAlt, bytes.bytes().by_ref()).unwrap(), person);
+  match Person::deserialize_enc(DER, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::UnsortedSet) => {},
+    res => panic!("Expected UnsortedSet, got {:?}", res),
+  }
+
+  let bytes = b"\x31\x0a\x80\x02ab\x81\x01\x05\x81\x01\x06";
+  match Person::deserialize_enc(asn1_cereal::BERAlt, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::DuplicateComponent) => {},
+    res => panic!("Expected DuplicateComponent, got {:?}", res),
+  }
+  let bytes = b"\x31\x04\x80\x02ab";
+  match Person::deserialize_enc(asn1_cereal::BERAlt, bytes.bytes().by_ref()) {
+    Err(asn1_cereal::err::DecodeError::MissingComponent) => {},
+    res => panic!("Expected MissingComponent, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, Asn1Schema, PerSerialize, PerDeserialize, OerSerialize, OerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Reading", extensible)]
+struct Reading {
+  #[asn1(range="0..255")]
+  sensor: u8,
+  #[asn1(optional, size="1..8")]
+  label: Option<String>,
+  #[asn1(range="-10..10")]
+  delta: i8,
+}
+
+#[derive(Asn1Info, Asn1Schema, PerSerialize, PerDeserialize, OerSerialize, OerDeserialize, XerSerialize,
+         XerDeserialize, JerSerialize, JerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Colour", form="enumerated", extensible)]
+enum Colour {
+  Red,
+  #[asn1(value=5)]
+  Green,
+  Blue = 2,
+}
+
+#[derive(Asn1Info, Asn1Schema, PerSerialize, PerDeserialize, OerSerialize, OerDeserialize, XerSerialize,
+         XerDeserialize, JerSerialize, JerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Measurement")]
+enum Measurement {
+  Label(String),
+  Count(u32),
+}
+
+#[derive(Asn1Info, Asn1Schema, PerSerialize, PerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Percent", range="0..100")]
+struct Percent(u8);
+
+#[test]
+fn per_constraints() {
+  use asn1_cereal::{PerSerialize, PerDeserialize, UPER};
+
+  let reading = Reading { sensor: 200, label: Some("ab".to_string()), delta: -3 };
+  let mut buf: Vec<u8> = Vec::new();
+  reading.per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x72\x0e\x1c\x47");
+  assert_eq!(Reading::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), reading);
+  buf.clear();
+  reading.per_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x40\xc8\x20\x61\x62\x38");
+  assert_eq!(Reading::per_deserialize(buf.bytes().by_ref()).unwrap(), reading);
+
+  // ENUMERATED values are encoded as their index, in order of their values.
+  buf.clear();
+  Colour::Green.per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x40");
+  assert_eq!(Colour::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), Colour::Green);
+
+  // CHOICE alternatives are numbered in the order of their tags.
+  buf.clear();
+  Measurement::Label("a".to_string()).per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x80\xe1");
+  assert_eq!(Measurement::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(),
+             Measurement::Label("a".to_string()));
+
+  buf.clear();
+  Percent(50).per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x64");
+  assert_eq!(Percent::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), Percent(50));
+  match Percent(101).per_serialize_enc(UPER, &mut Vec::new()) {
+    Err(_) => {},
+    res => panic!("Expected a constraint error, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, Asn1Schema, PerSerialize, PerDeserialize, OerSerialize, OerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Heading")]
+struct Heading {
+  #[asn1(default, range="0..359")]
+  degrees: u16,
+  #[asn1(optional)]
+  confident: Option<bool>,
+}
+
+#[test]
+fn per_default() {
+  use asn1_cereal::{PerSerialize, PerDeserialize, UPER};
+
+  // A DEFAULT component has a preamble bit, and is left out when it has the
+  // default value.
+  let cases: Vec<(Heading, &[u8])> = vec![
+    (Heading { degrees: 90, confident: None }, b"\x8b\x40"),
+    (Heading { degrees: 0, confident: Some(true) }, b"\x60"),
+  ];
+  for (heading, bytes) in cases {
+    let mut buf: Vec<u8> = Vec::new();
+    heading.per_serialize_enc(UPER, &mut buf).unwrap();
+    assert_eq!(&buf[..], bytes);
+    assert_eq!(Heading::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), heading);
+  }
+}
+
+#[test]
+fn oer_preamble() {
+  use asn1_cereal::{OerSerialize, OerDeserialize, COER};
+
+  let reading = Reading { sensor: 200, label: Some("ab".to_string()), delta: -3 };
+  let mut buf: Vec<u8> = Vec::new();
+  reading.oer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x40\xc8\x02ab\xfd");
+  assert_eq!(Reading::oer_deserialize_enc(COER, buf.bytes().by_ref()).unwrap(), reading);
+
+  // ENUMERATED values are encoded as their value.
+  buf.clear();
+  Colour::Green.oer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x05");
+  assert_eq!(Colour::oer_deserialize(buf.bytes().by_ref()).unwrap(), Colour::Green);
+
+  // CHOICE alternatives are preceded by their tag.
+  buf.clear();
+  Measurement::Label("a".to_string()).oer_serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x13\x01a");
+  assert_eq!(Measurement::oer_deserialize(buf.bytes().by_ref()).unwrap(), Measurement::Label("a".to_string()));
+
+  // A DEFAULT component is left out when it has the default value.
+  let cases: Vec<(Heading, &[u8])> = vec![
+    (Heading { degrees: 90, confident: None }, b"\x80\x00\x5a"),
+    (Heading { degrees: 0, confident: Some(true) }, b"\x40\xff"),
+  ];
+  for (heading, bytes) in cases {
+    buf.clear();
+    heading.oer_serialize_enc(COER, &mut buf).unwrap();
+    assert_eq!(&buf[..], bytes);
+    assert_eq!(Heading::oer_deserialize_enc(COER, buf.bytes().by_ref()).unwrap(), heading);
+  }
+  let explicit = b"\xc0\x00\x00\xff";
+  assert_eq!(Heading::oer_deserialize(explicit.bytes().by_ref()).unwrap(),
+             Heading { degrees: 0, confident: Some(true) });
+  assert!(Heading::oer_deserialize_enc(COER, explicit.bytes().by_ref()).is_err());
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, PerSerialize, PerDeserialize, OerSerialize, OerDeserialize,
+         JerSerialize, JerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Position", form="sequence")]
+struct Position {
+  #[asn1(default="4")]
+  precision: u8,
+  #[asn1(default="String::from(\"WGS84\")")]
+  datum: String,
+}
+
+#[derive(Asn1Info, BerSerialize, BerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Options", form="set")]
+struct Options {
+  #[asn1(default="true")]
+  enabled: bool,
+  #[asn1(optional)]
+  limit: Option<u32>,
+}
+
+#[test]
+fn default_values() {
+  use asn1_cereal::{PerSerialize, PerDeserialize, UPER, OerSerialize, OerDeserialize, COER};
+  use asn1_cereal::JerDeserialize;
+
+  // Components with their DEFAULT value are left out.
+  let position = Position { precision: 4, datum: "WGS84".to_string() };
+  let mut buf: Vec<u8> = Vec::new();
+  position.per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x00");
+  assert_eq!(Position::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), position);
+  buf.clear();
+  position.oer_serialize_enc(COER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x00");
+  assert_eq!(Position::oer_deserialize_enc(COER, buf.bytes().by_ref()).unwrap(), position);
+  assert_eq!(Position::jer_deserialize(b"{}".bytes().by_ref()).unwrap(), position);
+
+  // A component with the Rust default value isn't the DEFAULT value.
+  let position = Position { precision: 0, datum: "WGS84".to_string() };
+  buf.clear();
+  position.oer_serialize_enc(COER, &mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x80\x01\x00");
+  assert_eq!(Position::oer_deserialize_enc(COER, buf.bytes().by_ref()).unwrap(), position);
+  buf.clear();
+  position.per_serialize_enc(UPER, &mut buf).unwrap();
+  assert_eq!(Position::per_deserialize_enc(UPER, buf.bytes().by_ref()).unwrap(), position);
+
+  // DER leaves out components with their DEFAULT value, and they can be absent in BER.
+  let position = Position { precision: 4, datum: "WGS84".to_string() };
+  buf.clear();
+  position.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x30\x00");
+  assert_eq!(Position::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), position);
+  assert_eq!(Position::deserialize(b"\x30\x00".bytes().by_ref()).unwrap(), position);
+  let position = Position { precision: 0, .. position };
+  buf.clear();
+  position.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x30\x03\x80\x01\x00");
+  assert_eq!(Position::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), position);
+  let bytes = b"\x30\x08\xa1\x06\x13\x04ETRS";
+  let position = Position { precision: 4, datum: "ETRS".to_string() };
+  assert_eq!(Position::deserialize(bytes.bytes().by_ref()).unwrap(), position);
+
+  let options = Options { enabled: true, limit: None };
+  buf.clear();
+  options.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x31\x00");
+  assert_eq!(Options::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), options);
+  let options = Options { enabled: false, limit: Some(3) };
+  buf.clear();
+  options.serialize(&mut buf).unwrap();
+  assert_eq!(&buf[..], b"\x31\x06\x80\x01\x00\x81\x01\x03");
+  assert_eq!(Options::deserialize_enc(DER, buf.bytes().by_ref()).unwrap(), options);
+}
+
+#[derive(Asn1Info, Asn1Schema, XerSerialize, XerDeserialize, JerSerialize, JerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Sensor-Report")]
+struct SensorReport {
+  #[asn1(name="sensorId")]
+  sensor_id: u32,
+  #[asn1(optional)]
+  label: Option<String>,
+  colour: Colour,
+  reading: Measurement,
+}
+
+#[test]
+fn xer_field_names() {
+  use asn1_cereal::{Asn1Info, XerSerialize, XerDeserialize};
+
+  assert_eq!(SensorReport::asn1_fields(), &["sensorId", "label", "colour", "reading"]);
+  let report = SensorReport {
+    sensor_id: 7,
+    label: Some("a&b".to_string()),
+    colour: Colour::Green,
+    reading: Measurement::Count(3),
+  };
+  let xml = "<Sensor-Report><sensorId>7</sensorId><label>a&amp;b</label><colour><Green/></colour>\
+             <reading><Count>3</Count></reading></Sensor-Report>";
+  let mut buf: Vec<u8> = Vec::new();
+  report.xer_serialize(&mut buf).unwrap();
+  assert_eq!(String::from_utf8(buf).unwrap(), xml);
+  assert_eq!(SensorReport::xer_deserialize(xml.bytes().map(Ok).by_ref()).unwrap(), report);
+
+  // Whitespace between elements is ignored, and OPTIONAL elements can be left out.
+  let xml = "<?xml version=\"1.0\"?>\n<Sensor-Report>\n  <sensorId>7</sensorId>\n  <colour><Green/></colour>\n  \
+             <reading><Count>3</Count></reading>\n</Sensor-Report>\n";
+  let report = SensorReport { label: None, .. report };
+  assert_eq!(SensorReport::xer_deserialize(xml.bytes().map(Ok).by_ref()).unwrap(), report);
+
+  // The components of a SEQUENCE must be in order, but not those of a SET.
+  let xml = "<Sensor-Report><colour><Green/></colour><sensorId>7</sensorId>\
+             <reading><Count>3</Count></reading></Sensor-Report>";
+  match SensorReport::xer_deserialize(xml.bytes().map(Ok).by_ref()) {
+    Err(asn1_cereal::err::DecodeError::InvalidXml) => {},
+    res => panic!("Expected InvalidXml, got {:?}", res),
+  }
+  let person = Person { id: 5, name: "Ann".to_string(), age: None };
+  let xml = "<Person><name>Ann</name><id>5</id></Person>";
+  assert_eq!(Person::xer_deserialize(xml.bytes().map(Ok).by_ref()).unwrap(), person);
+
+  let xml = "<Sensor-Report><sensorId>7</sensorId><colour><Purple/></colour></Sensor-Report>";
+  match SensorReport::xer_deserialize(xml.bytes().map(Ok).by_ref()) {
+    Err(asn1_cereal::err::DecodeError::UnknownEnumerated) => {},
+    res => panic!("Expected UnknownEnumerated, got {:?}", res),
+  }
+}
+
+#[derive(Asn1Info, JerSerialize, JerDeserialize, Debug, PartialEq)]
+#[asn1(asn1_type="Firmware", extensible)]
+struct Firmware {
+  #[asn1(default)]
+  version: u8,
+  flags: asn1_cereal::BitString,
+  #[asn1(size="8")]
+  mask: asn1_cereal::BitString,
+}
+
+#[test]
+fn jer_objects() {
+  use asn1_cereal::{JerSerialize, JerDeserialize, BitString};
+
+  let report = SensorReport {
+    sensor_id: 7,
+    label: Some("a\"b".to_string()),
+    colour: Colour::Green,
+    reading: Measurement::Count(3),
+  };
+  let json = r#"{"sensorId":7,"label":"a\"b","colour":"Green","reading":{"Count":3}}"#;
+  let mut buf: Vec<u8> = Vec::new();
+  report.jer_serialize(&mut buf).unwrap();
+  assert_eq!(String::from_utf8(buf).unwrap(), json);
+  assert_eq!(SensorReport::jer_deserialize(json.bytes().map(Ok).by_ref()).unwrap(), report);
+
+  // Members can be in any order, and OPTIONAL members can be left out.
+  let json = r#" { "reading": {"Label": "x"}, "colour": "Red", "sensorId": 7 } "#;
+  let report = SensorReport { label: None, colour: Colour::Red, reading: Measurement::Label("x".to_string()),
+                              .. report };
+  assert_eq!(SensorReport::jer_deserialize(json.bytes().map(Ok).by_ref()).unwrap(), report);
+
+  let json = r#"{"sensorId":7,"colour":"Purple","reading":{"Count":3}}"#;
+  match SensorReport::jer_deserialize(json.bytes().map(Ok).by_ref()) {
+    Err(asn1_cereal::err::DecodeError::UnknownEnumerated) => {},
+    res => panic!("Expected UnknownEnumerated, got {:?}", res),
+  }
+  let json = r#"{"sensorId":7,"colour":"Red","reading":{"Count":3},"extra":1}"#;
+  assert!(SensorReport::jer_deserialize(json.bytes().map(Ok).by_ref()).is_err());
+
+  let mut flags = BitString::from(vec![0x80]);
+  flags.resize(2);
+  let firmware = Firmware {
+    version: 0,
+    flags: flags,
+    mask: BitString::from(vec![0x0f]),
+  };
+  let json = r#"{"version":0,"flags":{"value":"80","length":2},"mask":"0F"}"#;
+  let mut buf: Vec<u8> = Vec::new();
+  firmware.jer_serialize(&mut buf).unwrap();
+  assert_eq!(String::from_utf8(buf).unwrap(), json);
+
+  // A DEFAULT member can be left out, and unknown members of an extensible
+  // type are skipped.
+  let json = r#"{"flags":{"length":2,"value":"80"},"mask":"0F","notes":[{"a":null}]}"#;
+  assert_eq!(Firmware::jer_deserialize(json.bytes().map(Ok).by_ref()).unwrap(), firmware);
+}
+
+#[test]
+fn schema_descriptors() {
+  use asn1_cereal::Asn1Schema;
+  use asn1_cereal::schema::Kind;
+  use asn1_cereal::constraint::Range;
+  use asn1_cereal::tag::{Tag, Class};
+
+  let schema = SensorReport::asn1_schema();
+  assert_eq!(schema.kind, Kind::Sequence);
+  assert_eq!(schema.asn1_type, "Sensor-Report");
+  let names: Vec<_> = schema.components.iter().map(|c| c.name).collect();
+  assert_eq!(names, vec!["sensorId", "label", "colour", "reading"]);
+  let label = schema.component("label").unwrap();
+  assert!(label.optional);
+  assert_eq!(label.tag, Some(Tag { class: Class::ContextSpecific, tagnum: 1, constructed: true }));
+
+  // The schema of each component can be followed.
+  let colour = (schema.component("colour").unwrap().schema.unwrap())();
+  assert_eq!(colour.kind, Kind::Enumerated);
+  assert!(colour.constraints.extensible);
+  let values: Vec<_> = colour.components.iter().map(|c| (c.name, c.value.unwrap())).collect();
+  assert_eq!(values, vec![("Red", 0), ("Green", 5), ("Blue", 2)]);
+  let reading = (schema.component("reading").unwrap().schema.unwrap())();
+  assert_eq!(reading.kind, Kind::Choice);
+  assert_eq!((reading.components[1].schema.unwrap())().asn1_type, "INTEGER");
+
+  let schema = Reading::asn1_schema();
+  assert_eq!(schema.component("sensor").unwrap().constraints.value, Some(Range::new(Some(0), Some(255))));
+  assert_eq!(schema.component("label").unwrap().constraints.size, Some(Range::new(Some(1), Some(8))));
+  assert!(Heading::asn1_schema().component("degrees").unwrap().default);
+
+  assert_eq!(Percent::asn1_schema().kind, Kind::Alias);
+  assert_eq!(Percent::asn1_schema().constraints.value, Some(Range::new(Some(0), Some(100))));
+
+  let ports = Ports::asn1_schema();
+  assert_eq!(ports.kind, Kind::SetOf);
+  assert_eq!((ports.components[0].schema.unwrap())().asn1_type, "INTEGER");
+
+  let bits = KeyUsage::asn1_schema();
+  assert_eq!(bits.kind, Kind::NamedBits);
+  assert_eq!(bits.component("key_cert_sign").unwrap().value, Some(5));
+}
@@ -3,13 +3,12 @@ extern crate argparse;
 extern crate serde;
 extern crate serde_json;
 
-use asn1_cereal::{tag, byte};
+use asn1_cereal::{tag, byte, ObjectIdentifier};
 use asn1_cereal::ber::stream;
 
 // SNMP ASN.1 Definition
 // https://tools.ietf.org/html/rfc1157#page-30
 
-type ObjectIdentifier = u64;
 type NetworkAddress = u64;
 type ObjectName = String;
 